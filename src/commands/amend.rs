@@ -1,7 +1,7 @@
 use clap::Args;
 use colored::Colorize;
 
-use crate::git;
+use crate::{config, git};
 
 #[derive(Args)]
 pub struct AmendArgs {
@@ -12,24 +12,39 @@ pub struct AmendArgs {
     /// Edit the commit message
     #[arg(short, long)]
     pub edit: bool,
+
+    /// Replace only the commit message, leaving staged changes out of the commit
+    #[arg(short, long, value_name = "MSG", conflicts_with_all = ["all", "edit"])]
+    pub reword: Option<String>,
 }
 
 pub fn run(args: AmendArgs) -> i32 {
+    if git::capture(&["rev-parse", "--verify", "--quiet", "HEAD"]).is_err() {
+        eprintln!("gg: No commits to amend");
+        return 1;
+    }
+
     // Optionally stage all changes
     if args.all {
-        println!("Running: {}", "git add -A".bold());
+        if !config::is_quiet() {
+            println!("Running: {}", "git add -A".bold());
+        }
         if git::run(&["add", "-A"]) != 0 {
             return 1;
         }
     }
 
-    // Amend the commit
-    let amend_args = if args.edit {
+    // Amend the commit. --only with no paths leaves the index out of the commit.
+    let amend_args = if let Some(message) = &args.reword {
+        vec!["commit", "--amend", "--only", "-m", message]
+    } else if args.edit {
         vec!["commit", "--amend"]
     } else {
         vec!["commit", "--amend", "--no-edit"]
     };
 
-    println!("Running: {}", format!("git {}", amend_args.join(" ")).bold());
+    if !config::is_quiet() {
+        println!("Running: {}", format!("git {}", amend_args.join(" ")).bold());
+    }
     git::run(&amend_args)
 }
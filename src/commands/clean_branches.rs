@@ -1,14 +1,22 @@
 use clap::Args;
 use colored::Colorize;
 
-use crate::git;
-use crate::utils::{get_main_branch_name, get_repo};
+use crate::{config, git};
+use crate::utils::{get_branch_name, get_main_branch_name, get_repo};
 
 #[derive(Args)]
 pub struct CleanBranchesArgs {
     /// Actually delete branches (dry-run by default)
     #[arg(short, long)]
     pub force: bool,
+
+    /// Delete branches whose upstream branch is gone from the remote instead of merged ones
+    #[arg(long)]
+    pub gone: bool,
+
+    /// Run 'git remote prune origin' first to drop stale remote-tracking branches
+    #[arg(long)]
+    pub remote: bool,
 }
 
 pub fn run(args: CleanBranchesArgs) -> i32 {
@@ -24,19 +32,46 @@ pub fn run(args: CleanBranchesArgs) -> i32 {
 fn run_inner(args: CleanBranchesArgs) -> Result<i32, Box<dyn std::error::Error>> {
     let repo = get_repo()?;
     let main_branch = get_main_branch_name(&repo);
+    let current_branch = get_branch_name(&repo);
 
-    // Get list of merged branches
-    let merged_output = git::capture(&["branch", "--merged", main_branch])?;
+    // Pruning updates the [gone] markers that --gone relies on
+    if args.remote {
+        if !config::is_quiet() {
+            println!("Running: {}", "git remote prune origin".bold());
+        }
+        let code = git::run(&["remote", "prune", "origin"]);
+        if code != 0 {
+            return Ok(code);
+        }
+        if !config::is_quiet() {
+            println!();
+        }
+    }
 
-    let branches_to_delete: Vec<&str> = merged_output
-        .lines()
-        .map(|line| line.trim().trim_start_matches("* "))
-        .filter(|branch| !branch.is_empty())
-        .filter(|branch| *branch != "main" && *branch != "master" && *branch != main_branch)
+    let (kind, candidates) = if args.gone {
+        ("gone", find_gone_branches(&repo)?)
+    } else {
+        ("merged", find_merged_branches(&main_branch)?)
+    };
+
+    let branches_to_delete: Vec<String> = candidates
+        .into_iter()
+        .filter(|branch| !is_protected(branch, &main_branch))
+        .filter(|branch| {
+            let is_current = current_branch.as_deref() == Some(branch.as_str());
+            if is_current {
+                eprintln!(
+                    "{} Skipping {} - it is the current branch. Switch branches to delete it.",
+                    "Warning:".yellow(),
+                    branch
+                );
+            }
+            !is_current
+        })
         .collect();
 
     if branches_to_delete.is_empty() {
-        println!("No merged branches to clean up.");
+        println!("No {} branches to clean up.", kind);
         return Ok(0);
     }
 
@@ -50,14 +85,79 @@ fn run_inner(args: CleanBranchesArgs) -> Result<i32, Box<dyn std::error::Error>>
         return Ok(0);
     }
 
-    println!("{}", "Deleting merged branches:".bold());
-    for branch in branches_to_delete {
+    // -d, so git keeps branches with commits that would be lost
+    println!("{}", format!("Deleting {} branches:", kind).bold());
+    let mut refused = Vec::new();
+    for branch in &branches_to_delete {
         println!("  Deleting: {}", branch.red());
-        let code = git::run(&["branch", "-d", branch]);
-        if code != 0 {
-            eprintln!("  Failed to delete {}", branch);
+        if git::run(&["branch", "-d", branch]) != 0 {
+            refused.push(branch);
+        }
+    }
+
+    if !refused.is_empty() {
+        eprintln!();
+        eprintln!("{} Kept {} branch(es) that aren't fully merged:", "Warning:".yellow(), refused.len());
+        for branch in &refused {
+            eprintln!("  {}", branch);
+        }
+        if args.gone {
+            eprintln!("Gone branches are often squash-merged. Check each one, then delete it with 'git branch -D <branch>'.");
         }
     }
 
     Ok(0)
 }
+
+/// Branches that must never be deleted
+fn is_protected(branch: &str, main_branch: &str) -> bool {
+    matches!(branch, "main" | "master" | "HEAD") || branch == main_branch
+}
+
+/// Local branches merged into the main branch
+fn find_merged_branches(main_branch: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let merged_output = git::capture(&["branch", "--merged", main_branch])?;
+
+    Ok(merged_output
+        .lines()
+        .map(|line| line.trim().trim_start_matches("* "))
+        .filter(|branch| !branch.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// Local branches with an upstream configured whose remote-tracking ref no
+/// longer exists (shown as `[gone]` by `git branch -vv`)
+fn find_gone_branches(repo: &git2::Repository) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut gone = Vec::new();
+
+    for branch in repo.branches(Some(git2::BranchType::Local))? {
+        let (branch, _) = branch?;
+        let Some(name) = branch.name()?.map(String::from) else { continue };
+        let Ok(upstream) = repo.branch_upstream_name(&format!("refs/heads/{}", name)) else {
+            continue;
+        };
+        let Some(upstream) = upstream.as_str() else { continue };
+
+        if repo.find_reference(upstream).is_err() {
+            gone.push(name);
+        }
+    }
+
+    gone.sort();
+    Ok(gone)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_protected() {
+        assert!(is_protected("main", "develop"));
+        assert!(is_protected("master", "main"));
+        assert!(is_protected("HEAD", "main"));
+        assert!(is_protected("develop", "develop"));
+        assert!(!is_protected("feature", "main"));
+    }
+}
@@ -0,0 +1,160 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use clap::Args;
+use colored::Colorize;
+
+use crate::{config, git};
+use crate::lfs::Scanner;
+
+#[derive(Args)]
+pub struct CloneArgs {
+    /// Repository URL to clone
+    pub url: String,
+
+    /// Directory to clone into (default: derived from the URL)
+    pub directory: Option<String>,
+
+    /// Don't install LFS hooks or pull LFS files after cloning
+    #[arg(long)]
+    pub no_lfs: bool,
+
+    /// Extra arguments passed to git clone (after --)
+    #[arg(last = true)]
+    pub git_args: Vec<String>,
+}
+
+pub fn run(args: CloneArgs) -> i32 {
+    match run_inner(args) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("gg: {}", e);
+            1
+        }
+    }
+}
+
+fn run_inner(args: CloneArgs) -> Result<i32, Box<dyn std::error::Error>> {
+    let directory = match &args.directory {
+        Some(dir) => dir.clone(),
+        None => directory_from_url(&args.url).ok_or("Could not determine directory from URL")?,
+    };
+
+    let mut clone_args: Vec<&str> = vec!["clone"];
+    clone_args.extend(args.git_args.iter().map(String::as_str));
+    clone_args.push(&args.url);
+    clone_args.push(&directory);
+
+    if !config::is_quiet() {
+        println!("Running: {}", format!("git {}", clone_args.join(" ")).bold());
+    }
+    let code = git::run(&clone_args);
+    if code != 0 || args.no_lfs {
+        return Ok(code);
+    }
+
+    let repo_root = PathBuf::from(&directory);
+    let uses_lfs = Scanner::new(&repo_root)
+        .map(|scanner| !scanner.patterns().is_empty())
+        .unwrap_or(false);
+    if !uses_lfs {
+        return Ok(0);
+    }
+
+    // The fresh clone has pointer files checked out and no filter driver yet
+    if !config::is_quiet() {
+        println!();
+        println!("Running: {}", "gg lfs install".bold());
+    }
+    if run_gg(&repo_root, &["lfs", "install"])? != 0 {
+        eprintln!(
+            "{} Could not install LFS hooks. Run 'gg lfs install' in {}.",
+            "Warning:".yellow(),
+            directory
+        );
+        return Ok(0);
+    }
+
+    if !config::is_quiet() {
+        println!();
+        println!("Running: {}", "gg lfs pull".bold());
+    }
+    if run_gg(&repo_root, &["lfs", "pull"])? != 0 {
+        eprintln!(
+            "{} Could not pull LFS files. Configure .gg/lfs.toml and run 'gg lfs pull' in {}.",
+            "Warning:".yellow(),
+            directory
+        );
+    }
+
+    // The clone itself succeeded, so LFS problems are only warnings
+    Ok(0)
+}
+
+/// Run this gg binary inside the cloned repository
+fn run_gg(repo_root: &Path, args: &[&str]) -> Result<i32, Box<dyn std::error::Error>> {
+    let mut command = Command::new(std::env::current_exe()?);
+    if config::is_quiet() {
+        command.arg("--quiet");
+    }
+    let status = command
+        .args(args)
+        .current_dir(repo_root)
+        .status()?;
+    Ok(status.code().unwrap_or(1))
+}
+
+/// Derive the directory git would clone into ("host:user/repo.git" -> "repo")
+fn directory_from_url(url: &str) -> Option<String> {
+    let trimmed = url.trim().trim_end_matches('/');
+    let trimmed = trimmed.strip_suffix(".git").unwrap_or(trimmed);
+    let name = trimmed.rsplit(['/', ':', '\\']).next()?;
+
+    if name.is_empty() {
+        None
+    } else {
+        Some(name.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_directory_from_url_https() {
+        assert_eq!(
+            directory_from_url("https://github.com/user/repo.git"),
+            Some("repo".to_string())
+        );
+    }
+
+    #[test]
+    fn test_directory_from_url_ssh() {
+        assert_eq!(
+            directory_from_url("git@github.com:user/repo.git"),
+            Some("repo".to_string())
+        );
+    }
+
+    #[test]
+    fn test_directory_from_url_trailing_slash() {
+        assert_eq!(
+            directory_from_url("https://gitlab.com/user/repo/"),
+            Some("repo".to_string())
+        );
+    }
+
+    #[test]
+    fn test_directory_from_url_local_path() {
+        assert_eq!(
+            directory_from_url("/tmp/projects/my-repo"),
+            Some("my-repo".to_string())
+        );
+    }
+
+    #[test]
+    fn test_directory_from_url_empty() {
+        assert_eq!(directory_from_url(""), None);
+    }
+}
@@ -0,0 +1,118 @@
+//! Stream an LFS object's real content to stdout
+//!
+//! Resolves a tracked path to its pointer (or takes an OID directly), then
+//! serves the object from the local cache or downloads it from storage.
+
+use super::error::{report, GgError};
+use super::smudge::{download_and_stream, stream_cached};
+use crate::lfs::pointer::MAX_POINTER_SIZE;
+use crate::lfs::storage;
+use crate::lfs::{LfsConfig, Pointer};
+use crate::utils::get_repo_with_root;
+use clap::Args;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Args, Debug)]
+pub struct CatArgs {
+    /// Path of an LFS-tracked file
+    #[arg(required_unless_present = "oid", conflicts_with = "oid")]
+    pub path: Option<String>,
+
+    /// Object ID (sha256) to print instead of resolving a path
+    #[arg(long)]
+    pub oid: Option<String>,
+}
+
+/// Print an LFS object's content to stdout
+pub fn run(args: CatArgs) -> i32 {
+    match run_inner(args) {
+        Ok(_) => 0,
+        Err(e) => report(&*e),
+    }
+}
+
+fn run_inner(args: CatArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let (repo, repo_root) = get_repo_with_root()?;
+
+    let (oid, label) = match (&args.oid, &args.path) {
+        (Some(oid), _) => (parse_oid(oid)?, oid.clone()),
+        (None, Some(path)) => (resolve_path(&repo, &repo_root, path)?, path.clone()),
+        (None, None) => return Err("Specify a path or --oid".into()),
+    };
+
+    if stream_cached(&oid, &mut io::stdout())? {
+        return Ok(());
+    }
+
+    let config = LfsConfig::load(&repo_root).map_err(|e| {
+        GgError::Config(format!("{}\nRun 'gg lfs install' to create a configuration file.", e))
+    })?;
+
+    let rt = crate::lfs::runtime()?;
+    rt.block_on(async {
+        let storage = storage::shared_storage(&config).await?;
+        if !storage.exists(&oid).await? {
+            return Err(GgError::Storage(format!("Object {} is not in the local cache or remote storage", oid)).into());
+        }
+        download_and_stream(storage, &oid, None, &repo_root, &label, None, &mut io::stdout()).await
+    })
+}
+
+/// Normalize a user-supplied OID, accepting an optional `sha256:` prefix.
+fn parse_oid(input: &str) -> Result<String, String> {
+    let oid = input.trim_start_matches("sha256:").to_lowercase();
+    if oid.len() != 64 || !oid.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid OID '{}': expected 64 hex characters", input));
+    }
+    Ok(oid)
+}
+
+/// Find the OID for a tracked path. A pointer in the working tree is read
+/// directly; an expanded file is resolved through the pointer blob in the index.
+fn resolve_path(
+    repo: &git2::Repository,
+    repo_root: &Path,
+    path: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let full_path = std::env::current_dir()?.join(path);
+    let relative = full_path
+        .strip_prefix(repo_root)
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|_| PathBuf::from(path));
+
+    if Pointer::is_pointer_file(&full_path) {
+        return Ok(Pointer::parse(&full_path)?.sha256().to_string());
+    }
+
+    let index = repo.index()?;
+    let entry = index
+        .get_path(&relative, 0)
+        .ok_or_else(|| format!("'{}' is not tracked by git", path))?;
+    let blob = repo.find_blob(entry.id)?;
+    let content = blob.content();
+    if content.len() > MAX_POINTER_SIZE {
+        return Err(format!("'{}' is not stored as an LFS pointer", path).into());
+    }
+    let pointer = Pointer::parse_content(content)
+        .map_err(|_| format!("'{}' is not stored as an LFS pointer", path))?;
+    Ok(pointer.sha256().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_oid_accepts_prefix_and_uppercase() {
+        let oid = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        assert_eq!(parse_oid(oid).unwrap(), oid);
+        assert_eq!(parse_oid(&format!("sha256:{}", oid.to_uppercase())).unwrap(), oid);
+    }
+
+    #[test]
+    fn test_parse_oid_rejects_bad_input() {
+        assert!(parse_oid("abc123").is_err());
+        assert!(parse_oid(&"z".repeat(64)).is_err());
+    }
+}
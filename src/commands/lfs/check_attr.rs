@@ -0,0 +1,126 @@
+//! Show which .gitattributes pattern makes a file LFS-tracked
+//!
+//! Like `git check-attr filter`, but names the responsible pattern and line
+//! so a too-broad or mistyped glob is easy to spot. Git's own answer is
+//! checked too, since gg lfs only reads the root .gitattributes.
+
+use crate::lfs::Scanner;
+use crate::utils::repo_root;
+use clap::Args;
+use colored::Colorize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use super::error::report;
+
+#[derive(Args, Debug)]
+pub struct CheckAttrArgs {
+    /// Paths to check, relative to the current directory
+    #[arg(required = true)]
+    pub paths: Vec<String>,
+}
+
+/// Print the LFS pattern matching each path
+pub fn run(args: CheckAttrArgs) -> i32 {
+    match run_inner(args) {
+        Ok(_) => 0,
+        Err(e) => report(&*e),
+    }
+}
+
+fn run_inner(args: CheckAttrArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let repo_root = repo_root()?;
+    let scanner = Scanner::new(&repo_root)?;
+    let current_dir = std::env::current_dir()?;
+
+    let relatives: Vec<PathBuf> = args
+        .paths
+        .iter()
+        .map(|path| repo_relative(&repo_root, &current_dir, path))
+        .collect();
+    let git_filters = git_filter_attrs(&repo_root, &relatives);
+    let mut missing = 0;
+
+    for (path, relative) in args.paths.iter().zip(&relatives) {
+        if !current_dir.join(path).exists() {
+            eprintln!("{} {}: no such file or directory", "Error:".red().bold(), path);
+            missing += 1;
+            continue;
+        }
+
+        let git_filter = git_filters.get(relative).map(String::as_str);
+        match scanner.matching_pattern(relative) {
+            Some(pattern) => {
+                let source = match pattern.line {
+                    Some(line) => format!(".gitattributes:{}", line),
+                    None => ".gitattributes".to_string(),
+                };
+                println!("{}: filter: gg-lfs (pattern {} in {})", path, pattern.pattern.cyan(), source);
+                if let Some(value) = git_filter.filter(|value| !is_lfs_filter(value)) {
+                    println!(
+                        "  {} git resolves filter to '{}': a later line or nested .gitattributes overrides it",
+                        "Warning:".yellow(),
+                        value
+                    );
+                }
+            }
+            None if git_filter.is_some_and(is_lfs_filter) => println!(
+                "{}: filter: {} (set outside the root .gitattributes, which is the only file gg lfs reads)",
+                path,
+                git_filter.unwrap_or_default().yellow()
+            ),
+            None => println!("{}: filter: {}", path, "unspecified".dimmed()),
+        }
+    }
+
+    if missing > 0 {
+        return Err(format!("{} path(s) do not exist", missing).into());
+    }
+    Ok(())
+}
+
+/// Resolve a path given relative to `current_dir` against the repository root.
+/// Paths outside the repository are matched as given.
+fn repo_relative(repo_root: &Path, current_dir: &Path, path: &str) -> PathBuf {
+    let full_path = current_dir.join(path);
+    full_path
+        .strip_prefix(repo_root)
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|_| PathBuf::from(path))
+}
+
+/// Ask git for the `filter` attribute of each path, which accounts for
+/// nested .gitattributes and later lines. Unset attributes are left out.
+fn git_filter_attrs(repo_root: &Path, relatives: &[PathBuf]) -> HashMap<PathBuf, String> {
+    let mut args = vec!["check-attr", "filter", "--"];
+    args.extend(relatives.iter().filter_map(|path| path.to_str()));
+    let fields = crate::git::list_paths(repo_root, &args).unwrap_or_default();
+
+    // -z output is path, attribute, value for each path
+    fields
+        .chunks_exact(3)
+        .filter_map(|chunk| {
+            let value = chunk[2].to_string_lossy().to_string();
+            (value != "unspecified").then(|| (chunk[0].clone(), value))
+        })
+        .collect()
+}
+
+/// Whether a filter attribute value hands the file to LFS
+fn is_lfs_filter(value: &str) -> bool {
+    value == "gg-lfs" || value == "lfs"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repo_relative_resolves_from_subdirectory() {
+        let root = Path::new("/work/repo");
+        assert_eq!(
+            repo_relative(root, &root.join("assets"), "model.bin"),
+            PathBuf::from("assets/model.bin")
+        );
+        assert_eq!(repo_relative(root, root, "a.bin"), PathBuf::from("a.bin"));
+    }
+}
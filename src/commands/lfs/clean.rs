@@ -6,15 +6,23 @@
 //! Reads file content from stdin, outputs pointer text to stdout.
 //! Caches the original content locally (no network access).
 //! Streams content to avoid loading large files into memory.
+//! Unchanged files reuse the OID recorded in the stat index instead of
+//! being hashed again.
 
-use crate::lfs::pointer::MAX_POINTER_SIZE;
-use crate::lfs::{Cache, Pointer};
+use super::error::exit_code;
+use crate::lfs::pointer::{hash_buffer_size, LFS_VERSION, MAX_POINTER_SIZE};
+use crate::lfs::scanner::Excludes;
+use crate::lfs::stat_index::{FileStat, StatIndex};
+use crate::lfs::{Cache, LfsConfig, Pointer};
+use crate::logging::{log_debug, log_error};
+use crate::utils::repo_root;
 use clap::Args;
 use std::io::{self, Read, Write};
+use std::path::Path;
 
 #[derive(Args, Debug)]
 pub struct CleanArgs {
-    /// The file path (passed by git as %f, used for diagnostics only)
+    /// The file path relative to the repository root (passed by git as %f)
     pub file: Option<String>,
 }
 
@@ -23,13 +31,32 @@ pub fn run(args: CleanArgs) -> i32 {
     match run_inner(args) {
         Ok(_) => 0,
         Err(e) => {
-            eprintln!("gg lfs clean: {}", e);
-            1
+            log_error!("gg lfs clean", "{}", e);
+            exit_code(&*e)
         }
     }
 }
 
-fn run_inner(_args: CleanArgs) -> Result<(), Box<dyn std::error::Error>> {
+fn run_inner(args: CleanArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let repo_root = repo_root().ok();
+    let config = repo_root.as_ref().and_then(|root| LfsConfig::load(root).ok());
+    let settings = CleanSettings::from_config(config.as_ref());
+
+    // git only runs the filter on paths whose attributes select it; this is
+    // a second check, for paths excluded in the config
+    if args.file.as_deref().is_some_and(|file| settings.excludes.matches(Path::new(file))) {
+        io::copy(&mut io::stdin().lock(), &mut io::stdout())?;
+        io::stdout().flush()?;
+        return Ok(());
+    }
+
+    // Stat the working file before reading its content, so a later change
+    // can never be recorded against the OID of what we hash
+    let mut indexed = match (&repo_root, &args.file) {
+        (Some(root), Some(file)) => Some((StatIndex::load(root), file.as_str(), FileStat::of(&root.join(file)))),
+        _ => None,
+    };
+
     let stdin = io::stdin();
     let mut reader = stdin.lock();
 
@@ -38,7 +65,9 @@ fn run_inner(_args: CleanArgs) -> Result<(), Box<dyn std::error::Error>> {
     let header_len = read_exact_or_eof(&mut reader, &mut header)?;
     header.truncate(header_len);
 
-    // If the entire content fits in the header and parses as a pointer, pass through unchanged
+    // If the entire content fits in the header and parses as a pointer, pass through unchanged.
+    // Anything longer is content even if it starts like a pointer: passing it
+    // through would commit text that smudge can't parse, so it gets hashed whole.
     if header_len <= MAX_POINTER_SIZE {
         if let Ok(_pointer) = Pointer::parse_content(io::BufReader::new(header.as_slice())) {
             io::stdout().write_all(&header)?;
@@ -47,9 +76,19 @@ fn run_inner(_args: CleanArgs) -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
+    let cache = Cache::new().ok();
+    if let Some((index, file, stat)) = &mut indexed {
+        if let Some(pointer) = indexed_pointer(&mut reader, header_len, index, cache.as_ref(), file, *stat)? {
+            let pointer = pointer.with_version(&settings.version);
+            log_debug!("gg lfs clean", "{}: unchanged, reusing {}", file, pointer.sha256());
+            io::stdout().write_all(pointer.to_string().as_bytes())?;
+            io::stdout().flush()?;
+            return Ok(());
+        }
+    }
+
     // Not a pointer — stream through hasher + cache file
     // Build a cache path (best-effort)
-    let cache = Cache::new().ok();
     let temp_dir = cache.as_ref().map(|c| c.temp_dir());
     let temp_path = temp_dir.as_ref().and_then(|d| {
         std::fs::create_dir_all(d).ok()?;
@@ -59,7 +98,8 @@ fn run_inner(_args: CleanArgs) -> Result<(), Box<dyn std::error::Error>> {
     // Chain header bytes with remaining stdin into a single reader
     let chained = io::Cursor::new(header).chain(reader);
 
-    let pointer = Pointer::from_reader(chained, temp_path.as_deref())?;
+    let pointer = Pointer::from_reader_with_buffer(chained, temp_path.as_deref(), hash_buffer_size())?
+        .with_version(&settings.version);
     let oid = pointer.sha256().to_string();
 
     // Move temp file to cache (best-effort)
@@ -68,6 +108,13 @@ fn run_inner(_args: CleanArgs) -> Result<(), Box<dyn std::error::Error>> {
         let _ = std::fs::remove_file(temp);
     }
 
+    if let Some((mut index, file, Some(stat))) = indexed {
+        if stat.size == pointer.size {
+            index.record(file, stat, &oid);
+            let _ = index.save();
+        }
+    }
+
     // Write pointer text to stdout
     let pointer_text = format!("{}", pointer);
     io::stdout().write_all(pointer_text.as_bytes())?;
@@ -76,6 +123,56 @@ fn run_inner(_args: CleanArgs) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Repository settings the clean filter applies to every file
+pub(super) struct CleanSettings {
+    /// Version URL written into new pointers
+    pub version: String,
+    /// Paths passed through as content
+    pub excludes: Excludes,
+}
+
+impl CleanSettings {
+    /// Settings from a loaded config, or the defaults without one
+    pub fn from_config(config: Option<&LfsConfig>) -> Self {
+        Self {
+            version: config.map_or(LFS_VERSION, LfsConfig::pointer_version).to_string(),
+            excludes: config.map(|config| Excludes::new(config.excludes())).unwrap_or_default(),
+        }
+    }
+}
+
+/// Reuse the OID recorded for an unchanged file instead of hashing it again.
+/// Returns None when there is no usable entry. Otherwise the rest of the
+/// content is drained and its length checked against the recorded size.
+pub(super) fn indexed_pointer<R: Read>(
+    reader: &mut R,
+    header_len: usize,
+    index: &mut StatIndex,
+    cache: Option<&Cache>,
+    file: &str,
+    stat: Option<FileStat>,
+) -> Result<Option<Pointer>, Box<dyn std::error::Error>> {
+    let Some(stat) = stat else {
+        return Ok(None);
+    };
+    let oid = match index.lookup(file, &stat) {
+        Some(oid) => oid.to_string(),
+        None => return Ok(None),
+    };
+    // Hash anyway when the object was evicted, so the content gets cached again
+    if !cache.is_some_and(|cache| cache.contains(&oid)) {
+        return Ok(None);
+    }
+
+    let total = header_len as u64 + io::copy(reader, &mut io::sink())?;
+    if total != stat.size {
+        index.remove(file);
+        let _ = index.save();
+        return Err(format!("{} changed while being staged; run git add again", file).into());
+    }
+    Ok(Some(Pointer::from_oid(&oid, stat.size)))
+}
+
 /// Read up to `buf.len()` bytes, returning the actual number read.
 /// Unlike `read_exact`, does not error on EOF.
 fn read_exact_or_eof<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
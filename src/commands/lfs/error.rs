@@ -0,0 +1,136 @@
+//! Error categories and exit codes for gg lfs commands
+//!
+//! Commands propagate errors with `?` as usual; `report` sorts the error into
+//! a category by its type so scripts and CI can tell a configuration mistake
+//! from a transient storage failure. Errors built from a message are tagged
+//! with a `GgError` variant where the category is known.
+//!
+//! | Code | Meaning |
+//! |------|---------|
+//! | 1 | Any other failure |
+//! | 2 | Invalid arguments (reported by clap) |
+//! | 3 | Configuration missing or invalid |
+//! | 4 | Remote storage unreachable or refused a request |
+//! | 5 | Content doesn't match its OID |
+//! | 6 | Not a git repository, or a git command failed |
+//! | 7 | Local filesystem error |
+
+use crate::lfs::cache::CacheError;
+use crate::lfs::config::ConfigError;
+use crate::lfs::pointer::PointerError;
+use crate::lfs::scanner::ScannerError;
+use crate::lfs::storage::StorageError;
+use colored::Colorize;
+use std::error::Error;
+use thiserror::Error;
+
+/// Exit code for errors without a category
+pub const EXIT_FAILURE: i32 = 1;
+pub const EXIT_CONFIG: i32 = 3;
+pub const EXIT_STORAGE: i32 = 4;
+pub const EXIT_INTEGRITY: i32 = 5;
+pub const EXIT_GIT: i32 = 6;
+pub const EXIT_IO: i32 = 7;
+
+/// A command error with a known category
+#[derive(Error, Debug)]
+pub enum GgError {
+    /// Missing or invalid configuration, or a missing prerequisite
+    #[error("{0}")]
+    Config(String),
+
+    /// Remote storage could not be reached or refused a request
+    #[error("{0}")]
+    Storage(String),
+
+    /// Content doesn't match its OID
+    #[error("{0}")]
+    Integrity(String),
+
+    /// Not a git repository, or a git command failed
+    #[error("{0}")]
+    Git(String),
+}
+
+impl GgError {
+    /// Process exit code for this category
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Config(_) => EXIT_CONFIG,
+            Self::Storage(_) => EXIT_STORAGE,
+            Self::Integrity(_) => EXIT_INTEGRITY,
+            Self::Git(_) => EXIT_GIT,
+        }
+    }
+}
+
+/// Exit code for an error returned by a command, by its type
+pub fn exit_code(err: &(dyn Error + 'static)) -> i32 {
+    if let Some(err) = err.downcast_ref::<GgError>() {
+        err.exit_code()
+    } else if let Some(err) = err.downcast_ref::<ConfigError>() {
+        match err {
+            ConfigError::Io(_) => EXIT_IO,
+            ConfigError::NoRepository => EXIT_GIT,
+            _ => EXIT_CONFIG,
+        }
+    } else if let Some(err) = err.downcast_ref::<StorageError>() {
+        match err {
+            StorageError::Config(_) => EXIT_CONFIG,
+            StorageError::Verification(_) => EXIT_INTEGRITY,
+            StorageError::Io(_) => EXIT_IO,
+            _ => EXIT_STORAGE,
+        }
+    } else if let Some(err) = err.downcast_ref::<ScannerError>() {
+        match err {
+            ScannerError::Io(_) => EXIT_IO,
+            ScannerError::InvalidPattern(_) => EXIT_CONFIG,
+            ScannerError::NoRepository => EXIT_GIT,
+        }
+    } else if let Some(err) = err.downcast_ref::<PointerError>() {
+        match err {
+            PointerError::Io(_) => EXIT_IO,
+            _ => EXIT_INTEGRITY,
+        }
+    } else if err.is::<git2::Error>() {
+        EXIT_GIT
+    } else if err.is::<CacheError>() || err.is::<std::io::Error>() {
+        EXIT_IO
+    } else {
+        EXIT_FAILURE
+    }
+}
+
+/// Print a command's error and return the exit code for it
+pub fn report(err: &(dyn Error + 'static)) -> i32 {
+    eprintln!("{} {}", "Error:".red().bold(), err);
+    exit_code(err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code_of<E: Error + 'static>(err: E) -> i32 {
+        let boxed: Box<dyn Error> = Box::new(err);
+        exit_code(&*boxed)
+    }
+
+    #[test]
+    fn test_exit_code_by_category() {
+        assert_eq!(code_of(GgError::Config("no config".to_string())), EXIT_CONFIG);
+        assert_eq!(code_of(ConfigError::Invalid("bucket cannot be empty".to_string())), EXIT_CONFIG);
+        assert_eq!(code_of(StorageError::Network("timed out".to_string())), EXIT_STORAGE);
+        assert_eq!(code_of(StorageError::Verification("size differs".to_string())), EXIT_INTEGRITY);
+        assert_eq!(code_of(GgError::Integrity("hash mismatch".to_string())), EXIT_INTEGRITY);
+        assert_eq!(code_of(git2::Error::from_str("not a repository")), EXIT_GIT);
+        assert_eq!(code_of(std::io::Error::other("disk full")), EXIT_IO);
+        assert_eq!(code_of(ScannerError::NoRepository), EXIT_GIT);
+    }
+
+    #[test]
+    fn test_exit_code_for_uncategorized_errors() {
+        let err: Box<dyn Error> = "Specify a path or --oid".into();
+        assert_eq!(exit_code(&*err), EXIT_FAILURE);
+    }
+}
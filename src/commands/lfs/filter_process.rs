@@ -4,12 +4,21 @@
 //! to handle clean/smudge in a single persistent process, avoiding per-file
 //! process spawn + tokio runtime + S3 client initialization overhead.
 
-use crate::lfs::pointer::MAX_POINTER_SIZE;
+use super::clean::{indexed_pointer, CleanSettings};
+use super::error::exit_code;
+use super::smudge::within;
+use crate::lfs::pointer::{hash_buffer_size, MAX_POINTER_SIZE};
+use crate::lfs::stat_index::{FileStat, StatIndex};
 use crate::lfs::storage::{self, Storage};
 use crate::lfs::{Cache, LfsConfig, Pointer};
+use crate::logging::{log_debug, log_error, log_warn};
+use crate::utils::repo_root;
 use clap::Args;
+use sha2::{Digest, Sha256};
+use std::fs::File;
 use std::io::{self, BufWriter, Read, Write};
 use std::path::Path;
+use std::time::Duration;
 
 /// Maximum data payload per pkt-line frame (65520 - 4 byte length prefix)
 const PKT_MAX_DATA: usize = 65516;
@@ -17,13 +26,20 @@ const PKT_MAX_DATA: usize = 65516;
 #[derive(Args, Debug)]
 pub struct FilterProcessArgs {}
 
+/// Storage to smudge from, shared by every file in the session
+struct Remote<'a> {
+    storage: &'a dyn Storage,
+    /// How long one download may take before the pointer is kept instead
+    timeout: Duration,
+}
+
 /// Run the long-running filter process
 pub fn run(_args: FilterProcessArgs) -> i32 {
     match run_inner() {
         Ok(_) => 0,
         Err(e) => {
-            eprintln!("gg lfs filter-process: {}", e);
-            1
+            log_error!("gg lfs filter-process", "{}", e);
+            exit_code(&*e)
         }
     }
 }
@@ -71,11 +87,8 @@ fn pkt_read<R: Read>(reader: &mut R) -> io::Result<Option<PktLine>> {
 /// Only use for small content (e.g., pointer text).
 fn pkt_read_to_flush<R: Read>(reader: &mut R) -> io::Result<Vec<u8>> {
     let mut buf = Vec::new();
-    loop {
-        match pkt_read(reader)? {
-            Some(PktLine::Data(data)) => buf.extend_from_slice(&data),
-            Some(PktLine::Flush) | None => break,
-        }
+    while let Some(PktLine::Data(data)) = pkt_read(reader)? {
+        buf.extend_from_slice(&data);
     }
     Ok(buf)
 }
@@ -117,6 +130,65 @@ fn pkt_stream_file<W: Write>(writer: &mut W, path: &Path) -> io::Result<()> {
     Ok(())
 }
 
+// ── SmudgeWriter: Write adapter into pkt-line frames ─────────────────
+
+/// Frames everything written to it as pkt-line data for git, sending the
+/// success status just before the first byte so a download that fails
+/// before producing anything can still fall back to the pointer. The
+/// content is hashed and counted as it passes and teed into `tee` for the
+/// cache; a failing tee is dropped rather than failing the checkout.
+struct SmudgeWriter<'a, W> {
+    inner: &'a mut W,
+    tee: Option<File>,
+    hasher: Sha256,
+    /// Bytes written so far
+    size: u64,
+    /// Whether the status and any content have been sent to git
+    started: bool,
+}
+
+impl<'a, W: Write> SmudgeWriter<'a, W> {
+    fn new(inner: &'a mut W, tee: Option<File>) -> Self {
+        Self { inner, tee, hasher: Sha256::new(), size: 0, started: false }
+    }
+
+    /// The hex SHA-256 and size of everything written, and whether the tee
+    /// holds all of it
+    fn finish(self) -> (String, u64, bool) {
+        let tee_complete = match self.tee {
+            Some(mut tee) => tee.flush().is_ok(),
+            None => false,
+        };
+        (format!("{:x}", self.hasher.finalize()), self.size, tee_complete)
+    }
+}
+
+impl<W: Write> Write for SmudgeWriter<'_, W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        if data.is_empty() {
+            return Ok(0);
+        }
+        if !self.started {
+            pkt_write(self.inner, "status=success\n")?;
+            pkt_flush(self.inner)?;
+            self.started = true;
+        }
+        pkt_write_data(self.inner, data)?;
+        self.hasher.update(data);
+        self.size += data.len() as u64;
+        if let Some(tee) = self.tee.as_mut() {
+            if tee.write_all(data).is_err() {
+                self.tee = None;
+            }
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
 // ── PktLineReader: Read adapter over pkt-line stream ─────────────────
 
 /// Presents a standard Read interface over pkt-line data frames.
@@ -181,14 +253,9 @@ fn handshake<R: Read, W: Write>(
 
     // Read version(s) until flush
     let mut got_v2 = false;
-    loop {
-        match pkt_read(reader)? {
-            Some(PktLine::Data(d)) => {
-                if String::from_utf8_lossy(&d).trim() == "version=2" {
-                    got_v2 = true;
-                }
-            }
-            _ => break,
+    while let Some(PktLine::Data(d)) = pkt_read(reader)? {
+        if String::from_utf8_lossy(&d).trim() == "version=2" {
+            got_v2 = true;
         }
     }
     if !got_v2 {
@@ -202,15 +269,10 @@ fn handshake<R: Read, W: Write>(
 
     // Read client capabilities until flush
     let mut client_caps = Vec::new();
-    loop {
-        match pkt_read(reader)? {
-            Some(PktLine::Data(d)) => {
-                let line = String::from_utf8_lossy(&d).trim().to_string();
-                if let Some(cap) = line.strip_prefix("capability=") {
-                    client_caps.push(cap.to_string());
-                }
-            }
-            _ => break,
+    while let Some(PktLine::Data(d)) = pkt_read(reader)? {
+        let line = String::from_utf8_lossy(&d).trim().to_string();
+        if let Some(cap) = line.strip_prefix("capability=") {
+            client_caps.push(cap.to_string());
         }
     }
 
@@ -232,24 +294,25 @@ fn run_inner() -> Result<(), Box<dyn std::error::Error>> {
     let stdin = io::stdin();
     let stdout = io::stdout();
     let mut reader = stdin.lock();
-    let mut writer = BufWriter::new(stdout.lock());
+    // Unlocked, so smudge downloads can stream into it from async code
+    let mut writer = BufWriter::new(stdout);
 
     handshake(&mut reader, &mut writer)?;
 
     // Shared resources — initialized once, reused for all files
     let cache = Cache::new().ok();
-    let repo = git2::Repository::discover(".")?;
-    let repo_root = repo
-        .workdir()
-        .ok_or("Not a git repository with a working directory")?
-        .to_path_buf();
-
-    let rt = tokio::runtime::Runtime::new()?;
-    let storage: Option<Box<dyn Storage>> = LfsConfig::load(&repo_root)
-        .ok()
-        .and_then(|config| rt.block_on(storage::create_storage(&config)).ok());
+    let repo_root = repo_root()?;
+
+    let rt = crate::lfs::runtime()?;
+    let config = LfsConfig::load(&repo_root).ok();
+    let settings = CleanSettings::from_config(config.as_ref());
+    let remote = config.and_then(|config| {
+        let storage = rt.block_on(storage::shared_storage(&config)).ok()?;
+        Some(Remote { storage, timeout: config.smudge_timeout() })
+    });
 
     let skip_smudge = std::env::var("GG_LFS_SKIP_SMUDGE").unwrap_or_default() == "1";
+    let mut stat_index = StatIndex::load(&repo_root);
 
     loop {
         // Read command metadata until flush
@@ -267,37 +330,51 @@ fn run_inner() -> Result<(), Box<dyn std::error::Error>> {
                     }
                 }
                 Some(PktLine::Flush) => break,
-                None => return Ok(()), // Git closed stdin — clean exit
+                None => {
+                    // Git closed stdin — clean exit
+                    let _ = stat_index.save();
+                    return Ok(());
+                }
             }
         }
 
         if command.is_empty() {
+            let _ = stat_index.save();
             return Ok(());
         }
 
         let result = match command.as_str() {
-            "clean" => process_clean(&mut reader, &mut writer, &cache),
+            // Paths excluded in the config are stored as plain content
+            "clean" if settings.excludes.matches(Path::new(&pathname)) => {
+                process_passthrough(&mut reader, &mut writer)
+            }
+            "clean" => process_clean(
+                &mut reader,
+                &mut writer,
+                &cache,
+                &mut stat_index,
+                &repo_root,
+                &pathname,
+                &settings,
+            ),
             "smudge" if skip_smudge => process_passthrough(&mut reader, &mut writer),
             "smudge" => process_smudge(
                 &mut reader,
                 &mut writer,
                 &cache,
-                storage.as_deref(),
-                &rt,
-                &repo_root,
+                remote.as_ref(),
+                rt,
                 &pathname,
             ),
             _ => process_passthrough(&mut reader, &mut writer),
         };
 
         if let Err(e) = result {
-            eprintln!(
-                "gg lfs filter-process: error on {} ({}): {}",
-                pathname, command, e
-            );
+            log_error!("gg lfs filter-process", "{} ({}): {}", pathname, command, e);
+            // Handlers consume the request before failing, so git is waiting
+            // for a status: a single error status ends this request
             let _ = pkt_write(&mut writer, "status=error\n");
             let _ = pkt_flush(&mut writer);
-            let _ = pkt_flush(&mut writer);
         }
     }
 }
@@ -320,11 +397,18 @@ fn process_passthrough<R: Read, W: Write>(
 
 /// Clean filter: convert file content to pointer text.
 /// Streams through hasher + cache file to handle large files without OOM.
+/// Unchanged files reuse the OID recorded in the stat index.
 fn process_clean<R: Read, W: Write>(
     reader: &mut R,
     writer: &mut W,
     cache: &Option<Cache>,
+    stat_index: &mut StatIndex,
+    repo_root: &Path,
+    pathname: &str,
+    settings: &CleanSettings,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    // Stat before reading, so a later change is never recorded against this content
+    let stat = FileStat::of(&repo_root.join(pathname));
     let mut pkt_reader = PktLineReader::new(reader);
 
     // Read header to check if already a pointer
@@ -332,16 +416,23 @@ fn process_clean<R: Read, W: Write>(
     let header_len = read_exact_or_eof(&mut pkt_reader, &mut header)?;
     header.truncate(header_len);
 
-    // If fits in header and is a pointer, pass through unchanged
-    if header_len <= MAX_POINTER_SIZE && pkt_reader.done {
-        if Pointer::parse_content(io::BufReader::new(header.as_slice())).is_ok() {
-            pkt_write(writer, "status=success\n")?;
-            pkt_flush(writer)?;
-            pkt_write_data(writer, &header)?;
-            pkt_flush(writer)?;
-            pkt_flush(writer)?;
-            return Ok(());
-        }
+    // If fits in header and is a pointer, pass through unchanged. Longer
+    // content that merely starts like a pointer is hashed whole, like `gg lfs clean`
+    if header_len <= MAX_POINTER_SIZE
+        && pkt_reader.done
+        && Pointer::parse_content(io::BufReader::new(header.as_slice())).is_ok()
+    {
+        pkt_write(writer, "status=success\n")?;
+        pkt_flush(writer)?;
+        pkt_write_data(writer, &header)?;
+        pkt_flush(writer)?;
+        pkt_flush(writer)?;
+        return Ok(());
+    }
+
+    if let Some(pointer) = indexed_pointer(&mut pkt_reader, header_len, stat_index, cache.as_ref(), pathname, stat)? {
+        log_debug!("gg lfs filter-process", "clean {}: unchanged, reusing {}", pathname, pointer.sha256());
+        return write_pointer(writer, &pointer.with_version(&settings.version));
     }
 
     // Not a pointer — stream through hasher + temp file for caching
@@ -351,8 +442,15 @@ fn process_clean<R: Read, W: Write>(
         Some(dir.join(format!("filter-clean-{}", std::process::id())))
     });
 
-    let chained = io::Cursor::new(header).chain(pkt_reader);
-    let pointer = Pointer::from_reader(chained, temp_path.as_deref())?;
+    let chained = io::Cursor::new(header).chain(&mut pkt_reader);
+    let pointer = match Pointer::from_reader_with_buffer(chained, temp_path.as_deref(), hash_buffer_size()) {
+        Ok(pointer) => pointer.with_version(&settings.version),
+        Err(e) => {
+            // Skip the rest of the content so the next request starts on a frame boundary
+            io::copy(&mut pkt_reader, &mut io::sink())?;
+            return Err(e.into());
+        }
+    };
     let oid = pointer.sha256().to_string();
 
     // Cache the content
@@ -361,7 +459,15 @@ fn process_clean<R: Read, W: Write>(
         let _ = std::fs::remove_file(temp);
     }
 
-    // Write pointer text as response
+    if let Some(stat) = stat.filter(|stat| stat.size == pointer.size) {
+        stat_index.record(pathname, stat, &oid);
+    }
+
+    write_pointer(writer, &pointer)
+}
+
+/// Respond to a clean request with pointer text
+fn write_pointer<W: Write>(writer: &mut W, pointer: &Pointer) -> Result<(), Box<dyn std::error::Error>> {
     let pointer_text = pointer.to_string();
     pkt_write(writer, "status=success\n")?;
     pkt_flush(writer)?;
@@ -374,13 +480,12 @@ fn process_clean<R: Read, W: Write>(
 
 /// Smudge filter: convert pointer text to real file content.
 /// Input is always small (pointer text). Output may be large (streamed).
-fn process_smudge<R: Read, W: Write>(
+fn process_smudge<R: Read, W: Write + Send>(
     reader: &mut R,
     writer: &mut W,
     cache: &Option<Cache>,
-    storage: Option<&dyn Storage>,
+    remote: Option<&Remote>,
     rt: &tokio::runtime::Runtime,
-    repo_root: &Path,
     pathname: &str,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let content = pkt_read_to_flush(reader)?;
@@ -404,6 +509,7 @@ fn process_smudge<R: Read, W: Write>(
     // Check cache first — stream directly
     if let Some(cache) = cache {
         if let Some(cached_path) = cache.get(&oid) {
+            log_debug!("gg lfs filter-process", "smudge {}: served {} from cache", pathname, oid);
             pkt_write(writer, "status=success\n")?;
             pkt_flush(writer)?;
             pkt_stream_file(writer, &cached_path)?;
@@ -414,11 +520,13 @@ fn process_smudge<R: Read, W: Write>(
     }
 
     // Cache miss — download from storage
-    let storage = match storage {
-        Some(s) => s,
+    log_debug!("gg lfs filter-process", "smudge {}: downloading {}", pathname, oid);
+    let remote = match remote {
+        Some(r) => r,
         None => {
-            eprintln!(
-                "gg lfs filter-process: warning: no storage for {}, outputting pointer",
+            log_warn!(
+                "gg lfs filter-process",
+                "no storage for {}, outputting pointer",
                 pathname
             );
             pkt_write(writer, "status=success\n")?;
@@ -430,33 +538,70 @@ fn process_smudge<R: Read, W: Write>(
         }
     };
 
-    let temp_dir = repo_root.join(".gg").join("tmp");
-    std::fs::create_dir_all(&temp_dir)?;
-    let temp_path = temp_dir.join(&oid);
+    // Tee into a file next to the cache so the object is cached without a
+    // second copy; without a cache the content is only streamed
+    let tee_path = cache
+        .as_ref()
+        .map(|cache| cache.temp_dir().join(format!("{}-{}", oid, std::process::id())));
+    let tee = tee_path.as_ref().and_then(|path| {
+        std::fs::create_dir_all(path.parent()?).ok()?;
+        File::create(path).ok()
+    });
 
-    rt.block_on(async { storage.download(&oid, &temp_path).await })?;
+    let mut stream = SmudgeWriter::new(writer, tee);
+    let downloaded = rt.block_on(within(remote.timeout, remote.storage.download_to_writer(&oid, &mut stream)));
+    let started = stream.started;
+    let (hash, size, cacheable) = stream.finish();
+    let result = match downloaded {
+        Ok(_) if hash != oid => Err("hash mismatch".into()),
+        Ok(_) if size != pointer.size => {
+            Err(format!("size mismatch: pointer says {} bytes, object has {}", pointer.size, size).into())
+        }
+        Ok(_) => Ok(()),
+        Err(e) => Err(e),
+    };
 
-    // Verify hash
-    let downloaded_pointer = Pointer::from_file(&temp_path)?;
-    if downloaded_pointer.oid != pointer.oid {
-        std::fs::remove_file(&temp_path).ok();
-        return Err(format!("hash mismatch for {}", pathname).into());
-    }
+    match result {
+        Ok(()) => {
+            if !started {
+                // Empty object: nothing was written, so no status was sent yet
+                pkt_write(writer, "status=success\n")?;
+                pkt_flush(writer)?;
+            }
+            pkt_flush(writer)?;
+            // An empty list keeps the status sent before the content
+            pkt_flush(writer)?;
 
-    // Cache the downloaded file
-    if let Some(cache) = cache {
-        let _ = cache.put_file(&oid, &temp_path);
+            if let (Some(cache), Some(tee_path)) = (cache, &tee_path) {
+                if !cacheable || cache.adopt_file(&oid, tee_path).is_err() {
+                    std::fs::remove_file(tee_path).ok();
+                }
+            }
+        }
+        Err(e) => {
+            if let Some(tee_path) = &tee_path {
+                std::fs::remove_file(tee_path).ok();
+            }
+            if !started {
+                // Same graceful degradation as `gg lfs smudge`: keep the pointer so
+                // the checkout succeeds and a later `gg lfs pull` can fill it in
+                log_warn!("gg lfs filter-process", "download failed for {}: {}", pathname, e);
+                pkt_write(writer, "status=success\n")?;
+                pkt_flush(writer)?;
+                pkt_write_data(writer, &content)?;
+                pkt_flush(writer)?;
+                pkt_flush(writer)?;
+            } else {
+                // Part of the content already went to git; the trailing status
+                // makes it discard the file instead of writing it truncated
+                log_error!("gg lfs filter-process", "download failed for {} after it started: {}", pathname, e);
+                pkt_flush(writer)?;
+                pkt_write(writer, "status=error\n")?;
+                pkt_flush(writer)?;
+            }
+        }
     }
 
-    // Stream to output
-    pkt_write(writer, "status=success\n")?;
-    pkt_flush(writer)?;
-    pkt_stream_file(writer, &temp_path)?;
-    pkt_flush(writer)?;
-    pkt_flush(writer)?;
-
-    std::fs::remove_file(&temp_path).ok();
-
     Ok(())
 }
 
@@ -473,3 +618,241 @@ fn read_exact_or_eof<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usiz
     }
     Ok(total)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames(lines: &[&str]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for line in lines {
+            if line.is_empty() {
+                out.extend_from_slice(b"0000");
+            } else {
+                pkt_write(&mut out, line).unwrap();
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_handshake_clean_only_client() {
+        let input = frames(&[
+            "git-filter-client\n", "version=2\n", "",
+            "capability=clean\n", "",
+        ]);
+        let mut output = Vec::new();
+
+        handshake(&mut input.as_slice(), &mut output).unwrap();
+
+        let response = String::from_utf8(output).unwrap();
+        assert!(response.contains("capability=clean"));
+        assert!(!response.contains("capability=smudge"));
+    }
+
+    #[test]
+    fn test_handshake_rejects_unknown_client() {
+        let input = frames(&["git-filter-other\n", "version=2\n", ""]);
+        assert!(handshake(&mut input.as_slice(), &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_process_clean_consumes_exactly_one_request() {
+        let mut input = Vec::new();
+        pkt_write_data(&mut input, b"hello").unwrap();
+        input.extend_from_slice(b"0000");
+        pkt_write(&mut input, "command=clean\n").unwrap();
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut stat_index = StatIndex::load(temp.path());
+        let mut reader = input.as_slice();
+        let mut output = Vec::new();
+        process_clean(&mut reader, &mut output, &None, &mut stat_index, temp.path(), "a.bin", &CleanSettings::from_config(None)).unwrap();
+
+        let response = String::from_utf8(output).unwrap();
+        assert!(response.starts_with("0013status=success\n0000"));
+        assert!(response.contains(&Pointer::from_bytes(b"hello").to_string()));
+        // The next request is still intact
+        match pkt_read(&mut reader).unwrap() {
+            Some(PktLine::Data(data)) => assert_eq!(data, b"command=clean\n"),
+            _ => panic!("next request was consumed"),
+        }
+    }
+
+    /// `len` bytes whose first MAX_POINTER_SIZE bytes are a pointer padded
+    /// with an extension-like line, followed by more of the same
+    fn pointer_shaped(len: usize) -> Vec<u8> {
+        let mut content = Pointer::from_bytes(b"hello").to_string().into_bytes();
+        let padding = MAX_POINTER_SIZE - content.len() - "x-padding \n".len();
+        content.extend_from_slice(format!("x-padding {}\n", "a".repeat(padding)).as_bytes());
+        content.resize(len, b'a');
+        content
+    }
+
+    #[test]
+    fn test_process_clean_hashes_oversized_pointer_shaped_content() {
+        let content = pointer_shaped(MAX_POINTER_SIZE * 2);
+        assert!(Pointer::parse_content(&content[..MAX_POINTER_SIZE]).is_ok());
+
+        let mut input = Vec::new();
+        pkt_write_data(&mut input, &content).unwrap();
+        input.extend_from_slice(b"0000");
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut stat_index = StatIndex::load(temp.path());
+        let mut output = Vec::new();
+        process_clean(&mut input.as_slice(), &mut output, &None, &mut stat_index, temp.path(), "a.txt", &CleanSettings::from_config(None)).unwrap();
+
+        let response = String::from_utf8(output).unwrap();
+        assert!(response.contains(&Pointer::from_bytes(&content).to_string()), "Got: {}", response);
+        assert!(!response.contains("x-padding"));
+    }
+
+    #[test]
+    fn test_process_clean_reuses_indexed_oid_for_unchanged_file() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("a.bin");
+        std::fs::write(&path, "hello").unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(std::time::SystemTime::now() - std::time::Duration::from_secs(60)).unwrap();
+
+        let hello = Pointer::from_bytes(b"hello");
+        let cache = Cache::with_root(temp.path().join("cache")).unwrap();
+        cache.put(hello.sha256(), b"hello").unwrap();
+        let mut stat_index = StatIndex::load(temp.path());
+        stat_index.record("a.bin", FileStat::of(&path).unwrap(), hello.sha256());
+
+        // Same size as the recorded file: trusted without hashing
+        let mut input = Vec::new();
+        pkt_write_data(&mut input, b"jello").unwrap();
+        input.extend_from_slice(b"0000");
+        let mut output = Vec::new();
+        let cache = Some(cache);
+        process_clean(&mut input.as_slice(), &mut output, &cache, &mut stat_index, temp.path(), "a.bin", &CleanSettings::from_config(None)).unwrap();
+        assert!(String::from_utf8(output).unwrap().contains(&hello.to_string()));
+
+        // A size mismatch means the content isn't what was stat'ed
+        let mut input = Vec::new();
+        pkt_write_data(&mut input, b"hello!").unwrap();
+        input.extend_from_slice(b"0000");
+        let result = process_clean(&mut input.as_slice(), &mut Vec::new(), &cache, &mut stat_index, temp.path(), "a.bin", &CleanSettings::from_config(None));
+        assert!(result.is_err());
+        assert_eq!(stat_index.lookup("a.bin", &FileStat::of(&path).unwrap()), None);
+    }
+
+    /// Storage that streams `content` in two chunks, failing after the
+    /// first when `fail_after_first` is set
+    struct ChunkedStorage {
+        content: &'static [u8],
+        fail_after_first: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl Storage for ChunkedStorage {
+        async fn upload(&self, oid: &str, _source: &Path) -> Result<storage::UploadResult, storage::StorageError> {
+            Err(storage::StorageError::Config(oid.to_string()))
+        }
+
+        async fn download(&self, oid: &str, _dest: &Path) -> Result<storage::DownloadResult, storage::StorageError> {
+            Err(storage::StorageError::NotFound(oid.to_string()))
+        }
+
+        async fn download_to_writer(
+            &self,
+            _oid: &str,
+            writer: &mut (dyn Write + Send),
+        ) -> Result<u64, storage::StorageError> {
+            let (first, rest) = self.content.split_at(self.content.len() / 2);
+            writer.write_all(first)?;
+            if self.fail_after_first {
+                return Err(storage::StorageError::Network("connection reset".to_string()));
+            }
+            writer.write_all(rest)?;
+            Ok(self.content.len() as u64)
+        }
+
+        async fn exists(&self, _oid: &str) -> Result<bool, storage::StorageError> {
+            Ok(true)
+        }
+
+        async fn stat(&self, _oid: &str) -> Result<Option<storage::ObjectInfo>, storage::StorageError> {
+            Ok(None)
+        }
+
+        async fn delete(&self, _oid: &str) -> Result<(), storage::StorageError> {
+            Ok(())
+        }
+
+        fn provider_name(&self) -> &str {
+            "chunked"
+        }
+    }
+
+    /// Run one smudge request for the pointer to "hello" against `storage`
+    fn smudge_hello(storage: &ChunkedStorage, cache: &Option<Cache>) -> String {
+        smudge_pointer(storage, cache, &Pointer::from_bytes(b"hello"))
+    }
+
+    /// Run one smudge request for `pointer` against `storage`
+    fn smudge_pointer(storage: &ChunkedStorage, cache: &Option<Cache>, pointer: &Pointer) -> String {
+        let mut input = Vec::new();
+        pkt_write_data(&mut input, pointer.to_string().as_bytes()).unwrap();
+        input.extend_from_slice(b"0000");
+
+        let remote = Remote { storage, timeout: Duration::from_secs(10) };
+        let mut output = Vec::new();
+        let rt = crate::lfs::runtime().unwrap();
+        process_smudge(&mut input.as_slice(), &mut output, cache, Some(&remote), rt, "a.bin").unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_process_smudge_streams_download_into_frames_and_cache() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let cache = Some(Cache::with_root(temp.path()).unwrap());
+        let storage = ChunkedStorage { content: b"hello", fail_after_first: false };
+
+        let response = smudge_hello(&storage, &cache);
+
+        assert_eq!(response, "0013status=success\n00000006he0007llo00000000");
+        let oid = Pointer::from_bytes(b"hello").sha256().to_string();
+        assert_eq!(cache.as_ref().unwrap().read(&oid).unwrap(), b"hello");
+        assert_eq!(std::fs::read_dir(cache.as_ref().unwrap().temp_dir()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_process_smudge_rejects_content_that_does_not_match() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let cache = Some(Cache::with_root(temp.path()).unwrap());
+        let storage = ChunkedStorage { content: b"jello", fail_after_first: false };
+
+        let response = smudge_hello(&storage, &cache);
+
+        // Content was already sent, so only the trailing status can reject it
+        assert!(response.ends_with("00000011status=error\n0000"), "{}", response);
+        assert_eq!(cache.as_ref().unwrap().count().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_process_smudge_rejects_pointer_with_wrong_size() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let cache = Some(Cache::with_root(temp.path()).unwrap());
+        let storage = ChunkedStorage { content: b"hello", fail_after_first: false };
+        let mut pointer = Pointer::from_bytes(b"hello");
+        pointer.size = 9;
+
+        let response = smudge_pointer(&storage, &cache, &pointer);
+
+        assert!(response.ends_with("00000011status=error\n0000"), "{}", response);
+        assert_eq!(cache.as_ref().unwrap().count().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_process_smudge_fails_file_when_stream_breaks() {
+        let storage = ChunkedStorage { content: b"hello", fail_after_first: true };
+
+        let response = smudge_hello(&storage, &None);
+
+        assert!(response.starts_with("0013status=success\n00000006he"));
+        assert!(response.ends_with("00000011status=error\n0000"), "{}", response);
+    }
+}
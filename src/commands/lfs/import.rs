@@ -3,14 +3,25 @@
 //! Bulk-converts all files matching LFS patterns to pointer files,
 //! uploading the real content to S3. Use this for initial setup
 //! when adopting gg lfs on a repo that has never used any LFS system.
-
-use crate::lfs::storage;
+//!
+//! Files are hashed and uploaded by up to `--jobs` concurrent tasks. A file
+//! is only replaced by its pointer once its object is uploaded and cached.
+
+use super::error::{report, GgError};
+use super::overrides::StorageOverrides;
+use super::progress::report_line;
+use crate::config;
+use crate::lfs::scanner::committed_as_content;
+use crate::lfs::storage::{self, Storage};
 use crate::lfs::{Cache, LfsConfig, Pointer, Scanner};
+use crate::utils::get_repo_with_root;
 use clap::Args;
 use colored::Colorize;
 use indicatif::{ProgressBar, ProgressStyle};
+use std::collections::{HashMap, VecDeque};
 use std::io::IsTerminal;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use tokio::task::JoinSet;
 
 #[derive(Args, Debug)]
 pub struct ImportArgs {
@@ -25,11 +36,28 @@ pub struct ImportArgs {
     /// Skip files matching glob pattern
     #[arg(short, long)]
     pub exclude: Option<String>,
+
+    /// Don't stage the converted pointer files
+    #[arg(long)]
+    pub no_stage: bool,
+
+    /// Files to hash and upload at once (default: lfs.concurrenttransfers from .lfsconfig, else 8)
+    #[arg(short, long)]
+    pub jobs: Option<usize>,
+
+    #[command(flatten)]
+    pub storage: StorageOverrides,
+}
+
+/// A finished hash or upload task
+enum Job {
+    Hashed(PathBuf, Result<Pointer, String>),
+    Uploaded(String, Result<(), String>),
 }
 
 /// Import large files into LFS
 pub fn run(args: ImportArgs) -> i32 {
-    let rt = match tokio::runtime::Runtime::new() {
+    let rt = match crate::lfs::runtime() {
         Ok(rt) => rt,
         Err(e) => {
             eprintln!(
@@ -44,36 +72,31 @@ pub fn run(args: ImportArgs) -> i32 {
     rt.block_on(async {
         match run_inner(args).await {
             Ok(_) => 0,
-            Err(e) => {
-                eprintln!("{} {}", "Error:".red().bold(), e);
-                1
-            }
+            Err(e) => report(&*e),
         }
     })
 }
 
 async fn run_inner(args: ImportArgs) -> Result<(), Box<dyn std::error::Error>> {
-    let repo = git2::Repository::discover(".")?;
-    let repo_root = repo
-        .workdir()
-        .ok_or("Not a git repository with a working directory")?;
+    let (repo, repo_root) = get_repo_with_root()?;
 
     // Load config
-    let config = LfsConfig::load(repo_root).map_err(|e| {
-        format!(
+    let mut config = LfsConfig::load(&repo_root).map_err(|e| {
+        GgError::Config(format!(
             "{}\nRun 'gg lfs install' to create a configuration file.",
             e
-        )
+        ))
     })?;
+    args.storage.apply(&mut config)?;
 
     // Initialize storage
-    let storage = storage::create_storage(&config).await?;
+    let storage = storage::shared_storage(&config).await?;
 
     // Initialize cache
     let cache = Cache::new()?;
 
     // Scan for LFS files
-    let scanner = Scanner::new(repo_root)?;
+    let scanner = Scanner::new(&repo_root)?;
     let patterns = scanner.patterns();
 
     if patterns.is_empty() {
@@ -85,14 +108,14 @@ async fn run_inner(args: ImportArgs) -> Result<(), Box<dyn std::error::Error>> {
     }
 
     // Find all files matching LFS patterns, applying include/exclude filters
-    let files = find_matching_files(repo_root, &scanner, &args.include, &args.exclude)?;
+    let files = find_matching_files(&repo_root, &scanner, &args.include, &args.exclude)?;
 
     if files.is_empty() {
         println!("{}", "No files to import.".dimmed());
         return Ok(());
     }
 
-    let show_progress = !args.dry_run && std::io::stderr().is_terminal();
+    let show_progress = !args.dry_run && std::io::stderr().is_terminal() && !config::is_quiet();
     let pb = if show_progress {
         let pb = ProgressBar::new(files.len() as u64);
         pb.set_style(ProgressStyle::default_bar()
@@ -103,72 +126,166 @@ async fn run_inner(args: ImportArgs) -> Result<(), Box<dyn std::error::Error>> {
         None
     };
 
-    println!(
-        "{} {} file(s) into LFS via {}...",
-        if args.dry_run {
-            "Would import"
-        } else {
-            "Importing"
-        },
-        files.len(),
-        storage.provider_name().cyan()
-    );
+    if args.dry_run || !config::is_quiet() {
+        println!(
+            "{} {} file(s) into LFS via {}...",
+            if args.dry_run {
+                "Would import"
+            } else {
+                "Importing"
+            },
+            files.len(),
+            storage.provider_name().cyan()
+        );
+    }
 
     let mut converted = 0;
+    // Converted files whose full content is still in committed history
+    let mut in_history = 0;
+    let mut staged: Vec<PathBuf> = Vec::new();
     let mut skipped = 0;
     let mut errors = 0;
 
+    // Skip files already converted to pointers
+    let mut to_import = Vec::new();
     for file_path in &files {
-        let relative = file_path.strip_prefix(repo_root).unwrap_or(file_path);
-
-        // Skip files already converted to pointers
         if Pointer::is_pointer_file(file_path) {
             skipped += 1;
             if let Some(ref pb) = pb { pb.inc(1); }
-            continue;
+        } else {
+            to_import.push(file_path.clone());
         }
+    }
 
-        let pointer = Pointer::from_file(file_path)?;
-        let oid = pointer.sha256();
-
-        if args.dry_run {
+    if args.dry_run {
+        for file_path in &to_import {
+            let relative = file_path.strip_prefix(&repo_root).unwrap_or(file_path);
+            let pointer = Pointer::from_file(file_path)?;
             println!(
-                "  {} {} ({} bytes)",
+                "  {} {} ({} bytes{})",
                 "Would import:".cyan(),
                 relative.display(),
-                pointer.size
+                pointer.size,
+                if args.no_stage { "" } else { ", would stage" }
             );
-            continue;
         }
+    } else {
+        let jobs = args.jobs.unwrap_or_else(|| LfsConfig::transfer_jobs(&repo_root)).max(1);
+        let mut to_hash = to_import.into_iter();
+        let mut to_upload: VecDeque<(String, PathBuf)> = VecDeque::new();
+        let mut tasks = JoinSet::new();
+        // Files with identical content share an object; upload each object
+        // once. None while the upload is in flight.
+        let mut upload_results: HashMap<String, Option<bool>> = HashMap::new();
+        // Hashed files waiting for their object's upload, uploader first
+        let mut waiting: HashMap<String, Vec<(PathBuf, Pointer)>> = HashMap::new();
+
+        loop {
+            // Uploads go first so hashed files don't pile up waiting
+            while tasks.len() < jobs {
+                if let Some((oid, file_path)) = to_upload.pop_front() {
+                    let relative = file_path.strip_prefix(&repo_root).unwrap_or(&file_path).to_path_buf();
+                    tasks.spawn(async move {
+                        let result = upload_object(storage, &oid, &file_path, &relative).await;
+                        Job::Uploaded(oid, result)
+                    });
+                } else if let Some(file_path) = to_hash.next() {
+                    let version = config.pointer_version().to_string();
+                    tasks.spawn_blocking(move || {
+                        let pointer = Pointer::from_file(&file_path)
+                            .map(|pointer| pointer.with_version(&version))
+                            .map_err(|e| e.to_string());
+                        Job::Hashed(file_path, pointer)
+                    });
+                } else {
+                    break;
+                }
+            }
 
-        // Upload to storage if not already there
-        if !storage.exists(oid).await? {
-            match storage.upload(oid, file_path).await {
-                Ok(_) => {}
-                Err(e) => {
-                    if let Some(ref pb) = pb { pb.suspend(|| eprintln!("  {} {} - {}", "Failed:".red(), relative.display(), e)); }
-                    errors += 1;
-                    if let Some(ref pb) = pb { pb.inc(1); }
-                    continue;
+            let Some(job) = tasks.join_next().await else {
+                break;
+            };
+            // Hashed files whose object is now safely in storage
+            let mut uploaded: Vec<(PathBuf, Pointer)> = Vec::new();
+            let mut failed: Vec<(PathBuf, String)> = Vec::new();
+            match job? {
+                Job::Hashed(file_path, Ok(pointer)) => {
+                    let oid = pointer.sha256().to_string();
+                    match upload_results.get(&oid) {
+                        Some(Some(true)) => uploaded.push((file_path, pointer)),
+                        Some(Some(false)) => failed.push((file_path, "same content as a file that failed".to_string())),
+                        Some(None) => waiting.entry(oid).or_default().push((file_path, pointer)),
+                        None => {
+                            upload_results.insert(oid.clone(), None);
+                            to_upload.push_back((oid.clone(), file_path.clone()));
+                            waiting.entry(oid).or_default().push((file_path, pointer));
+                        }
+                    }
+                }
+                Job::Hashed(file_path, Err(e)) => failed.push((file_path, e)),
+                Job::Uploaded(oid, result) => {
+                    let waiters = waiting.remove(&oid).unwrap_or_default();
+                    upload_results.insert(oid, Some(result.is_ok()));
+                    match result {
+                        Ok(()) => uploaded = waiters,
+                        Err(e) => {
+                            for (i, (file_path, _)) in waiters.into_iter().enumerate() {
+                                let reason = if i == 0 { e.clone() } else { "same content as a file that failed".to_string() };
+                                failed.push((file_path, reason));
+                            }
+                        }
+                    }
                 }
             }
-        }
 
-        // Cache locally and replace with pointer
-        cache.put_file(oid, file_path)?;
-        pointer.write(file_path)?;
-        converted += 1;
+            for (file_path, reason) in failed {
+                let relative = file_path.strip_prefix(&repo_root).unwrap_or(&file_path);
+                report_line(pb.as_ref(), &format!("  {} {} - {}", "Failed:".red(), relative.display(), reason));
+                errors += 1;
+                if let Some(ref pb) = pb { pb.inc(1); }
+            }
+
+            for (file_path, pointer) in uploaded {
+                let relative = file_path.strip_prefix(&repo_root).unwrap_or(&file_path);
+                let oid = pointer.sha256();
+
+                // Cache locally, then replace with pointer
+                if !cache.contains(oid) {
+                    cache.put_file(oid, &file_path)?;
+                }
+                pointer.write(&file_path)?;
+                staged.push(file_path.to_path_buf());
+                converted += 1;
+                if committed_as_content(&repo, &relative.to_string_lossy()) {
+                    in_history += 1;
+                }
 
-        if let Some(ref pb) = pb { pb.inc(1); }
+                if let Some(ref pb) = pb { pb.inc(1); }
+            }
+        }
     }
 
     if let Some(pb) = pb { pb.finish_and_clear(); }
 
+    // Stage converted pointers so the working tree is ready to commit
+    if !args.dry_run && !args.no_stage && !staged.is_empty() {
+        if !crate::git::add_paths(&repo_root, &staged).unwrap_or(false) {
+            eprintln!(
+                "{} Could not stage converted files. Run 'git add' manually.",
+                "Warning:".yellow()
+            );
+            staged.clear();
+        }
+    }
+
     if args.dry_run {
         println!(
             "\n{}",
             "Dry run - no files were actually imported.".yellow()
         );
+        if !args.no_stage {
+            println!("{}", "Imported files would be staged with 'git add'.".dimmed());
+        }
     } else {
         println!(
             "\n{}: {} imported, {} skipped, {} errors",
@@ -177,17 +294,44 @@ async fn run_inner(args: ImportArgs) -> Result<(), Box<dyn std::error::Error>> {
             skipped,
             errors
         );
+        if !staged.is_empty() {
+            println!(
+                "{}",
+                "Staged converted pointer files - commit to save the import".dimmed()
+            );
+        }
+        if in_history > 0 {
+            println!(
+                "{} {} imported file(s) are still stored in full in earlier commits; \
+                 import does not rewrite history.",
+                "Note:".yellow(),
+                in_history
+            );
+        }
     }
 
     if errors > 0 {
-        Err("Some files failed to import".into())
+        Err(GgError::Storage("Some files failed to import".to_string()).into())
     } else {
         Ok(())
     }
 }
 
+/// Upload an object unless storage already has it
+async fn upload_object(storage: &dyn Storage, oid: &str, file_path: &Path, relative: &Path) -> Result<(), String> {
+    match storage.exists(oid).await {
+        Ok(true) => Ok(()),
+        Ok(false) => storage
+            .upload_named(oid, file_path, relative, &|_| {})
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
 /// Find all files matching LFS patterns with optional include/exclude filters
-fn find_matching_files(
+pub(super) fn find_matching_files(
     repo_root: &Path,
     scanner: &Scanner,
     include: &Option<String>,
@@ -1,23 +1,34 @@
 //! Install/uninstall git hooks for LFS
 
-use crate::lfs::LfsConfig;
+use crate::lfs::{Cache, LfsConfig, Scanner};
+use crate::utils::{common_dir, get_repo_with_root};
 use clap::Args;
 use colored::Colorize;
 use std::fs;
+use std::io::{self, Write};
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use super::error::{report, GgError};
 
 #[derive(Args, Debug)]
 pub struct InstallArgs {
-    /// Force overwrite existing hooks
+    /// Overwrite existing hooks instead of chaining to them
     #[arg(short, long)]
     pub force: bool,
 }
 
 #[derive(Args, Debug)]
-pub struct UninstallArgs {}
+pub struct UninstallArgs {
+    /// Also delete .gg/lfs.toml and remove this repository's objects from the local cache
+    #[arg(long)]
+    pub purge: bool,
+
+    /// Don't ask before deleting .gg/lfs.toml
+    #[arg(short, long, requires = "purge")]
+    pub yes: bool,
+}
 
 /// Generate hook script content using the full path to the gg binary
 fn pre_push_hook(gg_path: &str) -> String {
@@ -41,25 +52,80 @@ fn post_merge_hook(gg_path: &str) -> String {
     )
 }
 
+/// Wrap a hook script so it first runs the user's original hook, saved as `<name>.local`
+fn chained_hook(name: &str, script: &str) -> String {
+    let local = format!("\"$(dirname \"$0\")/{}.local\" \"$@\"", name);
+    let mut chained = String::new();
+
+    for line in script.lines() {
+        match line.strip_prefix("exec ") {
+            // git sends the refs being pushed on stdin, and both hooks need them
+            Some(command) if name == "pre-push" => {
+                chained.push_str("input=$(cat)\n");
+                chained.push_str(&format!("printf '%s\\n' \"$input\" | {} || exit $?\n", local));
+                chained.push_str(&format!("printf '%s\\n' \"$input\" | exec {}\n", command));
+            }
+            Some(command) => {
+                chained.push_str(&format!("{} || exit $?\n", local));
+                chained.push_str(&format!("exec {}\n", command));
+            }
+            None => {
+                chained.push_str(line);
+                chained.push('\n');
+            }
+        }
+    }
+
+    chained
+}
+
+/// Directory git runs hooks from: `core.hooksPath` when set (e.g. `.husky`),
+/// otherwise `hooks/` in the git directory. Linked worktrees share the main
+/// checkout's hooks, so that is the common git directory rather than
+/// `.git/worktrees/<name>`. The flag is true for `core.hooksPath`.
+pub fn hooks_dir(repo: &git2::Repository) -> Result<(PathBuf, bool), git2::Error> {
+    let configured = repo.config()?.get_path("core.hooksPath").ok();
+
+    match configured {
+        Some(path) if path.is_absolute() => Ok((path, true)),
+        // Relative paths are relative to the working tree, like git does
+        Some(path) => {
+            let base = repo.workdir().unwrap_or_else(|| repo.path());
+            Ok((base.join(path), true))
+        }
+        None => Ok((common_dir(repo).join("hooks"), false)),
+    }
+}
+
+/// Object key prefix for a repository sharing a bucket: its directory name
+fn default_prefix(repo_root: &Path) -> String {
+    let name = repo_root
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "lfs".to_string());
+    format!("{}/", name)
+}
+
 /// Install LFS hooks
 pub fn run(args: InstallArgs) -> i32 {
     match run_inner(args) {
         Ok(_) => 0,
-        Err(e) => {
-            eprintln!("{} {}", "Error:".red().bold(), e);
-            1
-        }
+        Err(e) => report(&*e),
     }
 }
 
 fn run_inner(args: InstallArgs) -> Result<(), Box<dyn std::error::Error>> {
     // Find repository root
-    let repo = git2::Repository::discover(".")?;
-    let repo_root = repo
-        .workdir()
-        .ok_or("Not a git repository with a working directory")?;
+    let (repo, repo_root) = get_repo_with_root()?;
 
-    let hooks_dir = repo_root.join(".git").join("hooks");
+    let (hooks_dir, custom_hooks_path) = hooks_dir(&repo)?;
+    if custom_hooks_path {
+        println!(
+            "{} core.hooksPath is set, installing hooks into {}",
+            "Warning:".yellow(),
+            hooks_dir.display()
+        );
+    }
     fs::create_dir_all(&hooks_dir)?;
 
     // Resolve gg binary path for hooks
@@ -75,20 +141,38 @@ fn run_inner(args: InstallArgs) -> Result<(), Box<dyn std::error::Error>> {
 
     for (name, content) in &hooks {
         let hook_path = hooks_dir.join(name);
+        let local_path = hooks_dir.join(format!("{}.local", name));
+        let mut content = content.clone();
 
         if hook_path.exists() && !args.force {
             // Check if it's our hook
             let existing = fs::read_to_string(&hook_path)?;
             if !existing.contains("gg-lfs") {
+                // Keep the existing hook (lint, tests, husky...) running
+                // alongside ours, unless that would clobber another .local
+                if local_path.exists() {
+                    println!(
+                        "{} {} and {}.local both exist (use -f to overwrite)",
+                        "Skipping:".yellow(),
+                        name,
+                        name
+                    );
+                    continue;
+                }
+                fs::rename(&hook_path, &local_path)?;
                 println!(
-                    "{} {} exists (use -f to overwrite)",
-                    "Skipping:".yellow(),
+                    "{} existing {} hook to {}.local",
+                    "Moved:".green(),
+                    name,
                     name
                 );
-                continue;
             }
         }
 
+        if !args.force && local_path.exists() {
+            content = chained_hook(name, &content);
+        }
+
         fs::write(&hook_path, content)?;
 
         // Make executable
@@ -102,51 +186,56 @@ fn run_inner(args: InstallArgs) -> Result<(), Box<dyn std::error::Error>> {
         println!("{} {}", "Installed:".green(), name);
     }
 
-    // Create config template if it doesn't exist
-    if !LfsConfig::exists(repo_root) {
-        let config_path = LfsConfig::write_template(repo_root)?;
+    // Create a config if it doesn't exist: just a prefix when user defaults
+    // supply the storage settings, otherwise the full template
+    if !LfsConfig::config_path(&repo_root).exists() {
+        let user_path = LfsConfig::user_config_path().filter(|path| path.exists());
+        let config_path = match &user_path {
+            Some(user_path) => LfsConfig::write_override(&repo_root, user_path, &default_prefix(&repo_root))?,
+            None => LfsConfig::write_template(&repo_root)?,
+        };
         println!(
             "{} {}",
             "Created:".green(),
-            config_path.strip_prefix(repo_root).unwrap_or(&config_path).display()
-        );
-        println!(
-            "{}",
-            "Edit .gg/lfs.toml to configure your storage backend".cyan()
+            config_path.strip_prefix(&repo_root).unwrap_or(&config_path).display()
         );
+        match &user_path {
+            Some(user_path) => println!(
+                "{}",
+                format!("Storage settings come from {}; .gg/lfs.toml overrides the prefix", user_path.display()).cyan()
+            ),
+            None => println!(
+                "{}",
+                "Edit .gg/lfs.toml to configure your storage backend".cyan()
+            ),
+        }
     }
 
     // Add .gg/ to .gitignore if not already there
-    add_to_gitignore(repo_root)?;
+    add_to_gitignore(&repo_root)?;
 
     // Migrate old filter name if needed
-    migrate_filter_name(repo_root)?;
+    migrate_filter_name(&repo_root)?;
 
     // Register filter driver in git config
-    register_filter_driver(repo_root)?;
+    register_filter_driver(&repo_root)?;
 
     println!("{}", "LFS hooks installed successfully!".green().bold());
     Ok(())
 }
 
 /// Uninstall LFS hooks
-pub fn run_uninstall(_args: UninstallArgs) -> i32 {
-    match run_uninstall_inner() {
+pub fn run_uninstall(args: UninstallArgs) -> i32 {
+    match run_uninstall_inner(args) {
         Ok(_) => 0,
-        Err(e) => {
-            eprintln!("{} {}", "Error:".red().bold(), e);
-            1
-        }
+        Err(e) => report(&*e),
     }
 }
 
-fn run_uninstall_inner() -> Result<(), Box<dyn std::error::Error>> {
-    let repo = git2::Repository::discover(".")?;
-    let repo_root = repo
-        .workdir()
-        .ok_or("Not a git repository with a working directory")?;
+fn run_uninstall_inner(args: UninstallArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let (repo, repo_root) = get_repo_with_root()?;
 
-    let hooks_dir = repo_root.join(".git").join("hooks");
+    let (hooks_dir, _) = hooks_dir(&repo)?;
 
     let hooks = ["pre-push", "post-checkout", "post-merge"];
 
@@ -158,6 +247,13 @@ fn run_uninstall_inner() -> Result<(), Box<dyn std::error::Error>> {
             if content.contains("gg-lfs") {
                 fs::remove_file(&hook_path)?;
                 println!("{} {}", "Removed:".green(), name);
+
+                // Put back the hook we chained to on install
+                let local_path = hooks_dir.join(format!("{}.local", name));
+                if local_path.exists() {
+                    fs::rename(&local_path, &hook_path)?;
+                    println!("{} {} from {}.local", "Restored:".green(), name, name);
+                }
             } else {
                 println!(
                     "{} {} (not a gg-lfs hook)",
@@ -169,12 +265,76 @@ fn run_uninstall_inner() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     // Remove filter driver from git config
-    unregister_filter_driver(repo_root);
+    unregister_filter_driver(&repo_root);
+
+    if args.purge {
+        purge(&repo, &repo_root, args.yes)?;
+    }
 
     println!("{}", "LFS hooks uninstalled.".green().bold());
     Ok(())
 }
 
+/// Delete the repo's LFS config and its objects in the cache
+fn purge(repo: &git2::Repository, repo_root: &Path, yes: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let config_path = LfsConfig::config_path(repo_root);
+    if config_path.exists() {
+        if yes || confirm("Delete .gg/lfs.toml? It holds your storage credentials.")? {
+            fs::remove_file(&config_path)?;
+            println!("{} .gg/lfs.toml", "Removed:".green());
+        } else {
+            println!("{} .gg/lfs.toml", "Kept:".yellow());
+        }
+    }
+
+    // The cache is shared by every repository, so only objects this one
+    // references on any ref go
+    let scanner = Scanner::new(repo_root)?;
+    let referenced = super::status::referenced_oids(repo, &scanner, &[], true)?;
+    let cache = Cache::new()?;
+    let mut count = 0;
+    let mut size = 0;
+    for oid in &referenced {
+        let Some(path) = cache.get(oid) else { continue };
+        let object_size = fs::metadata(&path).map(|metadata| metadata.len()).unwrap_or(0);
+        if cache.remove(oid)? {
+            count += 1;
+            size += object_size;
+        }
+    }
+    println!(
+        "{} {} cached objects ({}) referenced by this repository",
+        "Removed:".green(),
+        count,
+        super::status::format_size(size)
+    );
+
+    Ok(())
+}
+
+/// Ask a yes/no question on stdin. Anything but "y"/"yes" is a no.
+fn confirm(question: &str) -> io::Result<bool> {
+    print!("{} [y/N] ", question);
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let answer = input.trim().to_lowercase();
+    Ok(answer == "y" || answer == "yes")
+}
+
+/// Git config key marking the repository as set up for gg lfs, for tools
+/// that look for LFS support in git config
+pub const ENABLED_KEY: &str = "gg-lfs.enabled";
+
+/// Git config key recording the gg version that installed the filter driver
+pub const VERSION_KEY: &str = "gg-lfs.version";
+
+/// The gg version that last ran `gg lfs install` in this repository, if any
+pub fn installed_version(repo: &git2::Repository) -> Option<String> {
+    repo.config().ok()?.get_string(VERSION_KEY).ok()
+}
+
 /// Register the gg lfs filter driver in git config
 pub fn register_filter_driver(repo_root: &Path) -> Result<(), Box<dyn std::error::Error>> {
     // Use the full path to the current binary so the filter works even if
@@ -191,6 +351,8 @@ pub fn register_filter_driver(repo_root: &Path) -> Result<(), Box<dyn std::error
         ("filter.gg-lfs.smudge", smudge_cmd.as_str()),
         ("filter.gg-lfs.process", process_cmd.as_str()),
         ("filter.gg-lfs.required", "true"),
+        (ENABLED_KEY, "true"),
+        (VERSION_KEY, env!("CARGO_PKG_VERSION")),
     ];
 
     for (key, value) in configs {
@@ -199,7 +361,7 @@ pub fn register_filter_driver(repo_root: &Path) -> Result<(), Box<dyn std::error
             .current_dir(repo_root)
             .status()?;
         if !status.success() {
-            return Err(format!("Failed to set {}", key).into());
+            return Err(GgError::Git(format!("Failed to set {}", key)).into());
         }
     }
 
@@ -210,7 +372,14 @@ pub fn register_filter_driver(repo_root: &Path) -> Result<(), Box<dyn std::error
 /// Remove the gg lfs filter driver from git config
 pub fn unregister_filter_driver(repo_root: &Path) {
     // Always remove our gg-lfs keys
-    for key in ["filter.gg-lfs.clean", "filter.gg-lfs.smudge", "filter.gg-lfs.required", "filter.gg-lfs.process"] {
+    for key in [
+        "filter.gg-lfs.clean",
+        "filter.gg-lfs.smudge",
+        "filter.gg-lfs.required",
+        "filter.gg-lfs.process",
+        ENABLED_KEY,
+        VERSION_KEY,
+    ] {
         let _ = Command::new("git")
             .args(["config", "--unset", key])
             .current_dir(repo_root)
@@ -0,0 +1,233 @@
+//! Lock files so two people don't edit the same binary asset
+//!
+//! Locks live in the bucket next to the objects, one small JSON object per
+//! path. They're advisory: `gg lfs push` warns before pushing a file
+//! someone else has locked, but nothing stops the push.
+
+use super::error::{report, GgError};
+use crate::lfs::storage::{self, Lock, Storage};
+use crate::lfs::LfsConfig;
+use crate::utils::{format_relative_time, get_repo_with_root, unix_now};
+use clap::Args;
+use colored::Colorize;
+use std::path::{Component, Path};
+
+#[derive(Args, Debug)]
+pub struct LockArgs {
+    /// Path to lock, relative to the current directory
+    pub path: String,
+}
+
+#[derive(Args, Debug)]
+pub struct UnlockArgs {
+    /// Path to unlock, relative to the current directory
+    pub path: String,
+
+    /// Remove the lock even if someone else holds it
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct LocksArgs {
+    /// Only show locks you hold
+    #[arg(long)]
+    pub mine: bool,
+}
+
+/// Lock a file
+pub fn run(args: LockArgs) -> i32 {
+    block_on(run_lock(args))
+}
+
+/// Release a lock
+pub fn run_unlock(args: UnlockArgs) -> i32 {
+    block_on(run_unlock_inner(args))
+}
+
+/// List current locks
+pub fn run_locks(args: LocksArgs) -> i32 {
+    block_on(run_locks_inner(args))
+}
+
+fn block_on(command: impl std::future::Future<Output = Result<(), Box<dyn std::error::Error>>>) -> i32 {
+    let rt = match crate::lfs::runtime() {
+        Ok(rt) => rt,
+        Err(e) => {
+            eprintln!("{} Failed to create async runtime: {}", "Error:".red().bold(), e);
+            return 1;
+        }
+    };
+
+    match rt.block_on(command) {
+        Ok(_) => 0,
+        Err(e) => report(&*e),
+    }
+}
+
+async fn run_lock(args: LockArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let session = LockSession::open().await?;
+    let path = lock_path(&session.repo_root, &std::env::current_dir()?, &args.path)?;
+
+    if session.storage.create_lock(&Lock::new(&path, &session.owner)).await? {
+        println!("{} {}", "Locked:".green(), path);
+        return Ok(());
+    }
+
+    match session.storage.read_lock(&path).await? {
+        Some(lock) if lock.owner == session.owner => {
+            println!("{} {} (already locked by you)", "Locked:".green(), path);
+            Ok(())
+        }
+        Some(lock) => Err(format!("{} is locked by {} ({})", path, lock.owner, locked_age(&lock)).into()),
+        // Released between the two requests
+        None => Err(format!("{} was locked and released by someone else; try again", path).into()),
+    }
+}
+
+async fn run_unlock_inner(args: UnlockArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let session = LockSession::open().await?;
+    let path = lock_path(&session.repo_root, &std::env::current_dir()?, &args.path)?;
+
+    let Some(lock) = session.storage.read_lock(&path).await? else {
+        return Err(format!("{} is not locked", path).into());
+    };
+    if lock.owner != session.owner && !args.force {
+        return Err(format!("{} is locked by {}; use --force to remove their lock", path, lock.owner).into());
+    }
+
+    if !session.storage.delete_lock(&lock).await? {
+        return Err(format!("{} was locked again while unlocking; try again", path).into());
+    }
+    if lock.owner == session.owner {
+        println!("{} {}", "Unlocked:".green(), path);
+    } else {
+        println!("{} {} (was locked by {})", "Unlocked:".green(), path, lock.owner);
+    }
+    Ok(())
+}
+
+async fn run_locks_inner(args: LocksArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let session = LockSession::open().await?;
+    let locks: Vec<Lock> = session
+        .storage
+        .list_locks()
+        .await?
+        .into_iter()
+        .filter(|lock| !args.mine || lock.owner == session.owner)
+        .collect();
+
+    if locks.is_empty() {
+        println!("{}", "No locks.".dimmed());
+        return Ok(());
+    }
+
+    let width = locks.iter().map(|lock| lock.path.len()).max().unwrap_or(0);
+    for lock in &locks {
+        let owner = if lock.owner == session.owner {
+            format!("{} (you)", lock.owner).green().to_string()
+        } else {
+            lock.owner.clone()
+        };
+        println!("{:<width$}  {}  {}", lock.path, owner, locked_age(lock).dimmed(), width = width);
+    }
+    Ok(())
+}
+
+/// What the lock commands need: storage and who is asking
+struct LockSession {
+    repo_root: std::path::PathBuf,
+    storage: &'static dyn Storage,
+    owner: String,
+}
+
+impl LockSession {
+    async fn open() -> Result<Self, Box<dyn std::error::Error>> {
+        let (repo, repo_root) = get_repo_with_root()?;
+        let owner = lock_owner(&repo)?;
+        let config = LfsConfig::load(&repo_root).map_err(|e| {
+            GgError::Config(format!("{}\nRun 'gg lfs install' to create a configuration file.", e))
+        })?;
+        let storage = storage::shared_storage(&config).await?;
+        Ok(Self { repo_root, storage, owner })
+    }
+}
+
+/// Who locks are taken for: git's `user.email`, else `user.name`
+pub(super) fn lock_owner(repo: &git2::Repository) -> Result<String, GgError> {
+    let config = repo.config().map_err(|e| GgError::Git(e.to_string()))?;
+    ["user.email", "user.name"]
+        .into_iter()
+        .filter_map(|key| config.get_string(key).ok())
+        .find(|value| !value.trim().is_empty())
+        .ok_or_else(|| GgError::Config("Set git's user.email to identify your locks".to_string()))
+}
+
+/// Warn about files about to be pushed that someone else has locked.
+/// Locks are advisory, so a failure to read them is ignored.
+pub(super) async fn warn_locked_by_others(storage: &dyn Storage, owner: &str, paths: &[String]) {
+    let Ok(locks) = storage.list_locks().await else { return };
+    for lock in locks.iter().filter(|lock| lock.owner != owner && paths.contains(&lock.path)) {
+        eprintln!(
+            "{} {} is locked by {} ({})",
+            "Warning:".yellow(),
+            lock.path,
+            lock.owner,
+            locked_age(lock)
+        );
+    }
+}
+
+/// Repository-relative path with `/` separators, the form locks are keyed by
+pub(super) fn lock_key_path(relative: &Path) -> String {
+    relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Resolve a path given relative to `current_dir` to the form locks use.
+/// Paths outside the repository are rejected.
+fn lock_path(repo_root: &Path, current_dir: &Path, path: &str) -> Result<String, String> {
+    let mut resolved = std::path::PathBuf::new();
+    for component in current_dir.join(path).components() {
+        match component {
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::CurDir => {}
+            component => resolved.push(component),
+        }
+    }
+
+    match resolved.strip_prefix(repo_root) {
+        Ok(relative) if relative.as_os_str().is_empty() => Err(format!("'{}' is the repository root, not a file", path)),
+        Ok(relative) => Ok(lock_key_path(relative)),
+        Err(_) => Err(format!("'{}' is outside the repository", path)),
+    }
+}
+
+fn locked_age(lock: &Lock) -> String {
+    format!("locked {}", format_relative_time(unix_now() - lock.locked_at))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_path_resolves_from_subdirectory() {
+        let root = Path::new("/work/repo");
+        assert_eq!(lock_path(root, &root.join("art"), "hero.psd").unwrap(), "art/hero.psd");
+        assert_eq!(lock_path(root, &root.join("art"), "../docs/./a.psd").unwrap(), "docs/a.psd");
+        assert_eq!(lock_path(root, root, "a.bin").unwrap(), "a.bin");
+    }
+
+    #[test]
+    fn test_lock_path_rejects_paths_outside_repository() {
+        let root = Path::new("/work/repo");
+        assert!(lock_path(root, root, "../other/a.bin").is_err());
+        assert!(lock_path(root, &root.join("art"), "..").is_err());
+    }
+}
@@ -1,34 +1,38 @@
 //! List LFS-tracked files
 
-use crate::lfs::{Pointer, Scanner};
+use crate::lfs::{Cache, Pointer, Scanner};
+use crate::utils::repo_root;
 use clap::Args;
 use colored::Colorize;
+use super::error::report;
 
 #[derive(Args, Debug)]
 pub struct LsFilesArgs {
     /// Show OID and size for each file
     #[arg(short, long)]
     pub long: bool,
+
+    /// Only list pointer files whose object is missing from the local cache
+    #[arg(long)]
+    pub not_cached: bool,
+
+    /// Only list files whose OID starts with this prefix
+    #[arg(long, value_name = "PREFIX")]
+    pub oid: Option<String>,
 }
 
 /// List LFS-tracked files
 pub fn run(args: LsFilesArgs) -> i32 {
     match run_inner(args) {
         Ok(_) => 0,
-        Err(e) => {
-            eprintln!("{} {}", "Error:".red().bold(), e);
-            1
-        }
+        Err(e) => report(&*e),
     }
 }
 
 fn run_inner(args: LsFilesArgs) -> Result<(), Box<dyn std::error::Error>> {
-    let repo = git2::Repository::discover(".")?;
-    let repo_root = repo
-        .workdir()
-        .ok_or("Not a git repository with a working directory")?;
+    let repo_root = repo_root()?;
 
-    let scanner = Scanner::new(repo_root)?;
+    let scanner = Scanner::new(&repo_root)?;
 
     if scanner.patterns().is_empty() {
         println!("{}", "No LFS patterns defined.".dimmed());
@@ -42,28 +46,54 @@ fn run_inner(args: LsFilesArgs) -> Result<(), Box<dyn std::error::Error>> {
         return Ok(());
     }
 
+    let cache = if args.not_cached { Some(Cache::new()?) } else { None };
+    let oid_prefix = args
+        .oid
+        .as_deref()
+        .map(|p| p.trim_start_matches("sha256:").to_lowercase());
+    let filtered = cache.is_some() || oid_prefix.is_some();
+    let mut listed = 0;
+
     for file_path in &files {
-        let relative = file_path.strip_prefix(repo_root).unwrap_or(file_path);
+        let relative = file_path.strip_prefix(&repo_root).unwrap_or(file_path);
+        let is_pointer = Pointer::is_pointer_file(file_path);
 
-        if args.long {
-            let (oid_short, size, kind) = if Pointer::is_pointer_file(file_path) {
-                match Pointer::parse(file_path) {
-                    Ok(p) => {
-                        let oid = p.sha256();
-                        let short = if oid.len() > 12 { &oid[..12] } else { oid };
-                        (short.to_string(), p.size, "pointer")
-                    }
-                    Err(_) => ("???".to_string(), 0, "pointer"),
-                }
+        // Hashing expanded files is slow, so only do it when something needs the OID
+        let pointer = if args.long || filtered {
+            if is_pointer {
+                Pointer::parse(file_path).ok()
             } else {
-                match Pointer::from_file(file_path) {
-                    Ok(p) => {
-                        let oid = p.sha256();
-                        let short = if oid.len() > 12 { &oid[..12] } else { oid };
-                        (short.to_string(), p.size, "real")
-                    }
-                    Err(_) => ("???".to_string(), 0, "real"),
+                Pointer::from_file(file_path).ok()
+            }
+        } else {
+            None
+        };
+
+        if let Some(cache) = &cache {
+            // Expanded files are already on disk; only pointers would be downloaded
+            let missing = is_pointer && pointer.as_ref().is_some_and(|p| !cache.contains(p.sha256()));
+            if !missing {
+                continue;
+            }
+        }
+
+        if let Some(prefix) = &oid_prefix {
+            if !pointer.as_ref().is_some_and(|p| p.sha256().starts_with(prefix.as_str())) {
+                continue;
+            }
+        }
+
+        listed += 1;
+
+        if args.long {
+            let kind = if is_pointer { "pointer" } else { "real" };
+            let (oid_short, size) = match &pointer {
+                Some(p) => {
+                    let oid = p.sha256();
+                    let short = if oid.len() > 12 { &oid[..12] } else { oid };
+                    (short.to_string(), p.size)
                 }
+                None => ("???".to_string(), 0),
             };
             println!(
                 "{} {:>10}  {} ({})",
@@ -77,6 +107,10 @@ fn run_inner(args: LsFilesArgs) -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
+    if filtered && listed == 0 {
+        println!("{}", "No LFS files match.".dimmed());
+    }
+
     Ok(())
 }
 
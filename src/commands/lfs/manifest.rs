@@ -0,0 +1,66 @@
+//! Machine-readable dry-run output for push and pull
+//!
+//! `--dry-run --json` prints one JSON array instead of the colored
+//! "Would upload:" lines, so automation can total up a transfer.
+
+use crate::lfs::Pointer;
+use serde::Serialize;
+use std::path::Path;
+
+/// What a real run would do with an object
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    /// Send to remote storage
+    Upload,
+    /// Fetch from remote storage
+    Download,
+    /// Copy from the local cache
+    Cached,
+    /// Nothing to transfer: the same object is already listed, or storage
+    /// already has it
+    Skip,
+}
+
+/// One file in a dry-run manifest
+#[derive(Serialize, Debug)]
+pub struct ManifestEntry {
+    pub path: String,
+    pub oid: String,
+    pub size: u64,
+    pub action: Action,
+}
+
+impl ManifestEntry {
+    pub fn new(relative: &Path, pointer: &Pointer, action: Action) -> Self {
+        Self {
+            path: relative.to_string_lossy().replace('\\', "/"),
+            oid: pointer.sha256().to_string(),
+            size: pointer.size,
+            action,
+        }
+    }
+}
+
+/// Print a manifest to stdout as a JSON array
+pub fn print(entries: &[ManifestEntry]) -> Result<(), serde_json::Error> {
+    println!("{}", serde_json::to_string_pretty(entries)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_serializes_lowercase_actions() {
+        let pointer = Pointer::from_bytes(b"hello");
+        let entries = [ManifestEntry::new(Path::new("assets/a.bin"), &pointer, Action::Upload)];
+        let json: serde_json::Value = serde_json::to_value(&entries).unwrap();
+
+        assert_eq!(json[0]["path"], "assets/a.bin");
+        assert_eq!(json[0]["oid"], pointer.sha256());
+        assert_eq!(json[0]["size"], 5);
+        assert_eq!(json[0]["action"], "upload");
+    }
+}
@@ -10,15 +10,29 @@
 //! 3. Upload each object from the git-lfs cache to S3
 //! 4. Cache in gg's local cache
 //! 5. Uninstall git-lfs hooks (optional)
+//!
+//! Progress is saved to .gg/migrate-state.json, so an interrupted migration
+//! skips already-migrated objects on the next run without asking storage.
 
+use super::error::{report, GgError};
+use super::progress::report_line;
+use crate::config;
+use crate::lfs::config::KeyLayout;
 use crate::lfs::storage;
 use crate::lfs::{Cache, LfsConfig, Pointer, Scanner};
+use crate::utils::{common_dir, get_repo_with_root};
 use clap::Args;
 use colored::Colorize;
 use indicatif::{ProgressBar, ProgressStyle};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// How often completed OIDs are written to the state file during a migration
+const STATE_SAVE_INTERVAL: Duration = Duration::from_secs(2);
 
 #[derive(Args, Debug)]
 pub struct MigrateArgs {
@@ -33,11 +47,19 @@ pub struct MigrateArgs {
     /// Keep git-lfs installed (don't remove git-lfs hooks/config)
     #[arg(long)]
     pub keep_gitlfs: bool,
+
+    /// Only migrate files matching glob pattern
+    #[arg(short, long)]
+    pub include: Option<String>,
+
+    /// Skip files matching glob pattern
+    #[arg(short, long)]
+    pub exclude: Option<String>,
 }
 
 /// Migrate from git-lfs to gg lfs
 pub fn run(args: MigrateArgs) -> i32 {
-    let rt = match tokio::runtime::Runtime::new() {
+    let rt = match crate::lfs::runtime() {
         Ok(rt) => rt,
         Err(e) => {
             eprintln!(
@@ -52,46 +74,45 @@ pub fn run(args: MigrateArgs) -> i32 {
     rt.block_on(async {
         match run_inner(args).await {
             Ok(_) => 0,
-            Err(e) => {
-                eprintln!("{} {}", "Error:".red().bold(), e);
-                1
-            }
+            Err(e) => report(&*e),
         }
     })
 }
 
 async fn run_inner(args: MigrateArgs) -> Result<(), Box<dyn std::error::Error>> {
-    let repo = git2::Repository::discover(".")?;
-    let repo_root = repo
-        .workdir()
-        .ok_or("Not a git repository with a working directory")?;
+    let (repo, repo_root) = get_repo_with_root()?;
 
     // Step 1: Verify git-lfs is available
-    println!("{}", "Checking git-lfs...".dimmed());
+    if !config::is_quiet() {
+        println!("{}", "Checking git-lfs...".dimmed());
+    }
     if !is_gitlfs_installed() {
-        return Err("git-lfs is not installed. Install it first: https://git-lfs.com".into());
+        return Err(GgError::Config("git-lfs is not installed. Install it first: https://git-lfs.com".to_string()).into());
     }
 
     // Check that the repo actually uses git-lfs
-    let scanner = Scanner::new(repo_root)?;
+    let scanner = Scanner::new(&repo_root)?;
     let patterns = scanner.patterns();
     if patterns.is_empty() {
-        return Err(
-            "No LFS patterns found in .gitattributes. Is this repo using git-lfs?".into(),
-        );
+        return Err(GgError::Config(
+            "No LFS patterns found in .gitattributes. Is this repo using git-lfs?".to_string(),
+        )
+        .into());
     }
 
-    println!(
-        "  Found {} LFS pattern(s) in .gitattributes",
-        patterns.len()
-    );
+    if !config::is_quiet() {
+        println!(
+            "  Found {} LFS pattern(s) in .gitattributes",
+            patterns.len()
+        );
+    }
 
     // Step 2: Load gg lfs config (must have run 'gg lfs install' first)
-    let config = LfsConfig::load(repo_root).map_err(|e| {
-        format!(
+    let config = LfsConfig::load(&repo_root).map_err(|e| {
+        GgError::Config(format!(
             "{}\nRun 'gg lfs install' first to configure S3 storage.",
             e
-        )
+        ))
     })?;
 
     let storage = storage::create_storage(&config).await?;
@@ -106,24 +127,37 @@ async fn run_inner(args: MigrateArgs) -> Result<(), Box<dyn std::error::Error>>
                 .args(["lfs", "fetch", "--all"])
                 .status()?;
             if !status.success() {
-                return Err("'git lfs fetch --all' failed. Ensure you have access to the git-lfs server.".into());
+                return Err(GgError::Git(
+                    "'git lfs fetch --all' failed. Ensure you have access to the git-lfs server.".to_string(),
+                )
+                .into());
             }
         }
     }
 
     // Step 4: Find the git-lfs cache directory
-    let lfs_objects_dir = repo_root.join(".git").join("lfs").join("objects");
-    if !lfs_objects_dir.exists() && !args.dry_run {
-        return Err(format!(
+    // Older git-lfs versions and `lfs.storage` overrides keep objects elsewhere
+    // Linked worktrees share the main checkout's .git, so use the common dir
+    let git_dir = common_dir(&repo);
+    let lfs_objects_dir = git_dir.join("lfs").join("objects");
+    let lfs_storage_dir = gitlfs_storage_dir(&repo_root, &git_dir);
+    let mut layouts: Vec<(ObjectLayout, usize)> = Vec::new();
+    if !lfs_objects_dir.exists()
+        && !lfs_storage_dir.as_ref().is_some_and(|d| d.exists())
+        && !args.dry_run
+    {
+        return Err(GgError::Git(format!(
             "git-lfs cache not found at {}. Run 'git lfs fetch --all' first.",
             lfs_objects_dir.display()
-        )
+        ))
         .into());
     }
 
     // Step 5: Find all files tracked by LFS and upload to S3
     let cache = Cache::new()?;
-    let files = scanner.scan_files()?;
+    let files =
+        super::import::find_matching_files(&repo_root, &scanner, &args.include, &args.exclude)?;
+    let filtered = args.include.is_some() || args.exclude.is_some();
 
     // Separate into pointer files and real files
     let mut pointer_files: Vec<(PathBuf, Pointer)> = Vec::new();
@@ -143,11 +177,15 @@ async fn run_inner(args: MigrateArgs) -> Result<(), Box<dyn std::error::Error>>
 
     let total = pointer_files.len() + real_files.len();
     if total == 0 {
-        println!("{}", "No LFS files found to migrate.".dimmed());
+        if filtered {
+            println!("{}", "No LFS files match the include/exclude filters.".dimmed());
+        } else {
+            println!("{}", "No LFS files found to migrate.".dimmed());
+        }
         return Ok(());
     }
 
-    let show_progress = !args.dry_run && std::io::stderr().is_terminal();
+    let show_progress = !args.dry_run && std::io::stderr().is_terminal() && !config::is_quiet();
     let pb = if show_progress {
         let pb = ProgressBar::new(total as u64);
         pb.set_style(ProgressStyle::default_bar()
@@ -170,6 +208,18 @@ async fn run_inner(args: MigrateArgs) -> Result<(), Box<dyn std::error::Error>>
         pointer_files.len(),
         real_files.len()
     );
+    if config.storage.key_layout == KeyLayout::GitLfs {
+        println!("  Using git-lfs object keys (objects/ab/cd/<oid>); objects already in the bucket are skipped");
+    }
+
+    let mut state = MigrateState::load(&repo_root, &state_target(&config));
+    if state.len() > 0 {
+        println!(
+            "  {} skipping {} object(s) migrated by an earlier run",
+            "Resuming:".cyan(),
+            state.len()
+        );
+    }
 
     let mut uploaded = 0;
     let mut skipped = 0;
@@ -177,7 +227,7 @@ async fn run_inner(args: MigrateArgs) -> Result<(), Box<dyn std::error::Error>>
 
     // Handle pointer files: find real content in git-lfs cache, upload to S3
     for (file_path, pointer) in &pointer_files {
-        let relative = file_path.strip_prefix(repo_root).unwrap_or(file_path);
+        let relative = file_path.strip_prefix(&repo_root).unwrap_or(file_path);
         let oid = pointer.sha256();
 
         if args.dry_run {
@@ -190,31 +240,44 @@ async fn run_inner(args: MigrateArgs) -> Result<(), Box<dyn std::error::Error>>
             continue;
         }
 
+        // Migrated by an interrupted run: already uploaded and cached
+        if state.is_done(oid) {
+            skipped += 1;
+            if let Some(ref pb) = pb { pb.inc(1); }
+            continue;
+        }
+
         // Check if already in S3
         if storage.exists(oid).await? {
-            cache_from_gitlfs(&lfs_objects_dir, oid, &cache);
+            let layout = cache_from_gitlfs(&lfs_objects_dir, lfs_storage_dir.as_deref(), oid, &cache);
+            if let Some(layout) = layout {
+                record_layout(&mut layouts, layout);
+            }
+            state.complete(oid);
             skipped += 1;
             if let Some(ref pb) = pb { pb.inc(1); }
             continue;
         }
 
         // Find the real file in git-lfs cache
-        let lfs_cached = find_gitlfs_object(&lfs_objects_dir, oid);
+        let lfs_cached = locate_gitlfs_object(&lfs_objects_dir, lfs_storage_dir.as_deref(), oid);
         match lfs_cached {
-            Some(lfs_path) => {
-                match storage.upload(oid, &lfs_path).await {
+            Some((lfs_path, layout)) => {
+                record_layout(&mut layouts, layout);
+                match storage.upload_named(oid, &lfs_path, relative, &|_| {}).await {
                     Ok(_) => {
                         cache.put_file(oid, &lfs_path)?;
+                        state.complete(oid);
                         uploaded += 1;
                     }
                     Err(e) => {
-                        if let Some(ref pb) = pb { pb.suspend(|| eprintln!("  {} {} - {}", "Failed:".red(), relative.display(), e)); }
+                        report_line(pb.as_ref(), &format!("  {} {} - {}", "Failed:".red(), relative.display(), e));
                         errors += 1;
                     }
                 }
             }
             None => {
-                if let Some(ref pb) = pb { pb.suspend(|| eprintln!("  {} {} - not found in git-lfs cache (try 'git lfs fetch --all')", "Missing:".red(), relative.display())); }
+                report_line(pb.as_ref(), &format!("  {} {} - not found in git-lfs cache (try 'git lfs fetch --all')", "Missing:".red(), relative.display()));
                 errors += 1;
             }
         }
@@ -223,8 +286,8 @@ async fn run_inner(args: MigrateArgs) -> Result<(), Box<dyn std::error::Error>>
 
     // Handle real files (smudge-expanded): upload directly, then replace with pointer
     for file_path in &real_files {
-        let relative = file_path.strip_prefix(repo_root).unwrap_or(file_path);
-        let pointer = Pointer::from_file(file_path)?;
+        let relative = file_path.strip_prefix(&repo_root).unwrap_or(file_path);
+        let pointer = Pointer::from_file(file_path)?.with_version(config.pointer_version());
         let oid = pointer.sha256();
 
         if args.dry_run {
@@ -238,11 +301,11 @@ async fn run_inner(args: MigrateArgs) -> Result<(), Box<dyn std::error::Error>>
         }
 
         // Upload to S3 if not already there
-        if !storage.exists(oid).await? {
-            match storage.upload(oid, file_path).await {
+        if !state.is_done(oid) && !storage.exists(oid).await? {
+            match storage.upload_named(oid, file_path, relative, &|_| {}).await {
                 Ok(_) => {}
                 Err(e) => {
-                    if let Some(ref pb) = pb { pb.suspend(|| eprintln!("  {} {} - {}", "Failed:".red(), relative.display(), e)); }
+                    report_line(pb.as_ref(), &format!("  {} {} - {}", "Failed:".red(), relative.display(), e));
                     errors += 1;
                     if let Some(ref pb) = pb { pb.inc(1); }
                     continue;
@@ -253,6 +316,7 @@ async fn run_inner(args: MigrateArgs) -> Result<(), Box<dyn std::error::Error>>
         // Cache and replace with pointer
         cache.put_file(oid, file_path)?;
         pointer.write(file_path)?;
+        state.complete(oid);
         uploaded += 1;
 
         if let Some(ref pb) = pb { pb.inc(1); }
@@ -260,12 +324,46 @@ async fn run_inner(args: MigrateArgs) -> Result<(), Box<dyn std::error::Error>>
 
     if let Some(pb) = pb { pb.finish_and_clear(); }
 
+    // Keep progress for a retry, or start fresh next time once everything made it
+    if !args.dry_run {
+        if errors == 0 {
+            state.clear();
+        } else if let Err(e) = state.save() {
+            eprintln!("  {} Could not save migration progress: {}", "Warning:".yellow(), e);
+        }
+    }
+
+    // Tell the user where objects were found so a missing fetch is easier to diagnose
+    for (layout, count) in &layouts {
+        if *layout != ObjectLayout::Standard {
+            println!(
+                "  {} {} object(s) found in {} layout",
+                "Note:".cyan(),
+                count,
+                layout
+            );
+        }
+    }
+
     // Step 6: Uninstall git-lfs (unless --keep-gitlfs)
-    if !args.keep_gitlfs && !args.dry_run {
+    // Objects that failed to migrate may only exist on the git-lfs server, and
+    // a filtered migration leaves files behind that still need git-lfs
+    if errors > 0 && !args.keep_gitlfs {
+        println!(
+            "\n  {} Keeping git-lfs installed since {} file(s) failed to migrate; re-run 'gg lfs migrate' to finish",
+            "Note:".cyan(),
+            errors
+        );
+    } else if filtered && !args.keep_gitlfs {
+        println!(
+            "\n  {} Keeping git-lfs installed since only some files were migrated",
+            "Note:".cyan()
+        );
+    } else if !args.keep_gitlfs && !args.dry_run {
         println!("\n{}", "Removing git-lfs hooks...".cyan());
         let status = Command::new("git")
             .args(["lfs", "uninstall"])
-            .current_dir(repo_root)
+            .current_dir(&repo_root)
             .status();
         match status {
             Ok(s) if s.success() => println!("  {} git-lfs hooks", "Removed:".green()),
@@ -276,7 +374,7 @@ async fn run_inner(args: MigrateArgs) -> Result<(), Box<dyn std::error::Error>>
         }
 
         // Re-register gg's filter driver (git lfs uninstall nukes filter.lfs.*)
-        if let Err(e) = super::install::register_filter_driver(repo_root) {
+        if let Err(e) = super::install::register_filter_driver(&repo_root) {
             eprintln!(
                 "  {} Could not re-register filter driver: {}",
                 "Warning:".yellow(),
@@ -319,12 +417,92 @@ async fn run_inner(args: MigrateArgs) -> Result<(), Box<dyn std::error::Error>>
     }
 
     if errors > 0 {
-        Err("Some files failed to migrate".into())
+        Err(GgError::Storage("Some files failed to migrate".to_string()).into())
     } else {
         Ok(())
     }
 }
 
+/// Objects migrated so far, saved in .gg/migrate-state.json so an
+/// interrupted migration can resume. The state only applies to the storage
+/// target it was recorded for.
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct MigrateState {
+    target: String,
+    completed: BTreeSet<String>,
+    #[serde(skip)]
+    path: PathBuf,
+    /// When the state was last written; None until the first save
+    #[serde(skip)]
+    saved_at: Option<Instant>,
+}
+
+impl MigrateState {
+    /// Load the state for `target`. A missing or unreadable file, or one
+    /// recorded for another target, gives an empty state.
+    fn load(repo_root: &Path, target: &str) -> Self {
+        let path = repo_root.join(".gg").join("migrate-state.json");
+        let state = std::fs::read(&path)
+            .ok()
+            .and_then(|content| serde_json::from_slice::<Self>(&content).ok())
+            .filter(|state| state.target == target)
+            .unwrap_or_default();
+
+        Self { target: target.to_string(), path, ..state }
+    }
+
+    /// Number of objects recorded as migrated
+    fn len(&self) -> usize {
+        self.completed.len()
+    }
+
+    /// Whether an object was migrated by this or an earlier run
+    fn is_done(&self, oid: &str) -> bool {
+        self.completed.contains(oid)
+    }
+
+    /// Record a migrated object, saving every few seconds so an
+    /// interrupted run loses little progress
+    fn complete(&mut self, oid: &str) {
+        if !self.completed.insert(oid.to_string()) {
+            return;
+        }
+        if self.saved_at.is_none_or(|saved_at| saved_at.elapsed() >= STATE_SAVE_INTERVAL) {
+            let _ = self.save();
+        }
+    }
+
+    /// Write the state, via a temp file so an interrupt never leaves it partial
+    fn save(&mut self) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let temp = self.path.with_extension(format!("tmp-{}", std::process::id()));
+        std::fs::write(&temp, serde_json::to_vec(self)?)?;
+        std::fs::rename(&temp, &self.path)?;
+        self.saved_at = Some(Instant::now());
+        Ok(())
+    }
+
+    /// Remove the state after a complete migration
+    fn clear(self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Identify where objects are migrated to, so saved progress isn't reused
+/// after the bucket, prefix or key layout changes
+fn state_target(config: &LfsConfig) -> String {
+    let storage = &config.storage;
+    format!(
+        "{}|{}|{}|{:?}",
+        storage.endpoint.as_deref().unwrap_or(""),
+        storage.bucket,
+        storage.prefix.as_deref().unwrap_or(""),
+        storage.key_layout
+    )
+}
+
 /// Check if git-lfs is installed
 fn is_gitlfs_installed() -> bool {
     Command::new("git")
@@ -351,18 +529,162 @@ fn find_gitlfs_object(lfs_objects_dir: &Path, oid: &str) -> Option<PathBuf> {
     }
 }
 
-/// Try to cache a git-lfs object in gg's cache
-fn cache_from_gitlfs(lfs_objects_dir: &Path, oid: &str, cache: &Cache) {
-    if let Some(lfs_path) = find_gitlfs_object(lfs_objects_dir, oid) {
-        let _ = cache.put_file(oid, &lfs_path);
+/// Where a git-lfs object was found on disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ObjectLayout {
+    /// .git/lfs/objects/{oid[0..2]}/{oid[2..4]}/{oid}
+    Standard,
+    /// .git/lfs/objects/{oid} (older git-lfs versions)
+    Flat,
+    /// Under the directory configured with `lfs.storage`
+    LfsStorage,
+    /// Somewhere else below .git/lfs/objects (found by scanning)
+    Scanned,
+}
+
+impl std::fmt::Display for ObjectLayout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Standard => write!(f, "standard"),
+            Self::Flat => write!(f, "flat (legacy)"),
+            Self::LfsStorage => write!(f, "lfs.storage"),
+            Self::Scanned => write!(f, "non-standard"),
+        }
     }
 }
 
+/// Read the `lfs.storage` override, resolving relative paths against the git dir
+fn gitlfs_storage_dir(repo_root: &Path, git_dir: &Path) -> Option<PathBuf> {
+    let output = Command::new("git")
+        .args(["config", "lfs.storage"])
+        .current_dir(repo_root)
+        .output()
+        .ok()?;
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() || value.is_empty() {
+        return None;
+    }
+
+    let path = PathBuf::from(value);
+    if path.is_absolute() {
+        Some(path)
+    } else {
+        Some(git_dir.join(path))
+    }
+}
+
+/// Find a git-lfs object by OID, falling back from the standard layout to
+/// the flat legacy layout, the `lfs.storage` directory, and finally a
+/// recursive scan of the objects directory.
+fn locate_gitlfs_object(
+    lfs_objects_dir: &Path,
+    lfs_storage_dir: Option<&Path>,
+    oid: &str,
+) -> Option<(PathBuf, ObjectLayout)> {
+    if let Some(path) = find_gitlfs_object(lfs_objects_dir, oid) {
+        return Some((path, ObjectLayout::Standard));
+    }
+
+    let flat = lfs_objects_dir.join(oid);
+    if !oid.is_empty() && flat.is_file() {
+        return Some((flat, ObjectLayout::Flat));
+    }
+
+    if let Some(storage_dir) = lfs_storage_dir {
+        let storage_objects = storage_dir.join("objects");
+        let found = find_gitlfs_object(&storage_objects, oid)
+            .or_else(|| Some(storage_objects.join(oid)).filter(|p| p.is_file()));
+        if let Some(path) = found {
+            return Some((path, ObjectLayout::LfsStorage));
+        }
+    }
+
+    scan_for_object(lfs_objects_dir, oid).map(|path| (path, ObjectLayout::Scanned))
+}
+
+/// Try to cache a git-lfs object in gg's cache, returning where it was found
+fn cache_from_gitlfs(
+    lfs_objects_dir: &Path,
+    lfs_storage_dir: Option<&Path>,
+    oid: &str,
+    cache: &Cache,
+) -> Option<ObjectLayout> {
+    let (lfs_path, layout) = locate_gitlfs_object(lfs_objects_dir, lfs_storage_dir, oid)?;
+    let _ = cache.put_file(oid, &lfs_path);
+    Some(layout)
+}
+
+/// Count how many objects were found in each layout
+fn record_layout(layouts: &mut Vec<(ObjectLayout, usize)>, layout: ObjectLayout) {
+    match layouts.iter_mut().find(|(l, _)| *l == layout) {
+        Some((_, count)) => *count += 1,
+        None => layouts.push((layout, 1)),
+    }
+}
+
+/// Recursively search a directory for a file named after the OID
+fn scan_for_object(dir: &Path, oid: &str) -> Option<PathBuf> {
+    if oid.is_empty() {
+        return None;
+    }
+
+    for entry in std::fs::read_dir(dir).ok()?.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if let Some(found) = scan_for_object(&path, oid) {
+                return Some(found);
+            }
+        } else if entry.file_name() == oid {
+            return Some(path);
+        }
+    }
+
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::fs;
 
+    #[test]
+    fn test_migrate_state_round_trip() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut state = MigrateState::load(temp.path(), "bucket-a");
+        assert_eq!(state.len(), 0);
+
+        state.complete("aaa");
+        state.complete("bbb");
+        state.save().unwrap();
+
+        let state = MigrateState::load(temp.path(), "bucket-a");
+        assert!(state.is_done("aaa") && state.is_done("bbb"));
+        assert!(!state.is_done("ccc"));
+
+        state.clear();
+        assert_eq!(MigrateState::load(temp.path(), "bucket-a").len(), 0);
+    }
+
+    #[test]
+    fn test_migrate_state_ignored_for_another_target() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut state = MigrateState::load(temp.path(), "bucket-a");
+        state.complete("aaa");
+        state.save().unwrap();
+
+        assert_eq!(MigrateState::load(temp.path(), "bucket-b").len(), 0);
+    }
+
+    #[test]
+    fn test_migrate_state_saves_first_completion() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut state = MigrateState::load(temp.path(), "bucket-a");
+        state.complete("aaa");
+
+        // Written right away, so a crash after one upload still resumes
+        assert!(MigrateState::load(temp.path(), "bucket-a").is_done("aaa"));
+    }
+
     #[test]
     fn test_find_gitlfs_object_found() {
         let temp = tempfile::TempDir::new().unwrap();
@@ -408,7 +730,7 @@ mod tests {
         fs::create_dir_all(&object_dir).unwrap();
         fs::write(object_dir.join(oid), b"lfs object data").unwrap();
 
-        cache_from_gitlfs(&lfs_dir, oid, &cache);
+        cache_from_gitlfs(&lfs_dir, None, oid, &cache);
         assert!(cache.contains(oid));
         assert_eq!(cache.read(oid).unwrap(), b"lfs object data");
     }
@@ -418,7 +740,64 @@ mod tests {
         let temp = tempfile::TempDir::new().unwrap();
         let cache = crate::lfs::Cache::with_root(temp.path().join("cache")).unwrap();
 
-        cache_from_gitlfs(temp.path(), "nonexistent_oid_that_wont_be_found", &cache);
+        cache_from_gitlfs(temp.path(), None, "nonexistent_oid_that_wont_be_found", &cache);
         assert_eq!(cache.count().unwrap(), 0);
     }
+
+    #[test]
+    fn test_locate_gitlfs_object_standard_layout() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let oid = "4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393";
+        let object_dir = temp.path().join(&oid[..2]).join(&oid[2..4]);
+        fs::create_dir_all(&object_dir).unwrap();
+        fs::write(object_dir.join(oid), b"lfs object data").unwrap();
+
+        let (_, layout) = locate_gitlfs_object(temp.path(), None, oid).unwrap();
+        assert_eq!(layout, ObjectLayout::Standard);
+    }
+
+    #[test]
+    fn test_locate_gitlfs_object_flat_layout() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let oid = "4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393";
+        fs::write(temp.path().join(oid), b"lfs object data").unwrap();
+
+        let (path, layout) = locate_gitlfs_object(temp.path(), None, oid).unwrap();
+        assert_eq!(layout, ObjectLayout::Flat);
+        assert_eq!(fs::read(path).unwrap(), b"lfs object data");
+    }
+
+    #[test]
+    fn test_locate_gitlfs_object_lfs_storage() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let objects = temp.path().join("objects");
+        let storage = temp.path().join("custom");
+        let oid = "4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393";
+        let object_dir = storage.join("objects").join(&oid[..2]).join(&oid[2..4]);
+        fs::create_dir_all(&object_dir).unwrap();
+        fs::write(object_dir.join(oid), b"lfs object data").unwrap();
+
+        let (_, layout) = locate_gitlfs_object(&objects, Some(&storage), oid).unwrap();
+        assert_eq!(layout, ObjectLayout::LfsStorage);
+    }
+
+    #[test]
+    fn test_locate_gitlfs_object_scan_fallback() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let oid = "4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393";
+        let nested = temp.path().join("some").join("other").join("dir");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(nested.join(oid), b"lfs object data").unwrap();
+
+        let (path, layout) = locate_gitlfs_object(temp.path(), None, oid).unwrap();
+        assert_eq!(layout, ObjectLayout::Scanned);
+        assert_eq!(path, nested.join(oid));
+    }
+
+    #[test]
+    fn test_locate_gitlfs_object_missing() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let oid = "4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393";
+        assert!(locate_gitlfs_object(temp.path(), None, oid).is_none());
+    }
 }
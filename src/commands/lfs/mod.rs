@@ -2,13 +2,21 @@
 //!
 //! Provides commands for managing large file storage.
 
+pub mod cat;
+pub mod check_attr;
 pub mod clean;
+pub mod env;
+pub mod error;
 pub mod filter_process;
 pub mod import;
 pub mod install;
+pub mod lock;
 pub mod ls_files;
+pub mod manifest;
 pub mod migrate;
+pub mod overrides;
 pub mod prune;
+pub mod progress;
 pub mod pull;
 pub mod push;
 pub mod smudge;
@@ -18,10 +26,14 @@ pub mod verify;
 
 use clap::{Args, Subcommand};
 
+pub use cat::CatArgs;
+pub use check_attr::CheckAttrArgs;
 pub use clean::CleanArgs;
+pub use env::EnvArgs;
 pub use filter_process::FilterProcessArgs;
 pub use import::ImportArgs;
 pub use install::{InstallArgs, UninstallArgs};
+pub use lock::{LockArgs, LocksArgs, UnlockArgs};
 pub use ls_files::LsFilesArgs;
 pub use migrate::MigrateArgs;
 pub use prune::PruneArgs;
@@ -72,6 +84,24 @@ pub enum LfsCommand {
     /// List LFS-tracked files
     LsFiles(LsFilesArgs),
 
+    /// Print effective LFS configuration, paths and hook state
+    Env(EnvArgs),
+
+    /// Print an LFS object's content to stdout
+    Cat(CatArgs),
+
+    /// Show which .gitattributes pattern makes a file LFS-tracked
+    CheckAttr(CheckAttrArgs),
+
+    /// Lock a file so others know not to edit it
+    Lock(LockArgs),
+
+    /// Release a file lock
+    Unlock(UnlockArgs),
+
+    /// List file locks
+    Locks(LocksArgs),
+
     /// Prune old objects from the local LFS cache
     Prune(PruneArgs),
 
@@ -101,6 +131,12 @@ pub fn run(args: LfsArgs) -> i32 {
         LfsCommand::Pull(args) => pull::run(args),
         LfsCommand::Status(args) => status::run(args),
         LfsCommand::LsFiles(args) => ls_files::run(args),
+        LfsCommand::Env(args) => env::run(args),
+        LfsCommand::Cat(args) => cat::run(args),
+        LfsCommand::CheckAttr(args) => check_attr::run(args),
+        LfsCommand::Lock(args) => lock::run(args),
+        LfsCommand::Unlock(args) => lock::run_unlock(args),
+        LfsCommand::Locks(args) => lock::run_locks(args),
         LfsCommand::Prune(args) => prune::run(args),
         LfsCommand::Verify(args) => verify::run(args),
         LfsCommand::Clean(args) => clean::run(args),
@@ -0,0 +1,111 @@
+//! One-off storage settings for push, pull, import and verify
+//!
+//! `--bucket`, `--region`, `--prefix` and `--endpoint` replace the loaded
+//! configuration for a single run, e.g. to restore from a test bucket.
+//! They are never written back to `.gg/lfs.toml`.
+
+use crate::lfs::config::ConfigError;
+use crate::lfs::LfsConfig;
+use clap::Args;
+
+#[derive(Args, Debug, Default)]
+#[command(next_help_heading = "Storage overrides (this run only)")]
+pub struct StorageOverrides {
+    /// Use this bucket instead of the configured one
+    #[arg(long)]
+    pub bucket: Option<String>,
+
+    /// Use this region instead of the configured one
+    #[arg(long)]
+    pub region: Option<String>,
+
+    /// Use this key prefix instead of the configured one ("" for none)
+    #[arg(long)]
+    pub prefix: Option<String>,
+
+    /// Use this S3-compatible endpoint instead of the configured one
+    #[arg(long)]
+    pub endpoint: Option<String>,
+}
+
+impl StorageOverrides {
+    /// Whether any override was given
+    pub fn is_empty(&self) -> bool {
+        self.bucket.is_none() && self.region.is_none() && self.prefix.is_none() && self.endpoint.is_none()
+    }
+
+    /// Apply the overrides to a loaded configuration and validate the result.
+    /// Moving to another bucket or endpoint also drops the configured read
+    /// mirror, so nothing is read from the storage being overridden.
+    pub fn apply(&self, config: &mut LfsConfig) -> Result<(), ConfigError> {
+        let storage = &mut config.storage;
+        if let Some(bucket) = &self.bucket {
+            storage.bucket = bucket.clone();
+        }
+        if let Some(region) = &self.region {
+            storage.region = region.clone();
+        }
+        if let Some(prefix) = &self.prefix {
+            storage.prefix = Some(prefix.clone()).filter(|prefix| !prefix.is_empty());
+        }
+        if let Some(endpoint) = &self.endpoint {
+            storage.endpoint = Some(endpoint.clone());
+        }
+        if self.bucket.is_some() || self.endpoint.is_some() {
+            storage.read_bucket = None;
+            storage.read_endpoint = None;
+        }
+
+        config.validate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mirrored_config() -> LfsConfig {
+        let mut config = LfsConfig::template();
+        config.storage.read_bucket = Some("mirror".to_string());
+        config.storage.read_endpoint = Some("https://mirror.example.com".to_string());
+        config
+    }
+
+    #[test]
+    fn test_apply_replaces_storage_settings() {
+        let mut config = mirrored_config();
+        let overrides = StorageOverrides {
+            bucket: Some("test-restore".to_string()),
+            region: Some("us-west-2".to_string()),
+            prefix: Some(String::new()),
+            endpoint: None,
+        };
+
+        overrides.apply(&mut config).unwrap();
+        assert_eq!(config.storage.bucket, "test-restore");
+        assert_eq!(config.storage.region, "us-west-2");
+        assert_eq!(config.storage.prefix, None);
+        assert_eq!(config.storage.read_bucket, None);
+        assert_eq!(config.storage.read_endpoint, None);
+    }
+
+    #[test]
+    fn test_apply_without_overrides_keeps_config() {
+        let mut config = mirrored_config();
+        StorageOverrides::default().apply(&mut config).unwrap();
+        assert_eq!(config.storage.bucket, "my-lfs-bucket");
+        assert_eq!(config.storage.read_bucket.as_deref(), Some("mirror"));
+
+        // A region alone still reads from the same bucket, so the mirror stays
+        let overrides = StorageOverrides { region: Some("eu-west-1".to_string()), ..Default::default() };
+        overrides.apply(&mut config).unwrap();
+        assert_eq!(config.storage.read_bucket.as_deref(), Some("mirror"));
+    }
+
+    #[test]
+    fn test_apply_validates_merged_config() {
+        let mut config = LfsConfig::template();
+        let overrides = StorageOverrides { bucket: Some(String::new()), ..Default::default() };
+        assert!(matches!(overrides.apply(&mut config), Err(ConfigError::Invalid(_))));
+    }
+}
@@ -0,0 +1,99 @@
+//! Transfer progress for logs
+//!
+//! When stderr is a terminal, `push` and `pull` draw a progress bar. A CI
+//! log can't redraw a bar, so there they print a line per finished file
+//! instead, e.g. `[45/312] uploaded assets/tex_42.png (12.00 MB)`, at most
+//! one every few seconds.
+
+use super::status::format_size;
+use indicatif::ProgressBar;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Minimum time between two progress lines
+const LOG_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Progress lines for a transfer of `total` files
+pub(super) struct LogProgress {
+    total: u64,
+    state: Mutex<LogState>,
+}
+
+struct LogState {
+    done: u64,
+    last_line: Option<Instant>,
+}
+
+impl LogProgress {
+    pub(super) fn new(total: usize) -> Self {
+        Self {
+            total: total as u64,
+            state: Mutex::new(LogState { done: 0, last_line: None }),
+        }
+    }
+
+    /// Count `count` finished files, the last being `relative`, and print a
+    /// line for it unless one was printed recently. The final file always
+    /// gets a line.
+    pub(super) fn record(&self, count: usize, action: &str, relative: &Path, size: u64) {
+        if let Some(line) = self.record_at(Instant::now(), count, action, relative, size) {
+            eprintln!("{}", line);
+        }
+    }
+
+    fn record_at(&self, now: Instant, count: usize, action: &str, relative: &Path, size: u64) -> Option<String> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.done = (state.done + count as u64).min(self.total);
+
+        let finished = state.done == self.total;
+        let due = state.last_line.is_none_or(|last| now.duration_since(last) >= LOG_INTERVAL);
+        if !finished && !due {
+            return None;
+        }
+        state.last_line = Some(now);
+        Some(format!(
+            "[{}/{}] {} {} ({})",
+            state.done,
+            self.total,
+            action,
+            relative.display(),
+            format_size(size)
+        ))
+    }
+}
+
+/// Print a line to stderr, above the progress bar if one is drawn
+pub(super) fn report_line(pb: Option<&ProgressBar>, message: &str) {
+    match pb {
+        Some(pb) => pb.suspend(|| eprintln!("{}", message)),
+        None => eprintln!("{}", message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_progress_throttles_lines() {
+        let log = LogProgress::new(4);
+        let start = Instant::now();
+        let path = Path::new("assets/tex_42.png");
+
+        assert_eq!(
+            log.record_at(start, 1, "uploaded", path, 12 * 1024 * 1024).as_deref(),
+            Some("[1/4] uploaded assets/tex_42.png (12.00 MB)")
+        );
+        assert_eq!(log.record_at(start + Duration::from_secs(1), 1, "uploaded", path, 10), None);
+        assert_eq!(
+            log.record_at(start + LOG_INTERVAL, 1, "skipped", path, 10).as_deref(),
+            Some("[3/4] skipped assets/tex_42.png (10 bytes)")
+        );
+        // The last file is always reported
+        assert_eq!(
+            log.record_at(start + LOG_INTERVAL, 1, "failed", path, 10).as_deref(),
+            Some("[4/4] failed assets/tex_42.png (10 bytes)")
+        );
+    }
+}
@@ -1,6 +1,12 @@
 //! Prune old LFS objects from the local cache
+//!
+//! With `--verify-remote`, an object is only removed once remote storage is
+//! confirmed to have it, so objects that were never pushed are kept.
 
-use crate::lfs::Cache;
+use super::error::{report, GgError};
+use crate::lfs::storage;
+use crate::lfs::{Cache, LfsConfig};
+use crate::utils::repo_root;
 use clap::Args;
 use colored::Colorize;
 
@@ -13,16 +19,17 @@ pub struct PruneArgs {
     /// Show what would be pruned without actually removing
     #[arg(short = 'n', long)]
     pub dry_run: bool,
+
+    /// Only remove objects that exist in remote storage; keep unpushed ones
+    #[arg(long)]
+    pub verify_remote: bool,
 }
 
 /// Prune old LFS cache objects
 pub fn run(args: PruneArgs) -> i32 {
     match run_inner(args) {
         Ok(_) => 0,
-        Err(e) => {
-            eprintln!("{} {}", "Error:".red().bold(), e);
-            1
-        }
+        Err(e) => report(&*e),
     }
 }
 
@@ -43,6 +50,10 @@ fn run_inner(args: PruneArgs) -> Result<(), Box<dyn std::error::Error>> {
         format_bytes(size)
     );
 
+    if args.verify_remote {
+        return prune_verified(&cache, &args, size);
+    }
+
     if args.dry_run {
         // For dry run, just report what would happen
         println!(
@@ -74,6 +85,73 @@ fn run_inner(args: PruneArgs) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Prune stale objects that remote storage confirms it has, reporting the rest as unpushed
+fn prune_verified(cache: &Cache, args: &PruneArgs, size: u64) -> Result<(), Box<dyn std::error::Error>> {
+    let repo_root = repo_root()?;
+    let config = LfsConfig::load(repo_root).map_err(|e| {
+        GgError::Config(format!("{}\nRun 'gg lfs install' to create a configuration file.", e))
+    })?;
+
+    let stale = cache.stale_objects(args.days)?;
+    let rt = crate::lfs::runtime()?;
+    let (removable, unpushed) = rt.block_on(async {
+        let storage = storage::shared_storage(&config).await?;
+        let mut removable = Vec::new();
+        let mut unpushed = Vec::new();
+        for oid in stale {
+            if storage.exists(&oid).await? {
+                removable.push(oid);
+            } else {
+                unpushed.push(oid);
+            }
+        }
+        Ok::<_, Box<dyn std::error::Error>>((removable, unpushed))
+    })?;
+
+    if !unpushed.is_empty() {
+        println!(
+            "\n{} keeping {} unpushed object(s) not found in remote storage:",
+            "Warning:".yellow(),
+            unpushed.len()
+        );
+        for oid in &unpushed {
+            println!("  {}", oid.dimmed());
+        }
+    }
+
+    if args.dry_run {
+        println!(
+            "\n{} Would prune {} object(s) not accessed in {} day(s).",
+            "Dry run:".cyan(),
+            removable.len(),
+            args.days
+        );
+        println!("{}", "No files were actually removed.".yellow());
+        return Ok(());
+    }
+
+    for oid in &removable {
+        cache.remove(oid)?;
+    }
+    let new_size = cache.size()?;
+    if removable.is_empty() {
+        println!(
+            "\n{} No pushed objects older than {} day(s).",
+            "Done:".green().bold(),
+            args.days
+        );
+    } else {
+        println!(
+            "\n{}: pruned {} object(s), freed {}",
+            "Done".green().bold(),
+            removable.len(),
+            format_bytes(size - new_size)
+        );
+    }
+
+    Ok(())
+}
+
 fn format_bytes(bytes: u64) -> String {
     if bytes < 1024 {
         format!("{} B", bytes)
@@ -1,12 +1,23 @@
 //! Pull LFS files from remote storage
 
+use super::error::{report, GgError};
+use super::manifest::{self, Action, ManifestEntry};
+use super::overrides::StorageOverrides;
+use super::progress::{report_line, LogProgress};
+use super::status::{format_size, format_transfer};
+use crate::config;
+use crate::lfs::cache::Placed;
+use crate::lfs::config::LinkMode;
+use crate::lfs::pointer::MAX_POINTER_SIZE;
 use crate::lfs::storage;
 use crate::lfs::{Cache, LfsConfig, Pointer, Scanner};
+use crate::utils::get_repo_with_root;
 use clap::Args;
 use colored::Colorize;
 use indicatif::{ProgressBar, ProgressStyle};
 use std::io::IsTerminal;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 #[derive(Args, Debug)]
 pub struct PullArgs {
@@ -14,13 +25,21 @@ pub struct PullArgs {
     #[arg(short = 'n', long)]
     pub dry_run: bool,
 
-    /// Include only files matching pattern
-    #[arg(short, long)]
-    pub include: Option<String>,
+    /// With --dry-run, print a JSON manifest of {path, oid, size, action} instead
+    #[arg(long, requires = "dry_run")]
+    pub json: bool,
 
-    /// Exclude files matching pattern
-    #[arg(short, long)]
-    pub exclude: Option<String>,
+    /// Include only files matching pattern (repeatable or comma-separated)
+    #[arg(short, long, value_delimiter = ',')]
+    pub include: Vec<String>,
+
+    /// Exclude files matching pattern (repeatable or comma-separated)
+    #[arg(short, long, value_delimiter = ',')]
+    pub exclude: Vec<String>,
+
+    /// Also restore LFS files with uncommitted changes, discarding the changes
+    #[arg(short, long, conflicts_with_all = ["post_checkout", "post_merge"])]
+    pub force: bool,
 
     /// Called by the post-checkout hook (old-ref new-ref flag)
     #[arg(long, hide = true, num_args = 3, value_names = &["OLD_REF", "NEW_REF", "FLAG"])]
@@ -29,12 +48,15 @@ pub struct PullArgs {
     /// Called by the post-merge hook
     #[arg(long, hide = true)]
     pub post_merge: bool,
+
+    #[command(flatten)]
+    pub storage: StorageOverrides,
 }
 
 /// Pull LFS files from remote storage
 pub fn run(args: PullArgs) -> i32 {
     // Create tokio runtime for async operations
-    let rt = match tokio::runtime::Runtime::new() {
+    let rt = match crate::lfs::runtime() {
         Ok(rt) => rt,
         Err(e) => {
             eprintln!("{} Failed to create async runtime: {}", "Error:".red().bold(), e);
@@ -45,57 +67,72 @@ pub fn run(args: PullArgs) -> i32 {
     rt.block_on(async {
         match run_inner(args).await {
             Ok(_) => 0,
-            Err(e) => {
-                eprintln!("{} {}", "Error:".red().bold(), e);
-                1
-            }
+            Err(e) => report(&*e),
         }
     })
 }
 
 async fn run_inner(args: PullArgs) -> Result<(), Box<dyn std::error::Error>> {
-    let repo = git2::Repository::discover(".")?;
-    let repo_root = repo
-        .workdir()
-        .ok_or("Not a git repository with a working directory")?;
+    // A file checkout (`git checkout -- path`) doesn't move HEAD; only pull
+    // after branch checkouts
+    if args.post_checkout.as_ref().is_some_and(|checkout_args| !is_branch_checkout(checkout_args)) {
+        return Ok(());
+    }
+    // Hooks may run from an IDE or script with no terminal attached; only
+    // errors are printed then
+    let hook = args.post_checkout.is_some() || args.post_merge;
+    let quiet = config::is_quiet() || (hook && !std::io::stdout().is_terminal());
+
+    let (repo, repo_root) = get_repo_with_root()?;
 
     // Load config
-    let config = match LfsConfig::load(repo_root) {
+    let mut config = match LfsConfig::load(&repo_root) {
         Ok(c) => c,
+        Err(_) if args.json => {
+            manifest::print(&[])?;
+            return Ok(());
+        }
+        Err(_) if quiet => return Ok(()),
         Err(_) => {
             // No config, nothing to pull
-            println!("{}", "No LFS configuration found. Run 'gg lfs install' first.".dimmed());
+            println!("{} {}", "No LFS configuration found.".dimmed(), LfsConfig::setup_hint(&repo_root).dimmed());
             return Ok(());
         }
     };
+    args.storage.apply(&mut config)?;
 
     // Initialize storage
     let storage = storage::create_storage(&config).await?;
 
     // Initialize cache
     let cache = Cache::new()?;
+    let link_mode = config.cache.as_ref().map(|c| c.link_mode).unwrap_or_default();
+    // Set when a hardlink/reflink had to fall back to copying
+    let mut copied_instead = false;
 
     // Scan for LFS pointer files
-    let scanner = Scanner::new(repo_root)?;
+    let scanner = Scanner::new(&repo_root)?;
 
     let pointer_files = if let Some(ref checkout_args) = args.post_checkout {
         // Post-checkout mode: only pull files that changed between old and new refs
-        find_post_checkout_pointer_files(repo_root, &scanner, checkout_args)?
+        find_post_checkout_pointer_files(&repo_root, &scanner, checkout_args)?
     } else if args.post_merge {
         // Post-merge mode: only pull files that changed in the merge
-        find_post_merge_pointer_files(repo_root, &scanner)?
+        find_post_merge_pointer_files(&repo_root, &scanner)?
     } else {
-        find_pointer_files(repo_root, &scanner, &args)?
+        find_pointer_files(&repo, &repo_root, &scanner, &args)?
     };
 
     if pointer_files.is_empty() {
-        if args.post_checkout.is_none() && !args.post_merge {
+        if args.json {
+            manifest::print(&[])?;
+        } else if args.post_checkout.is_none() && !args.post_merge {
             println!("{}", "No LFS pointer files found.".dimmed());
         }
         return Ok(());
     }
 
-    let show_progress = !args.dry_run && std::io::stderr().is_terminal();
+    let show_progress = !args.dry_run && std::io::stderr().is_terminal() && !quiet;
     let pb = if show_progress {
         let pb = ProgressBar::new(pointer_files.len() as u64);
         pb.set_style(ProgressStyle::default_bar()
@@ -105,119 +142,253 @@ async fn run_inner(args: PullArgs) -> Result<(), Box<dyn std::error::Error>> {
     } else {
         None
     };
+    // Without a terminal to draw the bar on, log lines show progress instead
+    let log = (!args.dry_run && !show_progress && !quiet).then(|| LogProgress::new(pointer_files.len()));
 
-    println!(
-        "{} {} LFS file(s) from {}...",
-        if args.dry_run { "Would pull" } else { "Pulling" },
-        pointer_files.len(),
-        storage.provider_name().cyan()
-    );
+    // In JSON mode stdout carries only the manifest
+    let mut manifest = args.json.then(Vec::new);
+
+    if !args.json && (args.dry_run || !quiet) {
+        println!(
+            "{} {} LFS file(s) from {}...",
+            if args.dry_run { "Would pull" } else { "Pulling" },
+            pointer_files.len(),
+            storage.provider_name().cyan()
+        );
+    }
 
     let mut downloaded = 0;
     let mut cached = 0;
+    // Files left alone because they changed locally
+    let mut kept = 0;
     let mut errors = 0;
-
-    for (file_path, pointer) in &pointer_files {
-        let relative = file_path
-            .strip_prefix(repo_root)
-            .unwrap_or(file_path);
-
+    // Downloads whose content didn't match the OID, also counted in `errors`
+    let mut corrupt = 0;
+    // Bytes fetched from storage, or that would be fetched in a dry run
+    let mut transferred = 0;
+    let started = Instant::now();
+
+    // Files with identical content share an object; fetch each object once
+    for (pointer, paths) in group_by_oid(&pointer_files) {
         let oid = pointer.sha256();
 
         if args.dry_run {
-            println!(
-                "  {} {} ({} bytes)",
-                "Would download:".cyan(),
-                relative.display(),
-                pointer.size
-            );
+            let in_cache = cache.contains(oid);
+            if !in_cache {
+                transferred += pointer.size;
+            }
+            for (i, file_path) in paths.iter().enumerate() {
+                let relative = file_path.strip_prefix(&repo_root).unwrap_or(file_path);
+                match manifest.as_mut() {
+                    Some(entries) => {
+                        let action = match (i, in_cache) {
+                            (0, true) => Action::Cached,
+                            (0, false) => Action::Download,
+                            _ => Action::Skip,
+                        };
+                        entries.push(ManifestEntry::new(relative, pointer, action));
+                    }
+                    None => println!(
+                        "  {} {} ({} bytes)",
+                        "Would download:".cyan(),
+                        relative.display(),
+                        pointer.size
+                    ),
+                }
+            }
             continue;
         }
 
-        // Check cache first
-        if let Some(cached_path) = cache.get(oid) {
-            // Copy from cache
-            std::fs::copy(&cached_path, file_path)?;
-            cached += 1;
-            if let Some(ref pb) = pb { pb.inc(1); }
+        // Never replace local edits made since the scan, or any in a hook;
+        // --force only restores files it was asked to
+        let mut targets = Vec::new();
+        for file_path in paths {
+            match local_state(file_path, pointer)? {
+                LocalState::Pointer => targets.push(file_path),
+                LocalState::Modified if args.force => targets.push(file_path),
+                LocalState::Current => {
+                    cached += 1;
+                    if let Some(ref pb) = pb { pb.inc(1); }
+                    if let Some(ref log) = log {
+                        log.record(1, "up to date", file_path.strip_prefix(&repo_root).unwrap_or(file_path), pointer.size);
+                    }
+                }
+                LocalState::Modified => {
+                    let relative = file_path.strip_prefix(&repo_root).unwrap_or(file_path);
+                    report_line(pb.as_ref(), &format!("  {} {} - has local changes, use --force to overwrite", "Kept:".yellow(), relative.display()));
+                    kept += 1;
+                    if let Some(ref pb) = pb { pb.inc(1); }
+                    if let Some(ref log) = log { log.record(1, "kept", relative, pointer.size); }
+                }
+            }
+        }
+        let paths = targets;
+        let Some(&first) = paths.first() else { continue };
+        let relative = first.strip_prefix(&repo_root).unwrap_or(first);
+
+        // Check cache first, re-hashing so a corrupt entry is never copied out
+        let in_cache = cache.contains(oid);
+        if cache.get_verified(oid).is_some() {
+            // Link or copy from cache
+            for file_path in &paths {
+                copied_instead |= cache.link_to(oid, file_path, link_mode)? == Placed::Copied;
+            }
+            cached += paths.len();
+            if let Some(ref pb) = pb { pb.inc(paths.len() as u64); }
+            if let Some(ref log) = log { log.record(paths.len(), "copied from cache", relative, pointer.size); }
             continue;
         }
+        if in_cache && !quiet {
+            report_line(pb.as_ref(), &format!("  {} {} - cached object is corrupt, downloading again", "Warning:".yellow(), relative.display()));
+        }
 
-        // Download from storage
-        let temp_path = repo_root.join(".gg").join("tmp").join(oid);
+        // Download from storage, resuming any partial file left by an interrupted pull
+        let temp_path = partial_download_path(&repo_root, oid);
         if let Some(parent) = temp_path.parent() {
             std::fs::create_dir_all(parent)?;
         }
+        discard_unresumable_part(&temp_path, pointer.size)?;
 
-        match storage.download(oid, &temp_path).await {
-            Ok(_result) => {
-                // Verify hash
+        let action = match storage.download_resume(oid, &temp_path).await {
+            Ok(result) => {
+                // Verify hash and size
                 let downloaded_pointer = Pointer::from_file(&temp_path)?;
-                if downloaded_pointer.oid != pointer.oid {
-                    if let Some(ref pb) = pb { pb.suspend(|| eprintln!("  {} {} - hash mismatch!", "Error:".red(), relative.display())); }
+                let mismatch = if downloaded_pointer.oid != pointer.oid {
+                    Some("hash mismatch!".to_string())
+                } else if downloaded_pointer.size != pointer.size {
+                    // The pointer itself is malformed: its size disagrees with its object
+                    Some(format!("size mismatch! pointer says {} bytes, object has {}", pointer.size, downloaded_pointer.size))
+                } else {
+                    None
+                };
+                if let Some(mismatch) = mismatch {
+                    report_line(pb.as_ref(), &format!("  {} {} - {}", "Error:".red(), relative.display(), mismatch));
                     std::fs::remove_file(&temp_path).ok();
-                    errors += 1;
-                    if let Some(ref pb) = pb { pb.inc(1); }
+                    errors += paths.len();
+                    corrupt += paths.len();
+                    if let Some(ref pb) = pb { pb.inc(paths.len() as u64); }
+                    if let Some(ref log) = log { log.record(paths.len(), "failed", relative, pointer.size); }
                     continue;
                 }
 
                 // Cache the downloaded file
                 cache.put_file(oid, &temp_path)?;
 
-                // Move to final location
-                std::fs::rename(&temp_path, file_path)?;
+                if link_mode == LinkMode::Copy {
+                    // Copy to the duplicates, then move to the first path
+                    for file_path in &paths[1..] {
+                        std::fs::copy(&temp_path, file_path)?;
+                    }
+                    std::fs::rename(&temp_path, first)?;
+                } else {
+                    for file_path in &paths {
+                        copied_instead |= cache.link_to(oid, file_path, link_mode)? == Placed::Copied;
+                    }
+                    std::fs::remove_file(&temp_path)?;
+                }
 
-                downloaded += 1;
+                downloaded += paths.len();
+                transferred += result.size;
+                "downloaded"
             }
             Err(e) => {
-                if let Some(ref pb) = pb { pb.suspend(|| eprintln!("  {} {} - {}", "Failed:".red(), relative.display(), e)); }
-                errors += 1;
+                report_line(pb.as_ref(), &format!("  {} {} - {}", "Failed:".red(), relative.display(), e));
+                errors += paths.len();
+                "failed"
             }
-        }
-        if let Some(ref pb) = pb { pb.inc(1); }
+        };
+        if let Some(ref pb) = pb { pb.inc(paths.len() as u64); }
+        if let Some(ref log) = log { log.record(paths.len(), action, relative, pointer.size); }
     }
 
     if let Some(pb) = pb { pb.finish_and_clear(); }
 
-    // Clean up temp directory
+    // Remove the temp directory once empty; failed downloads keep their
+    // .part files so the next pull can resume them
     let temp_dir = repo_root.join(".gg").join("tmp");
     if temp_dir.exists() {
-        std::fs::remove_dir_all(&temp_dir).ok();
+        std::fs::remove_dir(&temp_dir).ok();
     }
 
-    if args.dry_run {
+    if let Some(entries) = manifest {
+        manifest::print(&entries)?;
+    } else if args.dry_run {
         println!("\n{}", "Dry run - no files were actually downloaded.".yellow());
-    } else {
+        println!("Would download {}", format_size(transferred));
+    } else if !quiet {
         println!(
-            "\n{}: {} downloaded, {} from cache, {} errors",
+            "\n{}: {} downloaded, {} from cache, {} kept, {} errors",
             "Done".green().bold(),
             downloaded,
             cached,
+            kept,
             errors
         );
+        println!("Downloaded {}", format_transfer(transferred, started.elapsed()));
     }
 
-    if errors > 0 {
-        Err("Some files failed to download".into())
+    if link_mode == LinkMode::Hardlink && downloaded + cached > 0 && !quiet {
+        eprintln!(
+            "{} hardlinked files share storage with the LFS cache; editing one in place also changes the cached object",
+            "Warning:".yellow()
+        );
+    }
+    if copied_instead && link_mode != LinkMode::Copy && !quiet {
+        eprintln!(
+            "{} could not {} from the cache (different filesystem?), copied instead",
+            "Warning:".yellow(),
+            if link_mode == LinkMode::Hardlink { "hardlink" } else { "reflink" }
+        );
+    }
+
+    if corrupt > 0 {
+        Err(GgError::Integrity(format!("{} file(s) failed verification", corrupt)).into())
+    } else if errors > 0 {
+        Err(GgError::Storage("Some files failed to download".to_string()).into())
     } else {
         Ok(())
     }
 }
 
-/// Find all pointer files in the repository
+/// A pull target's working-tree content, compared with its pointer
+#[derive(Debug, PartialEq, Eq)]
+enum LocalState {
+    /// Still the pointer, or gone: safe to write
+    Pointer,
+    /// Already the object's content
+    Current,
+    /// Edited, or replaced by another pointer
+    Modified,
+}
+
+/// Compare a pull target with the object it should hold. Content of the
+/// right size is hashed, so unchanged files aren't reported as modified.
+fn local_state(path: &Path, pointer: &Pointer) -> Result<LocalState, Box<dyn std::error::Error>> {
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return Ok(LocalState::Pointer);
+    };
+    if let Ok(existing) = Pointer::parse(path) {
+        return Ok(if existing.oid == pointer.oid { LocalState::Pointer } else { LocalState::Modified });
+    }
+    if metadata.len() != pointer.size {
+        return Ok(LocalState::Modified);
+    }
+    Ok(if Pointer::from_file(path)?.oid == pointer.oid { LocalState::Current } else { LocalState::Modified })
+}
+
+/// Find all pointer files in the repository. With --force, files whose
+/// index entry is a pointer but whose content was edited are included too.
 fn find_pointer_files(
+    repo: &git2::Repository,
     repo_root: &Path,
     scanner: &Scanner,
     args: &PullArgs,
 ) -> Result<Vec<(std::path::PathBuf, Pointer)>, Box<dyn std::error::Error>> {
+    let index = if args.force { Some(repo.index()?) } else { None };
     let mut pointers = Vec::new();
 
-    let include_pattern = args.include.as_ref()
-        .map(|p| globset::Glob::new(p).map(|g| g.compile_matcher()))
-        .transpose()?;
-    let exclude_pattern = args.exclude.as_ref()
-        .map(|p| globset::Glob::new(p).map(|g| g.compile_matcher()))
-        .transpose()?;
+    let include_patterns = build_glob_set(&args.include)?;
+    let exclude_patterns = build_glob_set(&args.exclude)?;
 
     // Scan for files matching LFS patterns
     for file_path in scanner.scan_files()? {
@@ -226,50 +397,105 @@ fn find_pointer_files(
             .unwrap_or(&file_path);
 
         // Apply include/exclude filters
-        if let Some(ref pattern) = include_pattern {
-            if !pattern.is_match(relative) {
-                continue;
-            }
-        }
-
-        if let Some(ref pattern) = exclude_pattern {
-            if pattern.is_match(relative) {
-                continue;
-            }
+        if !is_selected(relative, include_patterns.as_ref(), exclude_patterns.as_ref()) {
+            continue;
         }
 
         // Check if it's a pointer file
         if let Ok(pointer) = Pointer::parse(&file_path) {
             pointers.push((file_path, pointer));
+        } else if let Some(pointer) = index.as_ref().and_then(|index| index_pointer(repo, index, relative)) {
+            pointers.push((file_path, pointer));
         }
     }
 
     Ok(pointers)
 }
 
+/// The pointer staged for a path, if its index entry is one
+fn index_pointer(repo: &git2::Repository, index: &git2::Index, relative: &Path) -> Option<Pointer> {
+    let entry = index.get_path(relative, 0)?;
+    let blob = repo.find_blob(entry.id).ok()?;
+    if blob.size() > MAX_POINTER_SIZE {
+        return None;
+    }
+    Pointer::parse_content(blob.content()).ok()
+}
+
+/// Group pointer files by object, keeping the order each object first appears in
+fn group_by_oid(files: &[(PathBuf, Pointer)]) -> Vec<(&Pointer, Vec<&PathBuf>)> {
+    let mut groups: Vec<(&Pointer, Vec<&PathBuf>)> = Vec::new();
+    let mut index: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+
+    for (path, pointer) in files {
+        match index.get(pointer.oid.as_str()) {
+            Some(&i) => groups[i].1.push(path),
+            None => {
+                index.insert(&pointer.oid, groups.len());
+                groups.push((pointer, vec![path]));
+            }
+        }
+    }
+
+    groups
+}
+
+/// Path of the partial download for an object
+fn partial_download_path(repo_root: &Path, oid: &str) -> PathBuf {
+    repo_root.join(".gg").join("tmp").join(format!("{}.part", oid))
+}
+
+/// Remove a partial download with nothing left to resume: one already as
+/// large as the object, or larger
+fn discard_unresumable_part(part_path: &Path, expected_size: u64) -> std::io::Result<()> {
+    match std::fs::metadata(part_path) {
+        Ok(meta) if meta.len() >= expected_size => std::fs::remove_file(part_path),
+        _ => Ok(()),
+    }
+}
+
+/// Compile glob patterns into a set. Returns None when no patterns are given.
+fn build_glob_set(patterns: &[String]) -> Result<Option<globset::GlobSet>, globset::Error> {
+    let patterns: Vec<&str> = patterns
+        .iter()
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        .collect();
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = globset::GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(globset::Glob::new(pattern)?);
+    }
+    builder.build().map(Some)
+}
+
+/// A file is selected if it matches any include (or there are none) and no exclude
+fn is_selected(
+    relative: &Path,
+    include: Option<&globset::GlobSet>,
+    exclude: Option<&globset::GlobSet>,
+) -> bool {
+    include.is_none_or(|set| set.is_match(relative))
+        && !exclude.is_some_and(|set| set.is_match(relative))
+}
+
 /// Find pointer files that changed during a merge (for post-merge hook).
 /// Uses ORIG_HEAD (set by git before merges) to diff against HEAD.
 fn find_post_merge_pointer_files(
     repo_root: &Path,
     scanner: &Scanner,
 ) -> Result<Vec<(std::path::PathBuf, Pointer)>, Box<dyn std::error::Error>> {
-    let output = std::process::Command::new("git")
-        .args(["diff-tree", "-r", "--name-only", "ORIG_HEAD", "HEAD"])
-        .current_dir(repo_root)
-        .output()?;
-
     let mut pointers = Vec::new();
 
-    if output.status.success() {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        for line in stdout.lines() {
-            let path = Path::new(line);
-            if scanner.is_lfs_file(path) {
-                let full_path = repo_root.join(path);
-                if full_path.exists() {
-                    if let Ok(pointer) = Pointer::parse(&full_path) {
-                        pointers.push((full_path, pointer));
-                    }
+    for path in crate::git::list_paths(repo_root, &["diff-tree", "-r", "--name-only", "ORIG_HEAD", "HEAD"])? {
+        if scanner.is_lfs_file(&path) {
+            let full_path = repo_root.join(&path);
+            if full_path.exists() {
+                if let Ok(pointer) = Pointer::parse(&full_path) {
+                    pointers.push((full_path, pointer));
                 }
             }
         }
@@ -278,6 +504,12 @@ fn find_post_merge_pointer_files(
     Ok(pointers)
 }
 
+/// Whether post-checkout args describe a branch checkout. Git passes "1" as
+/// the third argument for those and "0" when files were checked out.
+fn is_branch_checkout(checkout_args: &[String]) -> bool {
+    checkout_args.get(2).is_some_and(|flag| flag == "1")
+}
+
 /// Find pointer files that changed between two refs (for post-checkout hook).
 /// checkout_args: [old_ref, new_ref, flag]
 fn find_post_checkout_pointer_files(
@@ -289,23 +521,14 @@ fn find_post_checkout_pointer_files(
     let new_ref = &checkout_args[1];
 
     // Get files that changed between old and new refs
-    let output = std::process::Command::new("git")
-        .args(["diff-tree", "-r", "--name-only", old_ref, new_ref])
-        .current_dir(repo_root)
-        .output()?;
-
     let mut pointers = Vec::new();
 
-    if output.status.success() {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        for line in stdout.lines() {
-            let path = Path::new(line);
-            if scanner.is_lfs_file(path) {
-                let full_path = repo_root.join(path);
-                if full_path.exists() {
-                    if let Ok(pointer) = Pointer::parse(&full_path) {
-                        pointers.push((full_path, pointer));
-                    }
+    for path in crate::git::list_paths(repo_root, &["diff-tree", "-r", "--name-only", old_ref, new_ref])? {
+        if scanner.is_lfs_file(&path) {
+            let full_path = repo_root.join(&path);
+            if full_path.exists() {
+                if let Ok(pointer) = Pointer::parse(&full_path) {
+                    pointers.push((full_path, pointer));
                 }
             }
         }
@@ -313,3 +536,126 @@ fn find_post_checkout_pointer_files(
 
     Ok(pointers)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_group_by_oid() {
+        let pointer = |content: &[u8]| Pointer::from_bytes(content);
+        let files = vec![
+            (PathBuf::from("a.bin"), pointer(b"same")),
+            (PathBuf::from("b.bin"), pointer(b"other")),
+            (PathBuf::from("c.bin"), pointer(b"same")),
+        ];
+
+        let groups = group_by_oid(&files);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].1, vec![&files[0].0, &files[2].0]);
+        assert_eq!(groups[1].1, vec![&files[1].0]);
+    }
+
+    #[test]
+    fn test_local_state() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("a.bin");
+        let pointer = Pointer::from_bytes(b"hello");
+
+        assert_eq!(local_state(&path, &pointer).unwrap(), LocalState::Pointer);
+        pointer.write(&path).unwrap();
+        assert_eq!(local_state(&path, &pointer).unwrap(), LocalState::Pointer);
+        Pointer::from_bytes(b"other").write(&path).unwrap();
+        assert_eq!(local_state(&path, &pointer).unwrap(), LocalState::Modified);
+
+        std::fs::write(&path, b"hello").unwrap();
+        assert_eq!(local_state(&path, &pointer).unwrap(), LocalState::Current);
+        // Same size, different content
+        std::fs::write(&path, b"jello").unwrap();
+        assert_eq!(local_state(&path, &pointer).unwrap(), LocalState::Modified);
+        std::fs::write(&path, b"hello, edited").unwrap();
+        assert_eq!(local_state(&path, &pointer).unwrap(), LocalState::Modified);
+    }
+
+    #[test]
+    fn test_is_branch_checkout() {
+        assert!(is_branch_checkout(&patterns(&["a", "b", "1"])));
+        assert!(!is_branch_checkout(&patterns(&["a", "b", "0"])));
+        assert!(!is_branch_checkout(&patterns(&["a", "b"])));
+    }
+
+    #[test]
+    fn test_partial_download_path() {
+        let path = partial_download_path(Path::new("/repo"), "abc123");
+        assert_eq!(path, Path::new("/repo/.gg/tmp/abc123.part"));
+    }
+
+    #[test]
+    fn test_discard_unresumable_part_keeps_resumable() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let part = temp.path().join("abc.part");
+        std::fs::write(&part, b"12345").unwrap();
+
+        discard_unresumable_part(&part, 10).unwrap();
+        assert!(part.exists());
+    }
+
+    #[test]
+    fn test_discard_unresumable_part_removes_complete_and_too_large() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let part = temp.path().join("abc.part");
+        std::fs::write(&part, b"12345").unwrap();
+
+        discard_unresumable_part(&part, 5).unwrap();
+        assert!(!part.exists());
+
+        std::fs::write(&part, b"12345").unwrap();
+        discard_unresumable_part(&part, 3).unwrap();
+        assert!(!part.exists());
+    }
+
+    #[test]
+    fn test_discard_unresumable_part_missing_file() {
+        let temp = tempfile::TempDir::new().unwrap();
+        assert!(discard_unresumable_part(&temp.path().join("none.part"), 3).is_ok());
+    }
+
+    #[test]
+    fn test_build_glob_set_empty() {
+        assert!(build_glob_set(&[]).unwrap().is_none());
+        assert!(build_glob_set(&patterns(&["", " "])).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_build_glob_set_invalid_pattern() {
+        assert!(build_glob_set(&patterns(&["[unclosed"])).is_err());
+    }
+
+    #[test]
+    fn test_is_selected_no_filters() {
+        assert!(is_selected(Path::new("assets/a.psd"), None, None));
+    }
+
+    #[test]
+    fn test_is_selected_multiple_includes() {
+        let include = build_glob_set(&patterns(&["*.png", "models/*"])).unwrap();
+
+        assert!(is_selected(Path::new("textures/a.png"), include.as_ref(), None));
+        assert!(is_selected(Path::new("models/ship.fbx"), include.as_ref(), None));
+        assert!(!is_selected(Path::new("footage/raw.mov"), include.as_ref(), None));
+    }
+
+    #[test]
+    fn test_is_selected_exclude_wins() {
+        let include = build_glob_set(&patterns(&["*.png", "*.mov"])).unwrap();
+        let exclude = build_glob_set(&patterns(&["footage/*"])).unwrap();
+
+        assert!(is_selected(Path::new("textures/a.png"), include.as_ref(), exclude.as_ref()));
+        assert!(!is_selected(Path::new("footage/raw.mov"), include.as_ref(), exclude.as_ref()));
+    }
+}
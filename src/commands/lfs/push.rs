@@ -1,12 +1,23 @@
 //! Push LFS files to remote storage
 
-use crate::lfs::storage;
+use super::error::{report, GgError};
+use super::lock::{lock_key_path, lock_owner, warn_locked_by_others};
+use super::manifest::{self, Action, ManifestEntry};
+use super::overrides::StorageOverrides;
+use super::progress::{report_line, LogProgress};
+use super::status::{format_size, format_transfer};
+use crate::config;
+use crate::lfs::pointer::MAX_POINTER_SIZE;
+use crate::lfs::storage::{self, ObjectManifest, Storage, StorageError};
 use crate::lfs::{Cache, LfsConfig, Pointer, Scanner};
+use crate::utils::{get_repo_with_root, workdir_root};
 use clap::Args;
 use colored::Colorize;
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use std::io::{BufRead, IsTerminal};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tokio::task::JoinSet;
 
 #[derive(Args, Debug)]
 pub struct PushArgs {
@@ -14,18 +25,33 @@ pub struct PushArgs {
     #[arg(short = 'n', long)]
     pub dry_run: bool,
 
-    /// Push all LFS files, not just staged ones
+    /// With --dry-run, print a JSON manifest of {path, oid, size, action} instead
+    #[arg(long, requires = "dry_run")]
+    pub json: bool,
+
+    /// Push every LFS object referenced by any branch or tag, not just staged files
     #[arg(short, long)]
     pub all: bool,
 
+    /// After each upload, check the stored object's size (and checksum, when S3 recorded one)
+    #[arg(long)]
+    pub verify: bool,
+
+    /// Check every object with a HEAD request instead of reading the bucket's manifest.json
+    #[arg(long)]
+    pub no_manifest: bool,
+
     /// Called by the pre-push hook (reads refs from stdin)
-    #[arg(long, hide = true)]
+    #[arg(long, hide = true, conflicts_with = "dry_run")]
     pub pre_push: bool,
+
+    #[command(flatten)]
+    pub storage: StorageOverrides,
 }
 
 /// Push LFS files to remote storage
 pub fn run(args: PushArgs) -> i32 {
-    let rt = match tokio::runtime::Runtime::new() {
+    let rt = match crate::lfs::runtime() {
         Ok(rt) => rt,
         Err(e) => {
             eprintln!("{} Failed to create async runtime: {}", "Error:".red().bold(), e);
@@ -36,99 +62,150 @@ pub fn run(args: PushArgs) -> i32 {
     rt.block_on(async {
         match run_inner(args).await {
             Ok(_) => 0,
-            Err(e) => {
-                eprintln!("{} {}", "Error:".red().bold(), e);
-                1
-            }
+            Err(e) => report(&*e),
         }
     })
 }
 
 async fn run_inner(args: PushArgs) -> Result<(), Box<dyn std::error::Error>> {
-    let repo = git2::Repository::discover(".")?;
-    let repo_root = repo
-        .workdir()
-        .ok_or("Not a git repository with a working directory")?;
+    let (repo, repo_root) = get_repo_with_root()?;
+
+    if args.pre_push {
+        return push_pre_push(&repo, &repo_root, &args).await;
+    }
 
-    let config = LfsConfig::load(repo_root).map_err(|e| {
-        format!("{}\nRun 'gg lfs install' to create a configuration file.", e)
+    let mut config = LfsConfig::load(&repo_root).map_err(|e| {
+        GgError::Config(format!("{}\nRun 'gg lfs install' to create a configuration file.", e))
     })?;
+    args.storage.apply(&mut config)?;
 
     let storage = storage::create_storage(&config).await?;
     let cache = Cache::new()?;
-    let scanner = Scanner::new(repo_root)?;
+    let scanner = Scanner::new(&repo_root)?;
+
+    // In JSON mode stdout carries only the manifest
+    let mut manifest = args.json.then(Vec::new);
 
     if scanner.patterns().is_empty() {
-        println!("{}", "No LFS patterns defined. Use 'gg lfs track <pattern>' to add files.".yellow());
+        if args.json {
+            manifest::print(&[])?;
+        } else {
+            println!("{}", "No LFS patterns defined. Use 'gg lfs track <pattern>' to add files.".yellow());
+        }
         return Ok(());
     }
 
-    let files = if args.pre_push {
-        get_pre_push_lfs_files(repo_root, &scanner)?
-    } else if args.all {
-        scanner.scan_files()?
-    } else {
-        get_staged_lfs_files(&repo, &scanner)?
-    };
+    if args.all {
+        return push_all_objects(&repo, &scanner, storage.as_ref(), &cache, &args, manifest).await;
+    }
+
+    let files = get_staged_lfs_files(&repo, &scanner)?;
 
     if files.is_empty() {
-        if !args.pre_push {
+        if args.json {
+            manifest::print(&[])?;
+        } else {
             println!("{}", "No LFS files to push.".dimmed());
         }
         return Ok(());
     }
 
-    let show_progress = !args.dry_run && std::io::stderr().is_terminal();
-    let pb = if show_progress {
-        let pb = ProgressBar::new(files.len() as u64);
+    let show_progress = !args.dry_run && std::io::stderr().is_terminal() && !config::is_quiet();
+    let multi = if show_progress { Some(MultiProgress::new()) } else { None };
+    let pb = multi.as_ref().map(|multi| {
+        let pb = multi.add(ProgressBar::new(files.len() as u64));
         pb.set_style(ProgressStyle::default_bar()
             .template("  {bar:30} {pos}/{len} {msg}")
             .unwrap_or_else(|_| ProgressStyle::default_bar()));
-        Some(pb)
-    } else {
-        None
-    };
+        pb
+    });
+    // Without a terminal to draw the bar on, log lines show progress instead
+    let log = (!args.dry_run && !show_progress && !config::is_quiet()).then(|| LogProgress::new(files.len()));
 
-    if !args.dry_run {
+    if !args.dry_run && !config::is_quiet() {
         println!(
-            "{} {} LFS file(s) to {}...",
-            "Pushing", files.len(), storage.provider_name().cyan()
+            "Pushing {} LFS file(s) to {}...",
+            files.len(),
+            storage.provider_name().cyan()
         );
     }
 
+    if !args.dry_run {
+        if let Ok(owner) = lock_owner(&repo) {
+            let paths: Vec<String> = files
+                .iter()
+                .map(|file_path| lock_key_path(file_path.strip_prefix(&repo_root).unwrap_or(file_path)))
+                .collect();
+            warn_locked_by_others(storage.as_ref(), &owner, &paths).await;
+        }
+    }
+
+    let mut known = KnownObjects::load(storage.as_ref(), !args.dry_run && !args.no_manifest).await;
     let mut uploaded = 0;
     let mut skipped = 0;
+    let mut missing = 0;
     let mut errors = 0;
+    // Bytes sent, or that would be sent in a dry run
+    let mut transferred = 0;
+    let started = Instant::now();
+    // Files with identical content share an object; transfer each object once
+    let mut seen_oids = std::collections::HashSet::new();
 
     for file_path in &files {
-        let relative = file_path.strip_prefix(repo_root).unwrap_or(file_path);
+        let relative = file_path.strip_prefix(&repo_root).unwrap_or(file_path);
 
         if !Pointer::is_pointer_file(file_path) {
-            if !args.pre_push {
-                let pointer = Pointer::from_file(file_path)?;
-                let oid = pointer.sha256();
+            let pointer = Pointer::from_file(file_path)?;
+            let oid = pointer.sha256();
 
-                if args.dry_run {
-                    println!("  {} {} ({} bytes)", "Would upload:".cyan(), relative.display(), pointer.size);
-                    continue;
-                }
-
-                if storage.exists(oid).await? {
-                    cache.put_file(oid, file_path)?;
-                    skipped += 1;
+            if args.dry_run {
+                let action = if seen_oids.insert(oid.to_string()) && !known.probably_exists(oid).await {
+                    transferred += pointer.size;
+                    Action::Upload
                 } else {
-                    match storage.upload(oid, file_path).await {
-                        Ok(_) => {
-                            uploaded += 1;
-                            cache.put_file(oid, file_path)?;
-                        }
-                        Err(e) => {
-                            if let Some(ref pb) = pb { pb.suspend(|| eprintln!("  {} {} - {}", "Failed:".red(), relative.display(), e)); }
-                            errors += 1;
-                        }
+                    Action::Skip
+                };
+                report_dry_run(&mut manifest, relative, &pointer, action);
+                continue;
+            }
+
+            let action = if !seen_oids.insert(oid.to_string()) {
+                skipped += 1;
+                "skipped"
+            } else if known.exists(oid).await? {
+                cache.put_file(oid, file_path)?;
+                skipped += 1;
+                "skipped"
+            } else {
+                let bytes_pb = byte_progress_bar(multi.as_ref(), pointer.size, relative);
+                let result = storage
+                    .upload_named(oid, file_path, relative, &|n| {
+                        if let Some(ref b) = bytes_pb { b.inc(n); }
+                    })
+                    .await;
+                if let Some(b) = bytes_pb { b.finish_and_clear(); }
+                let result = match result {
+                    Ok(result) if args.verify => {
+                        storage::verify_object(storage.as_ref(), oid, pointer.size).await.map(|_| result.size)
+                    }
+                    other => other.map(|result| result.size),
+                };
+                match result {
+                    Ok(size) => {
+                        uploaded += 1;
+                        transferred += size;
+                        known.record(oid);
+                        cache.put_file(oid, file_path)?;
+                        "uploaded"
+                    }
+                    Err(e) => {
+                        report_line(pb.as_ref(), &format!("  {} {} - {}", "Failed:".red(), relative.display(), e));
+                        errors += 1;
+                        "failed"
                     }
                 }
-            }
+            };
+            if let Some(ref log) = log { log.record(1, action, relative, pointer.size); }
             if let Some(ref pb) = pb { pb.inc(1); }
             continue;
         }
@@ -137,86 +214,633 @@ async fn run_inner(args: PushArgs) -> Result<(), Box<dyn std::error::Error>> {
         let oid = pointer.sha256();
 
         if args.dry_run {
-            println!("  {} {} ({} bytes)", "Would upload:".cyan(), relative.display(), pointer.size);
+            let action = if seen_oids.insert(oid.to_string()) && !known.probably_exists(oid).await {
+                transferred += pointer.size;
+                Action::Upload
+            } else {
+                Action::Skip
+            };
+            report_dry_run(&mut manifest, relative, &pointer, action);
             continue;
         }
 
-        if storage.exists(oid).await? {
+        if !seen_oids.insert(oid.to_string()) || known.exists(oid).await? {
             skipped += 1;
             if let Some(ref pb) = pb { pb.inc(1); }
+            if let Some(ref log) = log { log.record(1, "skipped", relative, pointer.size); }
             continue;
         }
 
-        if let Some(cached_path) = cache.get(oid) {
-            match storage.upload(oid, &cached_path).await {
-                Ok(_) => { uploaded += 1; }
+        let action = if let Some(cached_path) = cache.get(oid) {
+            let bytes_pb = byte_progress_bar(multi.as_ref(), pointer.size, relative);
+            let result = storage
+                .upload_named(oid, &cached_path, relative, &|n| {
+                    if let Some(ref b) = bytes_pb { b.inc(n); }
+                })
+                .await;
+            if let Some(b) = bytes_pb { b.finish_and_clear(); }
+            let result = match result {
+                Ok(result) if args.verify => {
+                    storage::verify_object(storage.as_ref(), oid, pointer.size).await.map(|_| result.size)
+                }
+                other => other.map(|result| result.size),
+            };
+            match result {
+                Ok(size) => {
+                    uploaded += 1;
+                    transferred += size;
+                    known.record(oid);
+                    "uploaded"
+                }
                 Err(e) => {
-                    if let Some(ref pb) = pb { pb.suspend(|| eprintln!("  {} {} - {}", "Failed:".red(), relative.display(), e)); }
+                    report_line(pb.as_ref(), &format!("  {} {} - {}", "Failed:".red(), relative.display(), e));
                     errors += 1;
+                    "failed"
                 }
             }
         } else {
-            skipped += 1;
-        }
+            // Neither on the remote nor cached locally: committing the pointer
+            // would reference an object nobody can download
+            report_line(pb.as_ref(), &format!("  {} {} ({}) - not in local cache or remote storage", "Missing:".yellow(), relative.display(), oid));
+            missing += 1;
+            "missing"
+        };
         if let Some(ref pb) = pb { pb.inc(1); }
+        if let Some(ref log) = log { log.record(1, action, relative, pointer.size); }
     }
 
     if let Some(pb) = pb { pb.finish_and_clear(); }
+    known.save().await;
 
-    if args.dry_run {
+    if let Some(entries) = manifest {
+        manifest::print(&entries)?;
+    } else if args.dry_run {
         println!("\n{}", "Dry run - no files were actually uploaded.".yellow());
+        println!("Would upload {}", format_size(transferred));
     } else {
         println!(
-            "{}: {} uploaded, {} skipped, {} errors",
-            "Done".green().bold(), uploaded, skipped, errors
+            "{}: {} uploaded, {} skipped, {} missing, {} errors",
+            "Done".green().bold(), uploaded, skipped, missing, errors
         );
+        println!("Uploaded {}", format_transfer(transferred, started.elapsed()));
     }
 
-    if errors > 0 { Err("Some files failed to upload".into()) } else { Ok(()) }
+    if errors > 0 { Err(GgError::Storage("Some files failed to upload".to_string()).into()) } else { Ok(()) }
+}
+
+/// An object the pre-push hook may need to upload
+struct PendingUpload {
+    relative: PathBuf,
+    oid: String,
+    size: u64,
+    /// Cached content, or a working tree file holding it; without either the
+    /// object can only already be in storage
+    source: Option<PathBuf>,
+}
+
+/// What happened to a pending upload
+enum UploadOutcome {
+    /// Storage already had the object
+    Present,
+    /// Uploaded this many bytes
+    Uploaded(u64),
+    /// Neither in storage nor the local cache
+    Missing,
+    Failed(String),
 }
 
-/// Get files to push based on pre-push hook stdin
-fn get_pre_push_lfs_files(
+impl UploadOutcome {
+    /// Past-tense verb for progress lines
+    fn action(&self) -> &'static str {
+        match self {
+            UploadOutcome::Present => "skipped",
+            UploadOutcome::Uploaded(_) => "uploaded",
+            UploadOutcome::Missing => "missing",
+            UploadOutcome::Failed(_) => "failed",
+        }
+    }
+}
+
+/// Push the objects referenced by the commits being pushed, for the pre-push
+/// hook. `git push` waits on the hook, so nothing is loaded when no LFS files
+/// are being pushed, the manifest answers most existence checks in a single
+/// request, and the remaining checks and uploads run `transfer_jobs` at a time.
+async fn push_pre_push(
+    repo: &git2::Repository,
     repo_root: &Path,
-    scanner: &Scanner,
-) -> Result<Vec<std::path::PathBuf>, Box<dyn std::error::Error>> {
-    let mut files = std::collections::HashSet::new();
-    let stdin = std::io::stdin();
+    args: &PushArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let scanner = Scanner::new(repo_root)?;
+    if scanner.patterns().is_empty() {
+        return Ok(());
+    }
 
-    for line in stdin.lock().lines() {
-        let line = line?;
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() < 4 { continue; }
+    // Pointers are read from the pushed commits, not the working tree, which
+    // holds smudged content; committed content travels with git
+    let revisions = read_pre_push_revisions(std::io::stdin().lock())?;
+    if revisions.is_empty() {
+        return Ok(());
+    }
+    let objects = find_pointer_objects(repo, &scanner, &revisions)?;
+    if objects.is_empty() {
+        return Ok(());
+    }
 
-        let local_sha = parts[1];
-        let remote_sha = parts[3];
+    let mut config = LfsConfig::load(repo_root).map_err(|e| {
+        GgError::Config(format!("{}\nRun 'gg lfs install' to create a configuration file.", e))
+    })?;
+    args.storage.apply(&mut config)?;
+    let storage = storage::shared_storage(&config).await?;
+    let cache = Cache::new()?;
 
-        if local_sha == "0000000000000000000000000000000000000000" { continue; }
+    if let Ok(owner) = lock_owner(repo) {
+        let paths: Vec<String> = objects.iter().map(|(relative, _)| lock_key_path(relative)).collect();
+        warn_locked_by_others(storage, &owner, &paths).await;
+    }
 
-        let diff_args = if remote_sha == "0000000000000000000000000000000000000000" {
-            vec!["diff-tree", "-r", "--diff-filter=ACMR", "--name-only", "--root", local_sha]
+    let mut known = KnownObjects::load(storage, !args.no_manifest).await;
+    let skipped = objects.iter().filter(|(_, pointer)| known.contains(pointer.sha256())).count();
+    let pending: Vec<PendingUpload> = objects
+        .into_iter()
+        .filter(|(_, pointer)| !known.contains(pointer.sha256()))
+        .map(|(relative, pointer)| PendingUpload {
+            source: cache.get(pointer.sha256()).or_else(|| working_copy(repo_root, &relative, &pointer)),
+            oid: pointer.sha256().to_string(),
+            size: pointer.size,
+            relative,
+        })
+        .collect();
+
+    if !config::is_quiet() {
+        println!(
+            "Pushing {} LFS object(s) to {}...",
+            pending.len() + skipped,
+            storage.provider_name().cyan()
+        );
+    }
+
+    let show_progress = std::io::stderr().is_terminal() && !config::is_quiet();
+    let pb = show_progress.then(|| {
+        let pb = ProgressBar::new(pending.len() as u64);
+        pb.set_style(ProgressStyle::default_bar()
+            .template("  {bar:30} {pos}/{len} {msg}")
+            .unwrap_or_else(|_| ProgressStyle::default_bar()));
+        pb
+    });
+
+    let log = (!show_progress && !config::is_quiet()).then(|| LogProgress::new(pending.len()));
+
+    let started = Instant::now();
+    let jobs = LfsConfig::transfer_jobs(repo_root);
+    let outcomes = upload_pending(storage, pending, jobs, args.verify, pb.as_ref(), log.as_ref()).await?;
+    if let Some(pb) = pb { pb.finish_and_clear(); }
+
+    let mut uploaded = 0;
+    let mut missing = 0;
+    let mut errors = 0;
+    let mut transferred = 0;
+    for (upload, outcome) in &outcomes {
+        match outcome {
+            UploadOutcome::Present => known.record(&upload.oid),
+            UploadOutcome::Uploaded(size) => {
+                uploaded += 1;
+                transferred += size;
+                known.record(&upload.oid);
+            }
+            // Neither on the remote nor cached locally: the pushed commits would
+            // reference an object nobody can download
+            UploadOutcome::Missing => {
+                eprintln!(
+                    "  {} {} ({}) - not in local cache or remote storage",
+                    "Missing:".yellow(), upload.relative.display(), upload.oid
+                );
+                missing += 1;
+            }
+            UploadOutcome::Failed(e) => {
+                eprintln!("  {} {} - {}", "Failed:".red(), upload.relative.display(), e);
+                errors += 1;
+            }
+        }
+    }
+    let skipped = skipped + outcomes.iter().filter(|(_, outcome)| matches!(outcome, UploadOutcome::Present)).count();
+    known.save().await;
+
+    println!(
+        "{}: {} uploaded, {} skipped, {} missing, {} errors",
+        "Done".green().bold(), uploaded, skipped, missing, errors
+    );
+    println!("Uploaded {}", format_transfer(transferred, started.elapsed()));
+
+    if errors > 0 {
+        return Err(GgError::Storage("Some files failed to upload".to_string()).into());
+    }
+    if missing > 0 {
+        return Err(GgError::Storage(format!(
+            "{} LFS object(s) are missing from the local cache and remote storage; refusing to push.\n\
+             Restore the content (e.g. 'gg lfs pull' from another clone) or run 'git push --no-verify' to bypass.",
+            missing
+        )).into());
+    }
+    Ok(())
+}
+
+/// Check and upload objects the manifest didn't list, `jobs` at a time.
+/// Outcomes are returned in the order the uploads were given.
+async fn upload_pending(
+    storage: &'static dyn Storage,
+    pending: Vec<PendingUpload>,
+    jobs: usize,
+    verify: bool,
+    pb: Option<&ProgressBar>,
+    log: Option<&LogProgress>,
+) -> Result<Vec<(PendingUpload, UploadOutcome)>, Box<dyn std::error::Error>> {
+    let mut queue = pending.into_iter().enumerate();
+    let mut tasks = JoinSet::new();
+    let mut outcomes = Vec::new();
+
+    loop {
+        while tasks.len() < jobs.max(1) {
+            let Some((index, upload)) = queue.next() else { break };
+            tasks.spawn(async move {
+                let outcome = upload_one(storage, &upload, verify).await;
+                (index, upload, outcome)
+            });
+        }
+        let Some(job) = tasks.join_next().await else { break };
+        let (index, upload, outcome) = job?;
+        if let Some(pb) = pb { pb.inc(1); }
+        if let Some(log) = log { log.record(1, outcome.action(), &upload.relative, upload.size); }
+        outcomes.push((index, upload, outcome));
+    }
+
+    outcomes.sort_by_key(|(index, _, _)| *index);
+    Ok(outcomes.into_iter().map(|(_, upload, outcome)| (upload, outcome)).collect())
+}
+
+/// Upload one object unless storage already has it
+async fn upload_one(storage: &dyn Storage, upload: &PendingUpload, verify: bool) -> UploadOutcome {
+    match storage.exists(&upload.oid).await {
+        Ok(true) => return UploadOutcome::Present,
+        Ok(false) => {}
+        Err(e) => return UploadOutcome::Failed(e.to_string()),
+    }
+    let Some(source) = &upload.source else {
+        return UploadOutcome::Missing;
+    };
+
+    let result = match storage.upload_named(&upload.oid, source, &upload.relative, &|_| {}).await {
+        Ok(result) if verify => {
+            storage::verify_object(storage, &upload.oid, upload.size).await.map(|_| result.size)
+        }
+        other => other.map(|result| result.size),
+    };
+    match result {
+        Ok(size) => UploadOutcome::Uploaded(size),
+        Err(e) => UploadOutcome::Failed(e.to_string()),
+    }
+}
+
+/// The working tree file at `relative`, if it holds the pointer's content
+fn working_copy(repo_root: &Path, relative: &Path, pointer: &Pointer) -> Option<PathBuf> {
+    let path = repo_root.join(relative);
+    if path.metadata().ok()?.len() != pointer.size {
+        return None;
+    }
+    let content = Pointer::from_file(&path).ok()?;
+    (content.oid == pointer.oid).then_some(path)
+}
+
+/// Push every object referenced by pointer blobs reachable from any ref
+async fn push_all_objects(
+    repo: &git2::Repository,
+    scanner: &Scanner,
+    storage: &dyn Storage,
+    cache: &Cache,
+    args: &PushArgs,
+    mut manifest: Option<Vec<ManifestEntry>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let dry_run = args.dry_run;
+    let objects = find_all_pointer_objects(repo, scanner)?;
+
+    if objects.is_empty() {
+        if manifest.is_some() {
+            manifest::print(&[])?;
         } else {
-            vec!["diff-tree", "-r", "--diff-filter=ACMR", "--name-only", remote_sha, local_sha]
+            println!("{}", "No LFS objects found in history.".dimmed());
+        }
+        return Ok(());
+    }
+
+    let show_progress = !dry_run && std::io::stderr().is_terminal() && !config::is_quiet();
+    let multi = if show_progress { Some(MultiProgress::new()) } else { None };
+    let pb = multi.as_ref().map(|multi| {
+        let pb = multi.add(ProgressBar::new(objects.len() as u64));
+        pb.set_style(ProgressStyle::default_bar()
+            .template("  {bar:30} {pos}/{len} {msg}")
+            .unwrap_or_else(|_| ProgressStyle::default_bar()));
+        pb
+    });
+    let log = (!dry_run && !show_progress && !config::is_quiet()).then(|| LogProgress::new(objects.len()));
+
+    if !dry_run && !config::is_quiet() {
+        println!(
+            "Pushing {} LFS object(s) from all refs to {}...",
+            objects.len(),
+            storage.provider_name().cyan()
+        );
+    }
+
+    let mut known = KnownObjects::load(storage, !dry_run && !args.no_manifest).await;
+    let mut uploaded = 0;
+    let mut skipped = 0;
+    let mut missing = 0;
+    let mut errors = 0;
+    let mut transferred = 0;
+    let started = Instant::now();
+
+    for (relative, pointer) in &objects {
+        let oid = pointer.sha256();
+
+        if dry_run {
+            let action = if known.probably_exists(oid).await {
+                Action::Skip
+            } else {
+                transferred += pointer.size;
+                Action::Upload
+            };
+            report_dry_run(&mut manifest, relative, pointer, action);
+            continue;
+        }
+
+        if known.exists(oid).await? {
+            skipped += 1;
+            if let Some(ref pb) = pb { pb.inc(1); }
+            if let Some(ref log) = log { log.record(1, "skipped", relative, pointer.size); }
+            continue;
+        }
+
+        let action = match cache.get(oid) {
+            Some(cached_path) => {
+                let bytes_pb = byte_progress_bar(multi.as_ref(), pointer.size, relative);
+                let result = storage
+                    .upload_named(oid, &cached_path, relative, &|n| {
+                        if let Some(ref b) = bytes_pb { b.inc(n); }
+                    })
+                    .await;
+                if let Some(b) = bytes_pb { b.finish_and_clear(); }
+                let result = match result {
+                    Ok(result) if args.verify => {
+                        storage::verify_object(storage, oid, pointer.size).await.map(|_| result.size)
+                    }
+                    other => other.map(|result| result.size),
+                };
+                match result {
+                    Ok(size) => {
+                        uploaded += 1;
+                        transferred += size;
+                        known.record(oid);
+                        "uploaded"
+                    }
+                    Err(e) => {
+                        report_line(pb.as_ref(), &format!("  {} {} - {}", "Failed:".red(), relative.display(), e));
+                        errors += 1;
+                        "failed"
+                    }
+                }
+            }
+            None => {
+                report_line(pb.as_ref(), &format!("  {} {} ({}) - not in local cache", "Missing:".yellow(), relative.display(), oid));
+                missing += 1;
+                "missing"
+            }
         };
+        if let Some(ref pb) = pb { pb.inc(1); }
+        if let Some(ref log) = log { log.record(1, action, relative, pointer.size); }
+    }
+
+    if let Some(pb) = pb { pb.finish_and_clear(); }
+    known.save().await;
+
+    if let Some(entries) = manifest {
+        manifest::print(&entries)?;
+    } else if dry_run {
+        println!("\n{}", "Dry run - no files were actually uploaded.".yellow());
+        println!("Would upload {}", format_size(transferred));
+    } else {
+        println!(
+            "{}: {} uploaded, {} skipped, {} missing, {} errors",
+            "Done".green().bold(), uploaded, skipped, missing, errors
+        );
+        println!("Uploaded {}", format_transfer(transferred, started.elapsed()));
+    }
+
+    if errors > 0 {
+        return Err(GgError::Storage("Some files failed to upload".to_string()).into());
+    }
+    if missing > 0 {
+        return Err(GgError::Storage(format!(
+            "{} LFS object(s) are missing from the local cache and remote storage",
+            missing
+        )).into());
+    }
+    Ok(())
+}
+
+/// Objects known to be in storage, read from the bucket's manifest so most
+/// existence checks don't need a HEAD request. A manifest miss still asks
+/// storage, since another pusher may have uploaded the object.
+struct KnownObjects<'a> {
+    storage: &'a dyn Storage,
+    /// None when the manifest is disabled or couldn't be read
+    manifest: Option<ObjectManifest>,
+    /// Objects confirmed or uploaded this run that the manifest didn't list
+    added: usize,
+    /// Set when a dry run couldn't reach storage, so it stops asking
+    unreachable: bool,
+}
 
-        let output = std::process::Command::new("git")
-            .args(&diff_args)
-            .current_dir(repo_root)
-            .output()?;
-
-        if output.status.success() {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            for file_line in stdout.lines() {
-                let path = Path::new(file_line);
-                if scanner.is_lfs_file(path) {
-                    let full_path = repo_root.join(path);
-                    if full_path.exists() { files.insert(full_path); }
+impl<'a> KnownObjects<'a> {
+    /// Read the manifest, falling back to HEAD requests if that fails
+    async fn load(storage: &'a dyn Storage, enabled: bool) -> Self {
+        let manifest = if !enabled {
+            None
+        } else {
+            match storage.read_manifest().await {
+                Ok(manifest) => Some(manifest.unwrap_or_default()),
+                Err(e) => {
+                    eprintln!("{} could not read the storage manifest, checking each object: {}", "Warning:".yellow(), e);
+                    None
                 }
             }
+        };
+        Self { storage, manifest, added: 0, unreachable: false }
+    }
+
+    /// Whether the manifest lists an object, without asking storage
+    fn contains(&self, oid: &str) -> bool {
+        self.manifest.as_ref().is_some_and(|manifest| manifest.contains(oid))
+    }
+
+    /// Whether storage has an object, asking storage on a manifest miss
+    async fn exists(&mut self, oid: &str) -> Result<bool, StorageError> {
+        if self.contains(oid) {
+            return Ok(true);
+        }
+        let exists = self.storage.exists(oid).await?;
+        if exists {
+            self.record(oid);
+        }
+        Ok(exists)
+    }
+
+    /// Whether storage has an object, for a dry run. A dry run should work
+    /// offline, so after warning once about a storage error every object
+    /// counts as missing.
+    async fn probably_exists(&mut self, oid: &str) -> bool {
+        if self.unreachable {
+            return self.contains(oid);
+        }
+        match self.exists(oid).await {
+            Ok(exists) => exists,
+            Err(e) => {
+                eprintln!("{} could not check storage, assuming no objects are stored: {}", "Warning:".yellow(), e);
+                self.unreachable = true;
+                false
+            }
+        }
+    }
+
+    /// Note an object that is now in storage
+    fn record(&mut self, oid: &str) {
+        if let Some(manifest) = self.manifest.as_mut() {
+            if manifest.insert(oid) {
+                self.added += 1;
+            }
         }
     }
 
-    Ok(files.into_iter().collect())
+    /// Write back any additions, merged with whatever other pushers wrote
+    /// since the manifest was read. Failing only costs HEAD requests next time.
+    async fn save(self) {
+        let Some(mut manifest) = self.manifest else { return };
+        if self.added == 0 {
+            return;
+        }
+        if let Ok(Some(latest)) = self.storage.read_manifest().await {
+            manifest.merge(latest);
+        }
+        if let Err(e) = self.storage.write_manifest(&manifest).await {
+            eprintln!("{} could not update the storage manifest: {}", "Warning:".yellow(), e);
+        }
+    }
+}
+
+/// Show a file a dry run would push, or add it to the JSON manifest
+fn report_dry_run(manifest: &mut Option<Vec<ManifestEntry>>, relative: &Path, pointer: &Pointer, action: Action) {
+    match manifest {
+        Some(entries) => entries.push(ManifestEntry::new(relative, pointer, action)),
+        None if matches!(action, Action::Skip) => {
+            println!("  {} {} ({} bytes)", "Would skip:".dimmed(), relative.display(), pointer.size)
+        }
+        None => println!("  {} {} ({} bytes)", "Would upload:".cyan(), relative.display(), pointer.size),
+    }
+}
+
+/// Find LFS pointer blobs reachable from any ref, one entry per object
+pub(super) fn find_all_pointer_objects(
+    repo: &git2::Repository,
+    scanner: &Scanner,
+) -> Result<Vec<(std::path::PathBuf, Pointer)>, Box<dyn std::error::Error>> {
+    find_pointer_objects(repo, scanner, &["--all".to_string()])
+}
+
+/// Find LFS pointer blobs in the commits `revisions` select, as given to
+/// `git rev-list`, one entry per object
+fn find_pointer_objects(
+    repo: &git2::Repository,
+    scanner: &Scanner,
+    revisions: &[String],
+) -> Result<Vec<(std::path::PathBuf, Pointer)>, Box<dyn std::error::Error>> {
+    let repo_root = workdir_root(repo)?;
+    let output = std::process::Command::new("git")
+        .args(["rev-list", "--objects"])
+        .args(revisions)
+        .current_dir(repo_root)
+        .output()?;
+    if !output.status.success() {
+        return Err(GgError::Git(format!(
+            "git rev-list failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )).into());
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    let mut objects = Vec::new();
+
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        // Blob and tree lines are "<sha> <path>"; commits have no path
+        let Some((sha, path)) = line.split_once(' ') else { continue };
+        let path = Path::new(path);
+        if !scanner.is_lfs_file(path) {
+            continue;
+        }
+
+        let Ok(blob) = git2::Oid::from_str(sha).and_then(|oid| repo.find_blob(oid)) else { continue };
+        // Pointer files are tiny; anything bigger is committed content
+        if blob.size() > MAX_POINTER_SIZE {
+            continue;
+        }
+
+        if let Ok(pointer) = Pointer::parse_content(blob.content()) {
+            if seen.insert(pointer.oid.clone()) {
+                objects.push((path.to_path_buf(), pointer));
+            }
+        }
+    }
+
+    Ok(objects)
+}
+
+/// Create a byte-level progress bar for a single file transfer
+fn byte_progress_bar(multi: Option<&MultiProgress>, size: u64, relative: &Path) -> Option<ProgressBar> {
+    let pb = multi?.add(ProgressBar::new(size));
+    pb.set_style(ProgressStyle::default_bar()
+        .template("  {bar:30} {bytes}/{total_bytes} ({bytes_per_sec}) {msg}")
+        .unwrap_or_else(|_| ProgressStyle::default_bar()));
+    pb.set_message(relative.display().to_string());
+    Some(pb)
+}
+
+/// Revisions for `git rev-list` selecting the commits a push sends, from the
+/// pre-push hook's stdin: each pushed commit, minus what the remote has.
+/// Empty when the push only deletes refs.
+fn read_pre_push_revisions(input: impl BufRead) -> std::io::Result<Vec<String>> {
+    const ZERO_SHA: &str = "0000000000000000000000000000000000000000";
+    let mut pushed = Vec::new();
+    let mut remote = Vec::new();
+
+    // Each line is "<local ref> <local sha> <remote ref> <remote sha>"
+    for line in input.lines() {
+        let line = line?;
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 4 || parts[1] == ZERO_SHA { continue; }
+
+        pushed.push(parts[1].to_string());
+        if parts[3] != ZERO_SHA {
+            remote.push(parts[3].to_string());
+        }
+    }
+
+    if pushed.is_empty() {
+        return Ok(pushed);
+    }
+    // Commits on remote-tracking branches were pushed already. The remote's
+    // sha may be unknown here, after a force push over unfetched commits.
+    let mut revisions = vec!["--ignore-missing".to_string()];
+    revisions.append(&mut pushed);
+    revisions.extend(["--not".to_string(), "--remotes".to_string()]);
+    revisions.extend(remote);
+    Ok(revisions)
 }
 
 /// Get staged files that match LFS patterns
@@ -225,7 +849,7 @@ fn get_staged_lfs_files(
     scanner: &Scanner,
 ) -> Result<Vec<std::path::PathBuf>, Box<dyn std::error::Error>> {
     let mut files = Vec::new();
-    let repo_root = repo.workdir().ok_or("No working directory")?;
+    let repo_root = workdir_root(repo)?;
     let index = repo.index()?;
 
     for entry in index.iter() {
@@ -240,3 +864,155 @@ fn get_staged_lfs_files(
     Ok(files)
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lfs::storage::{DownloadResult, ObjectInfo, UploadResult};
+    use async_trait::async_trait;
+    use std::collections::HashSet;
+    use std::sync::Mutex;
+
+    /// Storage holding a set of OIDs and a manifest, counting HEAD requests
+    #[derive(Default)]
+    struct ManifestStorage {
+        objects: HashSet<String>,
+        manifest: Mutex<Option<ObjectManifest>>,
+        heads: Mutex<usize>,
+    }
+
+    #[async_trait]
+    impl Storage for ManifestStorage {
+        async fn upload(&self, oid: &str, _source: &Path) -> Result<UploadResult, StorageError> {
+            Ok(UploadResult { oid: oid.to_string(), size: 0, uploaded: true })
+        }
+
+        async fn download(&self, oid: &str, _dest: &Path) -> Result<DownloadResult, StorageError> {
+            Err(StorageError::NotFound(oid.to_string()))
+        }
+
+        async fn exists(&self, oid: &str) -> Result<bool, StorageError> {
+            *self.heads.lock().unwrap() += 1;
+            Ok(self.objects.contains(oid))
+        }
+
+        async fn stat(&self, _oid: &str) -> Result<Option<ObjectInfo>, StorageError> {
+            Ok(None)
+        }
+
+        async fn read_manifest(&self) -> Result<Option<ObjectManifest>, StorageError> {
+            Ok(self.manifest.lock().unwrap().clone())
+        }
+
+        async fn write_manifest(&self, manifest: &ObjectManifest) -> Result<(), StorageError> {
+            *self.manifest.lock().unwrap() = Some(manifest.clone());
+            Ok(())
+        }
+
+        async fn delete(&self, _oid: &str) -> Result<(), StorageError> {
+            Ok(())
+        }
+
+        fn provider_name(&self) -> &str {
+            "manifest"
+        }
+    }
+
+    #[test]
+    fn test_known_objects_skips_head_requests_for_manifest_hits() {
+        let storage = ManifestStorage {
+            objects: ["listed", "unlisted"].map(String::from).into(),
+            manifest: Mutex::new(Some(ObjectManifest::parse(br#"{"oids":["listed"]}"#).unwrap())),
+            ..Default::default()
+        };
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async {
+            let mut known = KnownObjects::load(&storage, true).await;
+            assert!(known.exists("listed").await.unwrap());
+            assert_eq!(*storage.heads.lock().unwrap(), 0);
+
+            // A miss still asks storage, so objects pushed by others are found
+            assert!(known.exists("unlisted").await.unwrap());
+            assert!(!known.exists("absent").await.unwrap());
+            assert_eq!(*storage.heads.lock().unwrap(), 2);
+
+            known.record("uploaded");
+            known.save().await;
+        });
+
+        let saved = storage.manifest.lock().unwrap().clone().unwrap();
+        for oid in ["listed", "unlisted", "uploaded"] {
+            assert!(saved.contains(oid), "{} missing from manifest", oid);
+        }
+        assert!(!saved.contains("absent"));
+    }
+
+    #[test]
+    fn test_known_objects_without_manifest_always_asks_storage() {
+        let storage = ManifestStorage {
+            objects: ["listed"].map(String::from).into(),
+            manifest: Mutex::new(Some(ObjectManifest::parse(br#"{"oids":["listed"]}"#).unwrap())),
+            ..Default::default()
+        };
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async {
+            let mut known = KnownObjects::load(&storage, false).await;
+            assert!(known.exists("listed").await.unwrap());
+            known.record("uploaded");
+            known.save().await;
+        });
+
+        assert_eq!(*storage.heads.lock().unwrap(), 1);
+        assert!(!storage.manifest.lock().unwrap().as_ref().unwrap().contains("uploaded"));
+    }
+
+    #[test]
+    fn test_upload_pending_checks_and_uploads_each_object_once() {
+        let storage: &'static ManifestStorage = Box::leak(Box::new(ManifestStorage {
+            objects: ["stored"].map(String::from).into(),
+            ..Default::default()
+        }));
+        let pending = |oid: &str, source: Option<&str>| PendingUpload {
+            relative: PathBuf::from(format!("{}.bin", oid)),
+            oid: oid.to_string(),
+            size: 0,
+            source: source.map(PathBuf::from),
+        };
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        let outcomes = rt.block_on(upload_pending(
+            storage,
+            vec![pending("stored", Some("a")), pending("new", Some("b")), pending("lost", None)],
+            2,
+            false,
+            None,
+            None,
+        )).unwrap();
+
+        let oids: Vec<&str> = outcomes.iter().map(|(upload, _)| upload.oid.as_str()).collect();
+        assert_eq!(oids, ["stored", "new", "lost"]);
+        assert!(matches!(outcomes[0].1, UploadOutcome::Present));
+        assert!(matches!(outcomes[1].1, UploadOutcome::Uploaded(_)));
+        assert!(matches!(outcomes[2].1, UploadOutcome::Missing));
+        assert_eq!(*storage.heads.lock().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_read_pre_push_revisions_excludes_what_the_remote_has() {
+        let zero = "0".repeat(40);
+        let (a, b, c) = ("a".repeat(40), "b".repeat(40), "c".repeat(40));
+        let input = format!(
+            "refs/heads/main {a} refs/heads/main {b}\n\
+             refs/heads/new {c} refs/heads/new {zero}\n\
+             (delete) {zero} refs/heads/old {b}\n"
+        );
+
+        let revisions = read_pre_push_revisions(input.as_bytes()).unwrap();
+        assert_eq!(revisions, ["--ignore-missing", &a, &c, "--not", "--remotes", &b]);
+
+        let deletion = format!("(delete) {zero} refs/heads/old {b}\n");
+        assert!(read_pre_push_revisions(deletion.as_bytes()).unwrap().is_empty());
+    }
+}
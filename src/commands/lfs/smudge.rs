@@ -6,11 +6,18 @@
 //! Reads pointer text from stdin, outputs real file content to stdout.
 //! Checks local cache first, falls back to S3 download on cache miss.
 
+use super::error::{exit_code, GgError};
+use crate::lfs::config::SMUDGE_TIMEOUT_ENV;
 use crate::lfs::pointer::MAX_POINTER_SIZE;
-use crate::lfs::storage;
+use crate::lfs::storage::{self, Storage, StorageError};
 use crate::lfs::{Cache, LfsConfig, Pointer};
+use crate::logging::{log_debug, log_error, log_warn};
+use crate::utils::workdir_root;
 use clap::Args;
+use std::future::Future;
 use std::io::{self, Read, Write};
+use std::path::Path;
+use std::time::Duration;
 
 #[derive(Args, Debug)]
 pub struct SmudgeArgs {
@@ -23,8 +30,8 @@ pub fn run(args: SmudgeArgs) -> i32 {
     match run_inner(args) {
         Ok(_) => 0,
         Err(e) => {
-            eprintln!("gg lfs smudge: {}", e);
-            1
+            log_error!("gg lfs smudge", "{}", e);
+            exit_code(&*e)
         }
     }
 }
@@ -64,24 +71,23 @@ fn download_and_output(
 ) -> Result<(), Box<dyn std::error::Error>> {
     let oid = pointer.sha256().to_string();
 
+    let file_hint = args.file.as_deref().unwrap_or("<unknown>");
+
     // Check local cache first — stream directly to stdout
-    if let Ok(cache) = Cache::new() {
-        if let Some(cached_path) = cache.get(&oid) {
-            let mut file = std::fs::File::open(&cached_path)?;
-            io::copy(&mut file, &mut io::stdout())?;
-            io::stdout().flush()?;
-            return Ok(());
-        }
+    if stream_cached(&oid, &mut io::stdout())? {
+        log_debug!("gg lfs smudge", "{}: served {} from cache", file_hint, oid);
+        return Ok(());
     }
 
     // Cache miss — try S3 download
-    let file_hint = args.file.as_deref().unwrap_or("<unknown>");
+    log_debug!("gg lfs smudge", "{}: downloading {}", file_hint, oid);
 
     let repo = match git2::Repository::discover(".") {
         Ok(r) => r,
         Err(_) => {
-            eprintln!(
-                "gg lfs smudge: warning: cannot find repository for {}, outputting pointer",
+            log_warn!(
+                "gg lfs smudge",
+                "cannot find repository for {}, outputting pointer",
                 file_hint
             );
             io::stdout().write_all(pointer_bytes)?;
@@ -90,11 +96,12 @@ fn download_and_output(
         }
     };
 
-    let repo_root = match repo.workdir() {
-        Some(r) => r,
-        None => {
-            eprintln!(
-                "gg lfs smudge: warning: bare repository, outputting pointer for {}",
+    let repo_root = match workdir_root(&repo) {
+        Ok(r) => r,
+        Err(_) => {
+            log_warn!(
+                "gg lfs smudge",
+                "bare repository, outputting pointer for {}",
                 file_hint
             );
             io::stdout().write_all(pointer_bytes)?;
@@ -103,11 +110,12 @@ fn download_and_output(
         }
     };
 
-    let config = match LfsConfig::load(repo_root) {
+    let config = match LfsConfig::load(&repo_root) {
         Ok(c) => c,
         Err(_) => {
-            eprintln!(
-                "gg lfs smudge: warning: no LFS config, outputting pointer for {}",
+            log_warn!(
+                "gg lfs smudge",
+                "no LFS config, outputting pointer for {}",
                 file_hint
             );
             io::stdout().write_all(pointer_bytes)?;
@@ -117,48 +125,16 @@ fn download_and_output(
     };
 
     // Need async runtime for S3 download
-    let rt = tokio::runtime::Runtime::new()?;
+    let rt = crate::lfs::runtime()?;
     let result = rt.block_on(async {
-        let storage = storage::create_storage(&config).await?;
-
-        // Download to a temp file
-        let temp_dir = repo_root.join(".gg").join("tmp");
-        std::fs::create_dir_all(&temp_dir)?;
-        let temp_path = temp_dir.join(&oid);
-
-        storage.download(&oid, &temp_path).await?;
-
-        // Verify hash
-        let downloaded_pointer = Pointer::from_file(&temp_path)?;
-        if downloaded_pointer.oid != pointer.oid {
-            std::fs::remove_file(&temp_path).ok();
-            let err: Box<dyn std::error::Error> =
-                format!("hash mismatch for {}", file_hint).into();
-            return Err(err);
-        }
-
-        // Cache the downloaded file
-        if let Ok(cache) = Cache::new() {
-            let _ = cache.put_file(&oid, &temp_path);
-        }
-
-        // Stream temp file to stdout instead of reading into memory
-        let mut file = std::fs::File::open(&temp_path)?;
-        io::copy(&mut file, &mut io::stdout())?;
-        io::stdout().flush()?;
-
-        // Clean up temp file
-        std::fs::remove_file(&temp_path).ok();
-
-        Ok::<(), Box<dyn std::error::Error>>(())
+        let storage = storage::shared_storage(&config).await?;
+        let timeout = Some(config.smudge_timeout());
+        download_and_stream(storage, &oid, Some(pointer.size), &repo_root, file_hint, timeout, &mut io::stdout()).await
     });
 
     if let Err(e) = result {
         // Graceful degradation: output the pointer content + warning
-        eprintln!(
-            "gg lfs smudge: warning: download failed for {}: {}",
-            file_hint, e
-        );
+        log_warn!("gg lfs smudge", "download failed for {}: {}", file_hint, e);
         io::stdout().write_all(pointer_bytes)?;
         io::stdout().flush()?;
     }
@@ -166,6 +142,104 @@ fn download_and_output(
     Ok(())
 }
 
+/// Stream a cached object to `out`. Returns false on a cache miss.
+pub(super) fn stream_cached(oid: &str, out: &mut dyn Write) -> io::Result<bool> {
+    let Some(cached_path) = Cache::new().ok().and_then(|cache| cache.get(oid)) else {
+        return Ok(false);
+    };
+    let mut file = std::fs::File::open(&cached_path)?;
+    io::copy(&mut file, out)?;
+    out.flush()?;
+    Ok(true)
+}
+
+/// Download an object, verify its hash, add it to the cache and stream it to `out`.
+/// `size`, when the pointer gave one, is checked too. `label` names the object
+/// in error messages. The download fails once it takes longer than `timeout`,
+/// if given.
+pub(super) async fn download_and_stream(
+    storage: &dyn Storage,
+    oid: &str,
+    size: Option<u64>,
+    repo_root: &Path,
+    label: &str,
+    timeout: Option<Duration>,
+    out: &mut dyn Write,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Download to a temp file
+    let temp_dir = repo_root.join(".gg").join("tmp");
+    std::fs::create_dir_all(&temp_dir)?;
+    let temp_path = temp_dir.join(oid);
+
+    match timeout {
+        Some(limit) => download_within(storage, oid, &temp_path, limit).await?,
+        None => {
+            storage.download(oid, &temp_path).await?;
+        }
+    }
+
+    // Verify hash and size
+    let downloaded_pointer = Pointer::from_file(&temp_path)?;
+    if downloaded_pointer.sha256() != oid {
+        std::fs::remove_file(&temp_path).ok();
+        return Err(GgError::Integrity(format!("hash mismatch for {}", label)).into());
+    }
+    if let Some(size) = size.filter(|&size| size != downloaded_pointer.size) {
+        std::fs::remove_file(&temp_path).ok();
+        return Err(GgError::Integrity(format!(
+            "size mismatch for {}: pointer says {} bytes, object has {}",
+            label, size, downloaded_pointer.size
+        )).into());
+    }
+
+    // Cache the downloaded file
+    if let Ok(cache) = Cache::new() {
+        let _ = cache.put_file(oid, &temp_path);
+    }
+
+    // Stream temp file to the output instead of reading into memory
+    let mut file = std::fs::File::open(&temp_path)?;
+    let result = io::copy(&mut file, out).and_then(|_| out.flush());
+
+    // Clean up temp file
+    std::fs::remove_file(&temp_path).ok();
+
+    result?;
+    Ok(())
+}
+
+/// Download an object to `dest`, giving up after `limit`. A partial file
+/// is removed on failure.
+async fn download_within(
+    storage: &dyn Storage,
+    oid: &str,
+    dest: &Path,
+    limit: Duration,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let result = within(limit, storage.download(oid, dest)).await;
+    if result.is_err() {
+        std::fs::remove_file(dest).ok();
+    }
+    result.map(|_| ())
+}
+
+/// Run a storage request, giving up after `limit` so a stalled transfer
+/// can't hang a checkout
+pub(super) async fn within<T>(
+    limit: Duration,
+    request: impl Future<Output = Result<T, StorageError>>,
+) -> Result<T, Box<dyn std::error::Error>> {
+    match tokio::time::timeout(limit, request).await {
+        Ok(result) => Ok(result?),
+        Err(_) => Err(GgError::Storage(format!(
+            "timed out after {}s (set {} to wait longer)",
+            limit.as_secs(),
+            SMUDGE_TIMEOUT_ENV
+        ))
+        .into()),
+    }
+}
+
 /// Read up to `buf.len()` bytes, returning the actual number read.
 /// Unlike `read_exact`, does not error on EOF.
 fn read_exact_or_eof<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
@@ -180,3 +254,65 @@ fn read_exact_or_eof<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usiz
     }
     Ok(total)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::error::EXIT_INTEGRITY;
+    use super::*;
+    use crate::lfs::storage::{DownloadResult, ObjectInfo, UploadResult};
+
+    /// Storage that downloads `content` for any OID
+    struct FixedStorage {
+        content: &'static [u8],
+    }
+
+    #[async_trait::async_trait]
+    impl Storage for FixedStorage {
+        async fn upload(&self, oid: &str, _source: &Path) -> Result<UploadResult, StorageError> {
+            Err(StorageError::Config(oid.to_string()))
+        }
+
+        async fn download(&self, oid: &str, dest: &Path) -> Result<DownloadResult, StorageError> {
+            std::fs::write(dest, self.content)?;
+            Ok(DownloadResult { oid: oid.to_string(), size: self.content.len() as u64, path: dest.to_path_buf() })
+        }
+
+        async fn exists(&self, _oid: &str) -> Result<bool, StorageError> {
+            Ok(true)
+        }
+
+        async fn stat(&self, _oid: &str) -> Result<Option<ObjectInfo>, StorageError> {
+            Ok(None)
+        }
+
+        async fn delete(&self, _oid: &str) -> Result<(), StorageError> {
+            Ok(())
+        }
+
+        fn provider_name(&self) -> &str {
+            "fixed"
+        }
+    }
+
+    #[test]
+    fn test_download_and_stream_rejects_wrong_size() {
+        let content: &'static [u8] = b"content whose pointer has the wrong size";
+        let storage = FixedStorage { content };
+        let oid = Pointer::from_bytes(content).sha256().to_string();
+        let repo_root = tempfile::TempDir::new().unwrap();
+        let mut out = Vec::new();
+        let rt = crate::lfs::runtime().unwrap();
+
+        let size = Some(content.len() as u64 + 1);
+        let err = rt
+            .block_on(download_and_stream(&storage, &oid, size, repo_root.path(), "a.bin", None, &mut out))
+            .unwrap_err();
+
+        assert_eq!(exit_code(&*err), EXIT_INTEGRITY);
+        assert!(err.to_string().contains("size mismatch for a.bin"), "{}", err);
+        assert!(out.is_empty());
+        // Rejected before it reached the cache
+        assert!(!repo_root.path().join(".gg").join("tmp").join(&oid).exists());
+        assert!(Cache::new().ok().and_then(|cache| cache.get(&oid)).is_none());
+    }
+}
@@ -1,36 +1,41 @@
 //! LFS status command
 
+use crate::lfs::pointer::MAX_POINTER_SIZE;
 use crate::lfs::{Cache, LfsConfig, Pointer, Scanner};
+use crate::logging::{self, Level};
+use crate::utils::get_repo_with_root;
 use clap::Args;
 use colored::Colorize;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use super::error::report;
 
+/// Detailed file information is shown with the global `-v` flag
 #[derive(Args, Debug)]
 pub struct StatusArgs {
-    /// Show detailed information
-    #[arg(short, long)]
-    pub verbose: bool,
+    /// Report how much of the shared cache this repository's objects use
+    #[arg(long)]
+    pub repo_cache: bool,
+
+    /// With --repo-cache, also count objects referenced from any ref
+    #[arg(long, requires = "repo_cache")]
+    pub all: bool,
 }
 
 /// Show LFS status
 pub fn run(args: StatusArgs) -> i32 {
     match run_inner(args) {
         Ok(_) => 0,
-        Err(e) => {
-            eprintln!("{} {}", "Error:".red().bold(), e);
-            1
-        }
+        Err(e) => report(&*e),
     }
 }
 
 fn run_inner(args: StatusArgs) -> Result<(), Box<dyn std::error::Error>> {
-    let repo = git2::Repository::discover(".")?;
-    let repo_root = repo
-        .workdir()
-        .ok_or("Not a git repository with a working directory")?;
+    let (repo, repo_root) = get_repo_with_root()?;
+    let verbose = logging::enabled(Level::Info);
 
     // Check for config
-    let config_exists = LfsConfig::exists(repo_root);
+    let config_exists = LfsConfig::exists(&repo_root);
 
     println!("{}", "gg-lfs Status".bold());
     println!("{}", "=".repeat(40));
@@ -38,7 +43,7 @@ fn run_inner(args: StatusArgs) -> Result<(), Box<dyn std::error::Error>> {
     // Configuration status
     println!("\n{}", "Configuration:".cyan().bold());
     if config_exists {
-        let config = LfsConfig::load(repo_root)?;
+        let config = LfsConfig::load(&repo_root)?;
         println!(
             "  Provider: {}",
             format!("{:?}", config.storage.provider).green()
@@ -51,16 +56,24 @@ fn run_inner(args: StatusArgs) -> Result<(), Box<dyn std::error::Error>> {
         if let Some(endpoint) = &config.storage.endpoint {
             println!("  Endpoint: {}", endpoint);
         }
+        if config.storage.read_endpoint.is_some() || config.storage.read_bucket.is_some() {
+            println!(
+                "  Mirror:   {}{}",
+                config.storage.read_bucket.as_deref().unwrap_or(&config.storage.bucket),
+                config.storage.read_endpoint.as_deref().map(|e| format!(" at {}", e)).unwrap_or_default()
+            );
+        }
     } else {
         println!(
-            "  {}",
-            "Not configured. Run 'gg lfs install' to set up.".yellow()
+            "  {} {}",
+            "Not configured.".yellow(),
+            LfsConfig::setup_hint(&repo_root).yellow()
         );
     }
 
     // Patterns
     println!("\n{}", "Tracked Patterns:".cyan().bold());
-    let scanner = Scanner::new(repo_root)?;
+    let scanner = Scanner::new(&repo_root)?;
     let patterns = scanner.patterns();
 
     if patterns.is_empty() {
@@ -74,6 +87,8 @@ fn run_inner(args: StatusArgs) -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
+    let cache = Cache::new().ok();
+
     // Files
     println!("\n{}", "LFS Files:".cyan().bold());
     let files = scanner.scan_files()?;
@@ -84,20 +99,28 @@ fn run_inner(args: StatusArgs) -> Result<(), Box<dyn std::error::Error>> {
         let mut pointers = 0;
         let mut actual_files = 0;
         let mut total_size: u64 = 0;
+        // OID -> whether it is cached, for pointers (verbose only)
+        let mut objects: HashMap<String, bool> = HashMap::new();
 
         for file_path in &files {
             let relative = file_path
-                .strip_prefix(repo_root)
+                .strip_prefix(&repo_root)
                 .unwrap_or(file_path);
 
             if Pointer::is_pointer_file(file_path) {
-                if args.verbose {
+                if verbose {
                     let pointer = Pointer::parse(file_path)?;
+                    let is_cached = cache
+                        .as_ref()
+                        .is_some_and(|cache| cache.contains(pointer.sha256()));
+                    objects.insert(pointer.sha256().to_string(), is_cached);
                     println!(
-                        "  {} {} ({} bytes, pointer)",
+                        "  {} {} ({} bytes, pointer, {}){}",
                         "→".dimmed(),
                         relative.display(),
-                        pointer.size
+                        pointer.size,
+                        if is_cached { "cached".green() } else { "missing".yellow() },
+                        matched_by(&scanner, relative)
                     );
                 }
                 pointers += 1;
@@ -105,19 +128,20 @@ fn run_inner(args: StatusArgs) -> Result<(), Box<dyn std::error::Error>> {
                 let size = std::fs::metadata(file_path)?.len();
                 total_size += size;
 
-                if args.verbose {
+                if verbose {
                     println!(
-                        "  {} {} ({} bytes)",
+                        "  {} {} ({} bytes){}",
                         "●".green(),
                         relative.display(),
-                        size
+                        size,
+                        matched_by(&scanner, relative)
                     );
                 }
                 actual_files += 1;
             }
         }
 
-        if !args.verbose {
+        if !verbose {
             println!("  {} file(s) as pointers", pointers);
             println!(
                 "  {} file(s) need upload ({} bytes)",
@@ -127,27 +151,75 @@ fn run_inner(args: StatusArgs) -> Result<(), Box<dyn std::error::Error>> {
         } else {
             println!();
             println!("  {} pointers, {} actual files", pointers, actual_files);
+            if !objects.is_empty() {
+                let cached = objects.values().filter(|&&is_cached| is_cached).count();
+                println!("  {} of {} objects cached locally", cached, objects.len());
+            }
         }
     }
 
+    // Pointers that no pattern tracks won't be smudged by git
+    let orphans = scanner.scan_orphaned_pointers()?;
+    if !orphans.is_empty() {
+        println!("\n{}", "Orphaned Pointers:".yellow().bold());
+        for file_path in &orphans {
+            let relative = file_path.strip_prefix(&repo_root).unwrap_or(file_path);
+            println!("  {} {}", "!".yellow(), relative.display());
+        }
+        println!(
+            "  {}",
+            "These files are LFS pointers, but no pattern tracks them, so git won't restore their content.\n  \
+             Re-track them with 'gg lfs track <pattern>' and run 'gg lfs pull', or view the content with 'gg lfs cat <path>'."
+                .dimmed()
+        );
+    }
+
     // Cache status
     println!("\n{}", "Local Cache:".cyan().bold());
-    match Cache::new() {
-        Ok(cache) => {
+    match &cache {
+        Some(cache) => {
             let count = cache.count().unwrap_or(0);
             let size = cache.size().unwrap_or(0);
-            println!("  {} objects ({})", count, format_size(size));
+            if args.repo_cache {
+                let referenced = referenced_oids(&repo, &scanner, &files, args.all)?;
+                let usage = RepoCacheUsage::of(cache.iter_objects(), &referenced);
+                println!(
+                    "  {} objects ({}) referenced by this repository{}",
+                    usage.cached,
+                    format_size(usage.size),
+                    if args.all { " on any ref" } else { "" }
+                );
+                if usage.missing > 0 {
+                    println!("  {} referenced object(s) not cached", usage.missing);
+                }
+                println!("  {}", format!("of {} objects ({}) in the shared cache", count, format_size(size)).dimmed());
+            } else {
+                println!("  {} objects ({})", count, format_size(size));
+            }
         }
-        Err(_) => {
+        None => {
             println!("  {}", "Cache not available".dimmed());
         }
     }
 
     // Hooks status
     println!("\n{}", "Git Hooks:".cyan().bold());
-    let hooks_dir = repo_root.join(".git").join("hooks");
+    let (hooks_dir, _) = super::install::hooks_dir(&repo)?;
     let hooks = ["pre-push", "post-checkout", "post-merge"];
 
+    // Teammates on different gg versions are a common source of confusion
+    if let Some(version) = super::install::installed_version(&repo) {
+        if version == env!("CARGO_PKG_VERSION") {
+            println!("  Installed by gg {}", version);
+        } else {
+            println!(
+                "  Installed by gg {} {}",
+                version,
+                format!("(this is gg {}; run 'gg lfs install' to update)", env!("CARGO_PKG_VERSION")).yellow()
+            );
+        }
+    }
+
     for hook in hooks {
         let hook_path = hooks_dir.join(hook);
         let status = if hook_path.exists() {
@@ -165,8 +237,71 @@ fn run_inner(args: StatusArgs) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// OIDs the checkout refers to: pointers staged in the index (which covers
+/// files checked out with their content) and pointer files in the working
+/// tree. With `all_refs`, also pointers reachable from any ref.
+pub(super) fn referenced_oids(
+    repo: &git2::Repository,
+    scanner: &Scanner,
+    files: &[PathBuf],
+    all_refs: bool,
+) -> Result<HashSet<String>, Box<dyn std::error::Error>> {
+    let mut oids = HashSet::new();
+
+    for entry in repo.index()?.iter() {
+        let path_str = String::from_utf8_lossy(&entry.path);
+        if !scanner.is_lfs_file(Path::new(path_str.as_ref())) || entry.file_size as usize > MAX_POINTER_SIZE {
+            continue;
+        }
+        let Ok(blob) = repo.find_blob(entry.id) else { continue };
+        if let Ok(pointer) = Pointer::parse_content(blob.content()) {
+            oids.insert(pointer.sha256().to_string());
+        }
+    }
+
+    for file_path in files {
+        if let Ok(pointer) = Pointer::parse(file_path) {
+            oids.insert(pointer.sha256().to_string());
+        }
+    }
+
+    if all_refs {
+        for (_, pointer) in super::push::find_all_pointer_objects(repo, scanner)? {
+            oids.insert(pointer.sha256().to_string());
+        }
+    }
+
+    Ok(oids)
+}
+
+/// The part of the cache holding a repository's objects
+#[derive(Debug, Default, PartialEq, Eq)]
+struct RepoCacheUsage {
+    /// Referenced objects present in the cache
+    cached: usize,
+    /// Their total size in bytes
+    size: u64,
+    /// Referenced objects not in the cache
+    missing: usize,
+}
+
+impl RepoCacheUsage {
+    /// Intersect cached objects, as `(oid, path, size)`, with the referenced OIDs
+    fn of(objects: impl Iterator<Item = (String, PathBuf, u64)>, referenced: &HashSet<String>) -> Self {
+        let mut usage = Self::default();
+        for (oid, _, size) in objects {
+            if referenced.contains(&oid) {
+                usage.cached += 1;
+                usage.size += size;
+            }
+        }
+        usage.missing = referenced.len().saturating_sub(usage.cached);
+        usage
+    }
+}
+
 /// Check if a hook file is a gg-lfs hook
-fn is_lfs_hook(path: &Path) -> bool {
+pub(super) fn is_lfs_hook(path: &Path) -> bool {
     if let Ok(content) = std::fs::read_to_string(path) {
         content.contains("gg-lfs") || content.contains("gg lfs")
     } else {
@@ -174,8 +309,16 @@ fn is_lfs_hook(path: &Path) -> bool {
     }
 }
 
+/// " [pattern]" naming the .gitattributes pattern that tracks a file
+fn matched_by(scanner: &Scanner, relative: &Path) -> String {
+    match scanner.matching_pattern(relative) {
+        Some(pattern) => format!(" {}", format!("[{}]", pattern.pattern).dimmed()),
+        None => String::new(),
+    }
+}
+
 /// Format bytes as human-readable size
-fn format_size(bytes: u64) -> String {
+pub(super) fn format_size(bytes: u64) -> String {
     const KB: u64 = 1024;
     const MB: u64 = KB * 1024;
     const GB: u64 = MB * 1024;
@@ -190,3 +333,71 @@ fn format_size(bytes: u64) -> String {
         format!("{} bytes", bytes)
     }
 }
+
+/// Format a wall-clock duration compactly, e.g. "4.2s", "2m13s" or "1h05m"
+pub(super) fn format_elapsed(elapsed: std::time::Duration) -> String {
+    let secs = elapsed.as_secs();
+    if secs < 60 {
+        format!("{:.1}s", elapsed.as_secs_f64())
+    } else if secs < 3600 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60)
+    }
+}
+
+/// Summarize a transfer as size, time and throughput, e.g. "12.40 GB in 2m13s (95.48 MB/s)"
+pub(super) fn format_transfer(bytes: u64, elapsed: std::time::Duration) -> String {
+    let secs = elapsed.as_secs_f64();
+    let rate = if secs > 0.0 { (bytes as f64 / secs) as u64 } else { 0 };
+    format!("{} in {} ({}/s)", format_size(bytes), format_elapsed(elapsed), format_size(rate))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_repo_cache_usage_counts_only_referenced_objects() {
+        let objects = vec![
+            ("aaa".to_string(), PathBuf::from("aa/aaa"), 10),
+            ("bbb".to_string(), PathBuf::from("bb/bbb"), 20),
+            ("ccc".to_string(), PathBuf::from("cc/ccc"), 40),
+        ];
+        let referenced: HashSet<String> = ["aaa", "ccc", "ddd"].iter().map(|oid| oid.to_string()).collect();
+
+        assert_eq!(
+            RepoCacheUsage::of(objects.into_iter(), &referenced),
+            RepoCacheUsage { cached: 2, size: 50, missing: 1 }
+        );
+    }
+
+    #[test]
+    fn test_repo_cache_usage_never_underflows_missing() {
+        // The same OID stored under two shard directories
+        let objects = vec![
+            ("aaa".to_string(), PathBuf::from("aa/aaa"), 10),
+            ("aaa".to_string(), PathBuf::from("zz/aaa"), 10),
+        ];
+        let referenced: HashSet<String> = ["aaa"].iter().map(|oid| oid.to_string()).collect();
+
+        assert_eq!(RepoCacheUsage::of(objects.into_iter(), &referenced).missing, 0);
+    }
+
+    #[test]
+    fn test_format_elapsed_picks_units() {
+        assert_eq!(format_elapsed(Duration::from_millis(4200)), "4.2s");
+        assert_eq!(format_elapsed(Duration::from_secs(133)), "2m13s");
+        assert_eq!(format_elapsed(Duration::from_secs(3900)), "1h05m");
+    }
+
+    #[test]
+    fn test_format_transfer_includes_throughput() {
+        assert_eq!(
+            format_transfer(200 * 1024 * 1024, Duration::from_secs(2)),
+            "200.00 MB in 2.0s (100.00 MB/s)"
+        );
+        assert_eq!(format_transfer(0, Duration::ZERO), "0 bytes in 0.0s (0 bytes/s)");
+    }
+}
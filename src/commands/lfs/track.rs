@@ -1,8 +1,12 @@
 //! Track/untrack files with LFS
 
+use crate::lfs::scanner::committed_as_content;
 use crate::lfs::Scanner;
+use crate::utils::{get_repo_with_root, repo_root};
+use std::path::Path;
 use clap::Args;
 use colored::Colorize;
+use super::error::report;
 
 #[derive(Args, Debug)]
 pub struct TrackArgs {
@@ -20,20 +24,14 @@ pub struct UntrackArgs {
 pub fn run(args: TrackArgs) -> i32 {
     match run_inner(args) {
         Ok(_) => 0,
-        Err(e) => {
-            eprintln!("{} {}", "Error:".red().bold(), e);
-            1
-        }
+        Err(e) => report(&*e),
     }
 }
 
 fn run_inner(args: TrackArgs) -> Result<(), Box<dyn std::error::Error>> {
-    let repo = git2::Repository::discover(".")?;
-    let repo_root = repo
-        .workdir()
-        .ok_or("Not a git repository with a working directory")?;
+    let (repo, repo_root) = get_repo_with_root()?;
 
-    let mut scanner = Scanner::new(repo_root)?;
+    let mut scanner = Scanner::new(&repo_root)?;
     scanner.add_pattern(&args.pattern)?;
 
     println!(
@@ -46,7 +44,7 @@ fn run_inner(args: TrackArgs) -> Result<(), Box<dyn std::error::Error>> {
     // Check if filter driver is registered
     let filter_check = std::process::Command::new("git")
         .args(["config", "filter.gg-lfs.clean"])
-        .current_dir(repo_root)
+        .current_dir(&repo_root)
         .output()?;
     if !filter_check.status.success() || filter_check.stdout.is_empty() {
         println!(
@@ -58,11 +56,14 @@ fn run_inner(args: TrackArgs) -> Result<(), Box<dyn std::error::Error>> {
     // Warn about already-committed files that aren't going through LFS
     let output = std::process::Command::new("git")
         .args(["ls-files", "--", &args.pattern])
-        .current_dir(repo_root)
+        .current_dir(&repo_root)
         .output()?;
     if output.status.success() {
         let stdout = String::from_utf8_lossy(&output.stdout);
-        let count = stdout.lines().filter(|l| !l.is_empty()).count();
+        let count = stdout
+            .lines()
+            .filter(|l| !l.is_empty() && committed_as_content(&repo, l))
+            .count();
         if count > 0 {
             println!(
                 "{}",
@@ -73,6 +74,16 @@ fn run_inner(args: TrackArgs) -> Result<(), Box<dyn std::error::Error>> {
                 )
                 .yellow()
             );
+            let commits = history_commit_count(&repo_root, &args.pattern);
+            println!(
+                "{}",
+                format!(
+                    "  Import only affects new commits: the full files stay in {} earlier commit(s).\n  \
+                     Rewrite history (e.g. with git filter-repo) if the repository itself must shrink.",
+                    commits
+                )
+                .yellow()
+            );
         }
     }
 
@@ -89,22 +100,28 @@ fn run_inner(args: TrackArgs) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Number of commits reachable from HEAD that touch paths matching `pattern`
+fn history_commit_count(repo_root: &Path, pattern: &str) -> usize {
+    std::process::Command::new("git")
+        .args(["rev-list", "--count", "HEAD", "--", pattern])
+        .current_dir(repo_root)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8_lossy(&output.stdout).trim().parse().ok())
+        .unwrap_or(0)
+}
+
 /// Stop tracking files matching a pattern
 pub fn run_untrack(args: UntrackArgs) -> i32 {
     match run_untrack_inner(args) {
         Ok(_) => 0,
-        Err(e) => {
-            eprintln!("{} {}", "Error:".red().bold(), e);
-            1
-        }
+        Err(e) => report(&*e),
     }
 }
 
 fn run_untrack_inner(args: UntrackArgs) -> Result<(), Box<dyn std::error::Error>> {
-    let repo = git2::Repository::discover(".")?;
-    let repo_root = repo
-        .workdir()
-        .ok_or("Not a git repository with a working directory")?;
+    let repo_root = repo_root()?;
 
     let mut scanner = Scanner::new(repo_root)?;
     let removed = scanner.remove_pattern(&args.pattern)?;
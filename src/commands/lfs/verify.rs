@@ -1,20 +1,40 @@
 //! Verify LFS storage configuration and connectivity
 
+use super::error::{report, GgError};
+use super::overrides::StorageOverrides;
+use crate::lfs::storage;
 use crate::lfs::LfsConfig;
+use crate::utils::repo_root;
+use aws_sdk_s3::primitives::{DateTime, DateTimeFormat};
+use aws_sdk_s3::types::{BucketLocationConstraint, CreateBucketConfiguration};
 use aws_sdk_s3::Client;
 use clap::Args;
 use colored::Colorize;
+use std::time::{Duration, SystemTime};
+
+/// Content written (and read back) by `--write`
+const VERIFY_TEST_CONTENT: &[u8] = b"gg-lfs-verify-test";
+
+/// How far S3 lets a request's time drift from its own clock
+const MAX_CLOCK_SKEW: Duration = Duration::from_secs(15 * 60);
 
 #[derive(Args, Debug)]
 pub struct VerifyArgs {
-    /// Test write access by uploading a small test file
+    /// Test write and read access by uploading and reading back a small test file
     #[arg(short, long)]
     pub write: bool,
+
+    /// Create the bucket in the configured region if it doesn't exist
+    #[arg(long)]
+    pub fix_bucket: bool,
+
+    #[command(flatten)]
+    pub storage: StorageOverrides,
 }
 
 /// Verify LFS configuration and S3 connectivity
 pub fn run(args: VerifyArgs) -> i32 {
-    let rt = match tokio::runtime::Runtime::new() {
+    let rt = match crate::lfs::runtime() {
         Ok(rt) => rt,
         Err(e) => {
             eprintln!("{} Failed to create async runtime: {}", "Error:".red().bold(), e);
@@ -25,44 +45,38 @@ pub fn run(args: VerifyArgs) -> i32 {
     rt.block_on(async {
         match run_inner(args).await {
             Ok(_) => 0,
-            Err(e) => {
-                eprintln!("{} {}", "Error:".red().bold(), e);
-                1
-            }
+            Err(e) => report(&*e),
         }
     })
 }
 
 async fn run_inner(args: VerifyArgs) -> Result<(), Box<dyn std::error::Error>> {
-    let repo = git2::Repository::discover(".")?;
-    let repo_root = repo
-        .workdir()
-        .ok_or("Not a git repository with a working directory")?;
+    let repo_root = repo_root()?;
 
     println!("{}", "Verifying LFS configuration...".cyan().bold());
     println!();
 
     // Step 1: Check config file exists
     print!("  {} Configuration file... ", "Checking".dimmed());
-    let config = match LfsConfig::load(repo_root) {
+    let mut config = match LfsConfig::load(repo_root) {
         Ok(c) => {
             println!("{}", "OK".green());
             c
         }
         Err(e) => {
             println!("{}", "FAILED".red());
-            return Err(format!(
+            return Err(GgError::Config(format!(
                 "Configuration not found: {}\n\nRun 'gg lfs install' to create a configuration file.",
                 e
-            ).into());
+            )).into());
         }
     };
 
-    // Step 2: Validate config values
+    // Step 2: Validate config values, with any command-line overrides
     print!("  {} Configuration values... ", "Validating".dimmed());
-    if let Err(e) = config.validate() {
+    if let Err(e) = args.storage.apply(&mut config) {
         println!("{}", "FAILED".red());
-        return Err(format!("Invalid configuration: {}", e).into());
+        return Err(GgError::Config(format!("Invalid configuration: {}", e)).into());
     }
     println!("{}", "OK".green());
 
@@ -78,26 +92,36 @@ async fn run_inner(args: VerifyArgs) -> Result<(), Box<dyn std::error::Error>> {
     if let Some(endpoint) = &config.storage.endpoint {
         println!("    Endpoint: {}", endpoint);
     }
+    if config.storage.force_path_style {
+        println!("    Addressing: path-style");
+    }
+    if !args.storage.is_empty() {
+        println!("    {}", "Overridden on the command line for this run only".dimmed());
+    }
     println!();
 
     // Step 3: Check AWS credentials
     print!("  {} AWS credentials... ", "Checking".dimmed());
-    let aws_config = build_aws_config(&config).await;
+    let s3_config = storage::s3_config(&config);
+    let aws_config = s3_config.load_aws_config().await;
     
     match aws_config.credentials_provider() {
-        Some(_) => println!("{}", "OK".green()),
+        Some(_) => {
+            println!("{}", "OK".green());
+            println!("    Source: {}", s3_config.credential_source());
+        }
         None => {
             println!("{}", "WARNING".yellow());
             println!("    {}", "No credentials found. Options:".yellow());
             println!("    {}",   "  1. Set AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY env vars".yellow());
-            println!("    {}",   "  2. Configure ~/.aws/credentials".yellow());
-            println!("    {}",   "  3. Add [storage.credentials] to .gg/lfs.toml".yellow());
+            println!("    {}",   "  2. Configure ~/.aws/credentials or set AWS_PROFILE".yellow());
+            println!("    {}",   "  3. Add [storage.credentials] to .gg/lfs.toml (keys or profile)".yellow());
         }
     }
 
     // Step 4: Check bucket exists and is accessible
     print!("  {} Bucket access... ", "Checking".dimmed());
-    let client = Client::new(&aws_config);
+    let client = s3_config.client(&aws_config);
     
     match client
         .head_bucket()
@@ -110,29 +134,45 @@ async fn run_inner(args: VerifyArgs) -> Result<(), Box<dyn std::error::Error>> {
         }
         Err(e) => {
             println!("{}", "FAILED".red());
-            let err_str = e.to_string();
-            
-            if err_str.contains("NoSuchBucket") || err_str.contains("404") {
-                return Err(format!(
-                    "Bucket '{}' does not exist.\n\nCreate the bucket in AWS console or update .gg/lfs.toml",
-                    config.storage.bucket
-                ).into());
-            } else if err_str.contains("AccessDenied") || err_str.contains("403") {
-                return Err(format!(
-                    "Access denied to bucket '{}'.\n\nCheck your AWS credentials have s3:ListBucket permission.",
-                    config.storage.bucket
-                ).into());
-            } else if err_str.contains("InvalidAccessKeyId") {
-                return Err("Invalid AWS access key ID.\n\nCheck your credentials (env vars, ~/.aws/credentials, or [storage.credentials] in .gg/lfs.toml).".into());
-            } else if err_str.contains("SignatureDoesNotMatch") {
-                return Err("Invalid AWS secret access key.\n\nCheck your credentials (env vars, ~/.aws/credentials, or [storage.credentials] in .gg/lfs.toml).".into());
-            } else if err_str.contains("timeout") || err_str.contains("Timeout") {
-                return Err(format!(
-                    "Connection timeout.\n\nCheck your network connection and region setting (current: {}).",
-                    config.storage.region
-                ).into());
+            // The full error context carries the S3 error code, not just the status
+            let mut err_str = aws_sdk_s3::error::DisplayErrorContext(&e).to_string();
+            let response = e.raw_response();
+            let skewed = response
+                .and_then(|r| r.headers().get("date"))
+                .and_then(|date| clock_offset(date, SystemTime::now()))
+                .is_some_and(|offset| offset > MAX_CLOCK_SKEW);
+
+            // HEAD responses have no body, so a 403 doesn't say why. Ask again
+            // with a request whose error names the code.
+            if response.is_some_and(|r| r.status().as_u16() == 403) {
+                if let Err(code) = list_bucket(&client, &config.storage.bucket).await {
+                    err_str = code;
+                }
+            }
+
+            if (err_str.contains("NoSuchBucket") || err_str.contains("404")) && args.fix_bucket {
+                print!("  {} Bucket '{}'... ", "Creating".dimmed(), config.storage.bucket);
+                if let Err(err_str) =
+                    create_bucket(&client, &config.storage.bucket, &config.storage.region).await
+                {
+                    println!("{}", "FAILED".red());
+                    if err_str.contains("AccessDenied") || err_str.contains("403") {
+                        return Err(GgError::Storage(format!(
+                            "Not allowed to create bucket '{}'.\n\nCheck your AWS credentials have s3:CreateBucket permission, or create it in the AWS console.",
+                            config.storage.bucket
+                        )).into());
+                    } else if err_str.contains("BucketAlreadyExists") {
+                        return Err(GgError::Storage(format!(
+                            "Bucket name '{}' is taken by another AWS account.\n\nChoose a different bucket name in .gg/lfs.toml.",
+                            config.storage.bucket
+                        )).into());
+                    } else {
+                        return Err(GgError::Storage(format!("Failed to create bucket: {}", err_str)).into());
+                    }
+                }
+                println!("{}", "OK".green());
             } else {
-                return Err(format!("Failed to access bucket: {}", err_str).into());
+                return Err(GgError::Storage(bucket_access_error(&err_str, skewed, &config)).into());
             }
         }
     }
@@ -148,36 +188,60 @@ async fn run_inner(args: VerifyArgs) -> Result<(), Box<dyn std::error::Error>> {
         let test_key = test_key.trim_start_matches('/');
         
         // Try to upload a small test object
-        match client
+        if let Err(e) = client
             .put_object()
             .bucket(&config.storage.bucket)
             .key(test_key)
-            .body(aws_sdk_s3::primitives::ByteStream::from_static(b"gg-lfs-verify-test"))
+            .body(aws_sdk_s3::primitives::ByteStream::from_static(VERIFY_TEST_CONTENT))
             .send()
             .await
         {
-            Ok(_) => {
-                // Clean up test object
-                let _ = client
-                    .delete_object()
-                    .bucket(&config.storage.bucket)
-                    .key(test_key)
-                    .send()
-                    .await;
-                
-                println!("{}", "OK".green());
+            println!("{}", "FAILED".red());
+            let err_str = e.to_string();
+
+            if err_str.contains("AccessDenied") || err_str.contains("403") {
+                return Err(GgError::Storage(format!(
+                    "Write access denied to bucket '{}'.\n\nCheck your AWS credentials have s3:PutObject permission.",
+                    config.storage.bucket
+                )).into());
+            } else {
+                return Err(GgError::Storage(format!("Failed to write to bucket: {}", err_str)).into());
             }
-            Err(e) => {
+        }
+        println!("{}", "OK".green());
+
+        // Read the test object back to catch GET-only permission or endpoint issues
+        print!("  {} Read-back... ", "Testing".dimmed());
+        let read_back = read_test_object(&client, &config.storage.bucket, test_key).await;
+
+        // Clean up test object
+        let _ = client
+            .delete_object()
+            .bucket(&config.storage.bucket)
+            .key(test_key)
+            .send()
+            .await;
+
+        match read_back {
+            Ok(body) if body == VERIFY_TEST_CONTENT => println!("{}", "OK".green()),
+            Ok(body) => {
                 println!("{}", "FAILED".red());
-                let err_str = e.to_string();
-                
+                return Err(GgError::Storage(format!(
+                    "Write succeeded but read-back returned different content ({} bytes, expected {}).\n\nCheck your endpoint and prefix settings in .gg/lfs.toml.",
+                    body.len(),
+                    VERIFY_TEST_CONTENT.len()
+                )).into());
+            }
+            Err(err_str) => {
+                println!("{}", "FAILED".red());
+
                 if err_str.contains("AccessDenied") || err_str.contains("403") {
-                    return Err(format!(
-                        "Write access denied to bucket '{}'.\n\nCheck your AWS credentials have s3:PutObject permission.",
+                    return Err(GgError::Storage(format!(
+                        "Write succeeded but read access denied to bucket '{}'.\n\nCheck your AWS credentials have s3:GetObject permission.",
                         config.storage.bucket
-                    ).into());
+                    )).into());
                 } else {
-                    return Err(format!("Failed to write to bucket: {}", err_str).into());
+                    return Err(GgError::Storage(format!("Write succeeded but failed to read back test object: {}", err_str)).into());
                 }
             }
         }
@@ -193,25 +257,177 @@ async fn run_inner(args: VerifyArgs) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-/// Build AWS config from LFS config
-async fn build_aws_config(config: &LfsConfig) -> aws_config::SdkConfig {
-    let mut builder = aws_config::from_env()
-        .region(aws_config::Region::new(config.storage.region.clone()));
+/// Explain a failed bucket check. Clock and signature problems come back as
+/// 403 too, so they are told apart before falling back to access denied.
+/// `skewed` is set when the server's Date header is far from the local clock.
+fn bucket_access_error(err_str: &str, skewed: bool, config: &LfsConfig) -> String {
+    let bucket = &config.storage.bucket;
+    let region = &config.storage.region;
 
-    if let Some(endpoint) = &config.storage.endpoint {
-        builder = builder.endpoint_url(endpoint);
+    if err_str.contains("NoSuchBucket") || err_str.contains("404") {
+        format!(
+            "Bucket '{}' does not exist.\n\nCreate the bucket in AWS console, update .gg/lfs.toml, or run 'gg lfs verify --fix-bucket'.",
+            bucket
+        )
+    } else if skewed || is_clock_skew(err_str) {
+        "The storage server rejected the request time.\n\nCheck that this machine's clock is correct (and the server's, for self-hosted S3); requests are only valid within a few minutes of the server's time.".to_string()
+    } else if err_str.contains("SignatureDoesNotMatch") {
+        let mut message = format!(
+            "Request signature was rejected.\n\nCheck your AWS secret access key (env vars, ~/.aws/credentials, or [storage.credentials] in .gg/lfs.toml), and that region '{}' matches the bucket's region.",
+            region
+        );
+        if config.storage.endpoint.is_some() {
+            message.push_str(" S3-compatible services often expect the region they were configured with, commonly 'us-east-1'.");
+        }
+        message
+    } else if err_str.contains("InvalidAccessKeyId") {
+        "Invalid AWS access key ID.\n\nCheck your credentials (env vars, ~/.aws/credentials, or [storage.credentials] in .gg/lfs.toml).".to_string()
+    } else if err_str.contains("AccessDenied") || err_str.contains("403") {
+        format!(
+            "Access denied to bucket '{}'.\n\nCheck your AWS credentials have s3:ListBucket permission.",
+            bucket
+        )
+    } else if err_str.contains("timeout") || err_str.contains("Timeout") {
+        format!(
+            "Connection timeout.\n\nCheck your network connection and region setting (current: {}).",
+            region
+        )
+    } else if err_str.contains("dispatch failure")
+        && config.storage.endpoint.is_some()
+        && !config.storage.force_path_style
+    {
+        format!(
+            "Could not reach bucket '{}' at the custom endpoint.\n\nS3-compatible services like MinIO and Ceph need path-style addressing: set force_path_style = true under [storage] in .gg/lfs.toml.",
+            bucket
+        )
+    } else {
+        format!("Failed to access bucket: {}", err_str)
     }
+}
+
+/// Whether an S3 error means the request time was too far from the server's
+fn is_clock_skew(err_str: &str) -> bool {
+    ["RequestTimeTooSkewed", "RequestExpired", "Signature expired", "Request has expired"]
+        .iter()
+        .any(|marker| err_str.contains(marker))
+}
+
+/// How far a server's HTTP `Date` header is from `now`, in either direction
+fn clock_offset(date: &str, now: SystemTime) -> Option<Duration> {
+    let server = DateTime::from_str(date, DateTimeFormat::HttpDate).ok()?;
+    let server = SystemTime::try_from(server).ok()?;
+    Some(match now.duration_since(server) {
+        Ok(behind) => behind,
+        Err(ahead) => ahead.duration(),
+    })
+}
+
+/// List at most one key, returning the full error context on failure.
+/// Unlike HeadBucket, the error response carries a body with the S3 code.
+async fn list_bucket(client: &Client, bucket: &str) -> Result<(), String> {
+    client
+        .list_objects_v2()
+        .bucket(bucket)
+        .max_keys(1)
+        .send()
+        .await
+        .map_err(|e| aws_sdk_s3::error::DisplayErrorContext(&e).to_string())?;
+    Ok(())
+}
+
+/// Download the verify test object and return its content
+async fn read_test_object(client: &Client, bucket: &str, key: &str) -> Result<Vec<u8>, String> {
+    let response = client
+        .get_object()
+        .bucket(bucket)
+        .key(key)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let body = response.body.collect().await.map_err(|e| e.to_string())?;
+    Ok(body.into_bytes().to_vec())
+}
+
+/// Create a bucket in `region`
+async fn create_bucket(client: &Client, bucket: &str, region: &str) -> Result<(), String> {
+    let mut request = client.create_bucket().bucket(bucket);
+
+    if let Some(constraint) = location_constraint(region) {
+        request = request.create_bucket_configuration(
+            CreateBucketConfiguration::builder()
+                .location_constraint(constraint)
+                .build(),
+        );
+    }
+
+    // The full error context carries the S3 error code (AccessDenied, BucketAlreadyExists...)
+    request
+        .send()
+        .await
+        .map_err(|e| aws_sdk_s3::error::DisplayErrorContext(&e).to_string())?;
+    Ok(())
+}
+
+/// S3 rejects a LocationConstraint of us-east-1, but every other region needs one
+fn location_constraint(region: &str) -> Option<BucketLocationConstraint> {
+    if region == "us-east-1" {
+        None
+    } else {
+        Some(BucketLocationConstraint::from(region))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    if let Some(creds) = &config.storage.credentials {
-        let credentials = aws_sdk_s3::config::Credentials::new(
-            &creds.access_key_id,
-            &creds.secret_access_key,
-            None,
-            None,
-            "gg-lfs-config",
+    #[test]
+    fn test_bucket_access_error_routes_clock_skew() {
+        let config = LfsConfig::template();
+        let err = "service error: unhandled error (RequestTimeTooSkewed): 403 Forbidden";
+        assert!(bucket_access_error(err, false, &config).contains("clock"));
+        assert!(bucket_access_error("403 Forbidden", false, &config).contains("Access denied"));
+
+        // A bodiless 403 is put down to the clock when the server's date is far off
+        assert!(bucket_access_error("403 Forbidden", true, &config).contains("clock"));
+    }
+
+    #[test]
+    fn test_clock_offset() {
+        let server = "Sun, 06 Nov 1994 08:49:37 GMT";
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(784111777);
+        assert_eq!(clock_offset(server, now), Some(Duration::ZERO));
+        assert_eq!(
+            clock_offset(server, now + Duration::from_secs(3600)),
+            Some(Duration::from_secs(3600))
         );
-        builder = builder.credentials_provider(credentials);
+        assert_eq!(
+            clock_offset(server, now - Duration::from_secs(60)),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(clock_offset("yesterday", now), None);
     }
 
-    builder.load().await
+    #[test]
+    fn test_bucket_access_error_hints_region_on_signature_mismatch() {
+        let mut config = LfsConfig::template();
+        let err = "service error: SignatureDoesNotMatch: 403 Forbidden";
+        let message = bucket_access_error(err, false, &config);
+        assert!(message.contains("secret access key"));
+        assert!(message.contains("region 'us-east-1'"));
+        assert!(!message.contains("S3-compatible"));
+
+        config.storage.endpoint = Some("http://minio:9000".to_string());
+        assert!(bucket_access_error(err, false, &config).contains("S3-compatible"));
+    }
+
+    #[test]
+    fn test_location_constraint() {
+        assert!(location_constraint("us-east-1").is_none());
+        assert_eq!(
+            location_constraint("eu-west-1"),
+            Some(BucketLocationConstraint::EuWest1)
+        );
+    }
 }
@@ -1,5 +1,6 @@
 pub mod amend;
 pub mod clean_branches;
+pub mod clone;
 pub mod lfs;
 pub mod pr;
 pub mod push;
@@ -14,6 +15,7 @@ pub mod undo;
 
 pub use amend::AmendArgs;
 pub use clean_branches::CleanBranchesArgs;
+pub use clone::CloneArgs;
 pub use lfs::LfsArgs;
 pub use pr::PrArgs;
 pub use push::PushArgs;
@@ -1,13 +1,28 @@
+use std::collections::HashMap;
+
 use clap::Args;
 
+use crate::config::UserConfig;
 use crate::git;
-use crate::utils::{get_branch_name, get_repo};
+use crate::utils::{get_branch_name, get_remote_default_branch, get_repo};
 
 #[derive(Args)]
 pub struct PrArgs {
     /// Just print the URL, don't open browser
     #[arg(short, long)]
     pub print: bool,
+
+    /// Branch the PR should merge into (default: origin/HEAD, if set)
+    #[arg(short, long)]
+    pub base: Option<String>,
+
+    /// Prefill the PR title (GitHub and GitLab)
+    #[arg(short, long)]
+    pub title: Option<String>,
+
+    /// Prefill the PR description (GitHub and GitLab)
+    #[arg(long)]
+    pub body: Option<String>,
 }
 
 pub fn run(args: PrArgs) -> i32 {
@@ -26,9 +41,21 @@ fn run_inner(args: PrArgs) -> Result<i32, Box<dyn std::error::Error>> {
 
     // Get the remote URL
     let remote_url = git::capture(&["remote", "get-url", "origin"])?;
+    let config = UserConfig::load().unwrap_or_default();
+
+    // Target the remote's default branch unless told otherwise
+    let base = args
+        .base
+        .or_else(|| get_remote_default_branch(&repo))
+        .filter(|base| *base != branch);
 
     // Convert to web URL for PR creation
-    let pr_url = build_pr_url(&remote_url, &branch)?;
+    let options = PrOptions {
+        base: base.as_deref(),
+        title: args.title.as_deref(),
+        body: args.body.as_deref(),
+    };
+    let pr_url = build_pr_url(&remote_url, &branch, &options, &config.pr.hosts)?;
 
     if args.print {
         println!("{}", pr_url);
@@ -40,28 +67,153 @@ fn run_inner(args: PrArgs) -> Result<i32, Box<dyn std::error::Error>> {
     open_url(&pr_url)
 }
 
-fn build_pr_url(remote_url: &str, branch: &str) -> Result<String, Box<dyn std::error::Error>> {
-    // Handle various git URL formats
-    let url = remote_url
-        .trim()
-        .trim_end_matches(".git")
-        .replace("git@github.com:", "https://github.com/")
-        .replace("git@gitlab.com:", "https://gitlab.com/")
-        .replace("git@bitbucket.org:", "https://bitbucket.org/");
-
-    // Determine the platform and construct PR URL
-    if url.contains("github.com") {
-        Ok(format!("{}/compare/{}?expand=1", url, branch))
-    } else if url.contains("gitlab.com") {
-        Ok(format!("{}/-/merge_requests/new?merge_request[source_branch]={}", url, branch))
-    } else if url.contains("bitbucket.org") {
-        Ok(format!("{}/pull-requests/new?source={}", url, branch))
+/// Hosting platform, which decides the PR URL format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Platform {
+    GitHub,
+    GitLab,
+    Bitbucket,
+}
+
+impl Platform {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "github" => Some(Self::GitHub),
+            "gitlab" => Some(Self::GitLab),
+            "bitbucket" => Some(Self::Bitbucket),
+            _ => None,
+        }
+    }
+
+    /// Detect the platform for a host: configured hosts first, then the host name itself
+    fn detect(
+        host: &str,
+        hosts: &HashMap<String, String>,
+    ) -> Result<Option<Self>, Box<dyn std::error::Error>> {
+        if let Some(name) = hosts.get(host) {
+            return Self::from_name(name)
+                .map(Some)
+                .ok_or_else(|| format!(
+                    "Unknown platform '{}' for host {} (expected github, gitlab or bitbucket)",
+                    name, host
+                ).into());
+        }
+
+        // Covers github.com and enterprise hosts like github.company.com
+        Ok(["github", "gitlab", "bitbucket"]
+            .into_iter()
+            .find(|name| host.contains(name))
+            .and_then(Self::from_name))
+    }
+}
+
+/// Convert a remote URL into (web URL, host)
+/// e.g. "git@host:user/repo.git" -> ("https://host/user/repo", "host")
+fn web_url(remote_url: &str) -> (String, String) {
+    let url = remote_url.trim().trim_end_matches(".git");
+
+    let (host, path) = if let Some((_, rest)) = url.split_once("://") {
+        // https://host/path, ssh://git@host:22/path
+        let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+        let host = authority.rsplit('@').next().unwrap_or(authority);
+        let host = host.split(':').next().unwrap_or(host);
+        (host, path)
+    } else if let Some((authority, path)) = url.split_once(':') {
+        // scp-like: git@host:path
+        (authority.rsplit('@').next().unwrap_or(authority), path)
     } else {
+        return (url.to_string(), String::new());
+    };
+
+    (format!("https://{}/{}", host, path.trim_start_matches('/')), host.to_string())
+}
+
+/// Optional fields to prefill on the PR page
+#[derive(Debug, Default)]
+struct PrOptions<'a> {
+    /// Branch to merge into
+    base: Option<&'a str>,
+    title: Option<&'a str>,
+    body: Option<&'a str>,
+}
+
+fn build_pr_url(
+    remote_url: &str,
+    branch: &str,
+    options: &PrOptions,
+    hosts: &HashMap<String, String>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let (url, host) = web_url(remote_url);
+    let base = options.base;
+
+    // Determine the platform and construct PR URL.
+    // Branches go in the path on GitHub and in query values elsewhere.
+    match Platform::detect(&host, hosts)? {
+        Some(Platform::GitHub) => {
+            let branch = encode_path(branch);
+            let mut pr_url = match base {
+                Some(base) => format!("{}/compare/{}...{}?expand=1", url, encode_path(base), branch),
+                None => format!("{}/compare/{}?expand=1", url, branch),
+            };
+            push_query(&mut pr_url, "title", options.title);
+            push_query(&mut pr_url, "body", options.body);
+            Ok(pr_url)
+        }
+        Some(Platform::GitLab) => {
+            let mut pr_url = format!(
+                "{}/-/merge_requests/new?merge_request[source_branch]={}",
+                url,
+                encode_query(branch)
+            );
+            if let Some(base) = base {
+                pr_url.push_str(&format!("&merge_request[target_branch]={}", encode_query(base)));
+            }
+            push_query(&mut pr_url, "merge_request[title]", options.title);
+            push_query(&mut pr_url, "merge_request[description]", options.body);
+            Ok(pr_url)
+        }
+        Some(Platform::Bitbucket) => {
+            let mut pr_url = format!("{}/pull-requests/new?source={}", url, encode_query(branch));
+            if let Some(base) = base {
+                pr_url.push_str(&format!("&dest={}", encode_query(base)));
+            }
+            Ok(pr_url)
+        }
         // Generic fallback - just open the repo
-        Ok(url)
+        None => Ok(url),
     }
 }
 
+/// Append "&key=value" to a URL when a value is given
+fn push_query(url: &mut String, key: &str, value: Option<&str>) {
+    if let Some(value) = value {
+        url.push_str(&format!("&{}={}", key, encode_query(value)));
+    }
+}
+
+/// Percent-encode a value for a URL path, keeping '/' separators
+fn encode_path(value: &str) -> String {
+    percent_encode(value, |b| b == b'/')
+}
+
+/// Percent-encode a value for a URL query string
+fn encode_query(value: &str) -> String {
+    percent_encode(value, |_| false)
+}
+
+/// Percent-encode everything except unreserved characters and bytes `keep` allows
+fn percent_encode(value: &str, keep: impl Fn(u8) -> bool) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') || keep(byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
 fn open_url(url: &str) -> Result<i32, Box<dyn std::error::Error>> {
     #[cfg(target_os = "macos")]
     let cmd = "open";
@@ -82,52 +234,176 @@ fn open_url(url: &str) -> Result<i32, Box<dyn std::error::Error>> {
 mod tests {
     use super::*;
 
+    fn build_pr_url_default(remote_url: &str, branch: &str) -> Result<String, Box<dyn std::error::Error>> {
+        build_pr_url(remote_url, branch, &PrOptions::default(), &HashMap::new())
+    }
+
+    fn hosts(entries: &[(&str, &str)]) -> HashMap<String, String> {
+        entries.iter().map(|(h, p)| (h.to_string(), p.to_string())).collect()
+    }
+
     #[test]
     fn test_build_pr_url_github_ssh() {
-        let url = build_pr_url("git@github.com:user/repo.git", "feature-branch").unwrap();
+        let url = build_pr_url_default("git@github.com:user/repo.git", "feature-branch").unwrap();
         assert_eq!(url, "https://github.com/user/repo/compare/feature-branch?expand=1");
     }
 
     #[test]
     fn test_build_pr_url_github_https() {
-        let url = build_pr_url("https://github.com/user/repo.git", "my-branch").unwrap();
+        let url = build_pr_url_default("https://github.com/user/repo.git", "my-branch").unwrap();
         assert_eq!(url, "https://github.com/user/repo/compare/my-branch?expand=1");
     }
 
     #[test]
     fn test_build_pr_url_github_no_git_suffix() {
-        let url = build_pr_url("https://github.com/user/repo", "branch").unwrap();
+        let url = build_pr_url_default("https://github.com/user/repo", "branch").unwrap();
         assert_eq!(url, "https://github.com/user/repo/compare/branch?expand=1");
     }
 
     #[test]
     fn test_build_pr_url_gitlab_ssh() {
-        let url = build_pr_url("git@gitlab.com:user/repo.git", "feature").unwrap();
+        let url = build_pr_url_default("git@gitlab.com:user/repo.git", "feature").unwrap();
         assert_eq!(url, "https://gitlab.com/user/repo/-/merge_requests/new?merge_request[source_branch]=feature");
     }
 
     #[test]
     fn test_build_pr_url_gitlab_https() {
-        let url = build_pr_url("https://gitlab.com/user/repo.git", "branch").unwrap();
+        let url = build_pr_url_default("https://gitlab.com/user/repo.git", "branch").unwrap();
         assert_eq!(url, "https://gitlab.com/user/repo/-/merge_requests/new?merge_request[source_branch]=branch");
     }
 
     #[test]
     fn test_build_pr_url_bitbucket_ssh() {
-        let url = build_pr_url("git@bitbucket.org:user/repo.git", "feature").unwrap();
+        let url = build_pr_url_default("git@bitbucket.org:user/repo.git", "feature").unwrap();
         assert_eq!(url, "https://bitbucket.org/user/repo/pull-requests/new?source=feature");
     }
 
     #[test]
     fn test_build_pr_url_unknown_host() {
-        let url = build_pr_url("https://git.company.com/user/repo.git", "branch").unwrap();
+        let url = build_pr_url_default("https://git.company.com/user/repo.git", "branch").unwrap();
         // Should return the base URL as fallback
         assert_eq!(url, "https://git.company.com/user/repo");
     }
 
     #[test]
     fn test_build_pr_url_trims_whitespace() {
-        let url = build_pr_url("  git@github.com:user/repo.git  \n", "branch").unwrap();
+        let url = build_pr_url_default("  git@github.com:user/repo.git  \n", "branch").unwrap();
         assert_eq!(url, "https://github.com/user/repo/compare/branch?expand=1");
     }
+
+    #[test]
+    fn test_build_pr_url_configured_github_enterprise() {
+        let hosts = hosts(&[("git.company.com", "github")]);
+        let url = build_pr_url("git@git.company.com:team/repo.git", "branch", &PrOptions::default(), &hosts).unwrap();
+        assert_eq!(url, "https://git.company.com/team/repo/compare/branch?expand=1");
+    }
+
+    #[test]
+    fn test_build_pr_url_configured_self_hosted_gitlab() {
+        let hosts = hosts(&[("code.internal", "GitLab")]);
+        let url = build_pr_url("https://code.internal/group/sub/repo.git", "feat", &PrOptions::default(), &hosts).unwrap();
+        assert_eq!(
+            url,
+            "https://code.internal/group/sub/repo/-/merge_requests/new?merge_request[source_branch]=feat"
+        );
+    }
+
+    #[test]
+    fn test_build_pr_url_configured_unknown_platform() {
+        let hosts = hosts(&[("git.company.com", "gitea")]);
+        assert!(build_pr_url("git@git.company.com:team/repo.git", "branch", &PrOptions::default(), &hosts).is_err());
+    }
+
+    #[test]
+    fn test_build_pr_url_detects_enterprise_host_name() {
+        let url = build_pr_url_default("git@github.company.com:team/repo.git", "branch").unwrap();
+        assert_eq!(url, "https://github.company.com/team/repo/compare/branch?expand=1");
+    }
+
+    #[test]
+    fn test_build_pr_url_ssh_scheme_with_port() {
+        let url = build_pr_url_default("ssh://git@gitlab.example.org:2222/team/repo.git", "b").unwrap();
+        assert_eq!(
+            url,
+            "https://gitlab.example.org/team/repo/-/merge_requests/new?merge_request[source_branch]=b"
+        );
+    }
+
+    #[test]
+    fn test_build_pr_url_github_with_base() {
+        let options = PrOptions { base: Some("develop"), ..Default::default() };
+        let url = build_pr_url("git@github.com:user/repo.git", "feature", &options, &HashMap::new()).unwrap();
+        assert_eq!(url, "https://github.com/user/repo/compare/develop...feature?expand=1");
+    }
+
+    #[test]
+    fn test_build_pr_url_gitlab_with_base() {
+        let options = PrOptions { base: Some("develop"), ..Default::default() };
+        let url = build_pr_url("git@gitlab.com:user/repo.git", "feature", &options, &HashMap::new()).unwrap();
+        assert_eq!(
+            url,
+            "https://gitlab.com/user/repo/-/merge_requests/new?merge_request[source_branch]=feature&merge_request[target_branch]=develop"
+        );
+    }
+
+    #[test]
+    fn test_build_pr_url_bitbucket_with_base() {
+        let options = PrOptions { base: Some("develop"), ..Default::default() };
+        let url = build_pr_url("git@bitbucket.org:user/repo.git", "feature", &options, &HashMap::new()).unwrap();
+        assert_eq!(url, "https://bitbucket.org/user/repo/pull-requests/new?source=feature&dest=develop");
+    }
+
+    #[test]
+    fn test_build_pr_url_github_encodes_branch_path() {
+        let url = build_pr_url_default("git@github.com:user/repo.git", "feature/JIRA-123#fix").unwrap();
+        assert_eq!(url, "https://github.com/user/repo/compare/feature/JIRA-123%23fix?expand=1");
+    }
+
+    #[test]
+    fn test_build_pr_url_gitlab_encodes_branch_query() {
+        let url = build_pr_url_default("git@gitlab.com:user/repo.git", "feature/JIRA-123#fix").unwrap();
+        assert_eq!(
+            url,
+            "https://gitlab.com/user/repo/-/merge_requests/new?merge_request[source_branch]=feature%2FJIRA-123%23fix"
+        );
+    }
+
+    #[test]
+    fn test_build_pr_url_bitbucket_encodes_branch_query() {
+        let url = build_pr_url_default("git@bitbucket.org:user/repo.git", "fix/a&b").unwrap();
+        assert_eq!(url, "https://bitbucket.org/user/repo/pull-requests/new?source=fix%2Fa%26b");
+    }
+
+    #[test]
+    fn test_percent_encode_non_ascii() {
+        assert_eq!(encode_query("café branch"), "caf%C3%A9%20branch");
+    }
+
+    #[test]
+    fn test_build_pr_url_github_title_and_body() {
+        let options = PrOptions {
+            title: Some("Fix login & logout"),
+            body: Some("Closes #12"),
+            ..Default::default()
+        };
+        let url = build_pr_url("git@github.com:user/repo.git", "fix", &options, &HashMap::new()).unwrap();
+        assert_eq!(
+            url,
+            "https://github.com/user/repo/compare/fix?expand=1&title=Fix%20login%20%26%20logout&body=Closes%20%2312"
+        );
+    }
+
+    #[test]
+    fn test_build_pr_url_gitlab_title_and_body() {
+        let options = PrOptions {
+            title: Some("Add feature"),
+            body: Some("Details"),
+            ..Default::default()
+        };
+        let url = build_pr_url("git@gitlab.com:user/repo.git", "feat", &options, &HashMap::new()).unwrap();
+        assert_eq!(
+            url,
+            "https://gitlab.com/user/repo/-/merge_requests/new?merge_request[source_branch]=feat&merge_request[title]=Add%20feature&merge_request[description]=Details"
+        );
+    }
 }
@@ -1,7 +1,7 @@
 use clap::Args;
 use colored::Colorize;
 
-use crate::git;
+use crate::{config, git};
 use crate::utils::{get_branch_name, get_repo, is_main_branch};
 
 #[derive(Args)]
@@ -46,8 +46,10 @@ fn run_inner(args: PushArgs) -> Result<i32, Box<dyn std::error::Error>> {
         }
     }
 
-    println!("Running: {}", format!("git {}", push_args.join(" ")).bold());
-    println!();
+    if !config::is_quiet() {
+        println!("Running: {}", format!("git {}", push_args.join(" ")).bold());
+        println!();
+    }
 
     Ok(git::run(&push_args))
 }
@@ -1,7 +1,7 @@
 use clap::Args;
 use colored::Colorize;
 
-use crate::git;
+use crate::{config, git};
 
 #[derive(Args)]
 pub struct QuickCommitArgs {
@@ -15,28 +15,106 @@ pub struct QuickCommitArgs {
     /// Add all changes (including untracked files)
     #[arg(short = 'A', long)]
     pub all: bool,
+
+    /// Conventional commit type (feat, fix, docs, ...) to prefix the message with
+    #[arg(short = 't', long = "type", value_name = "TYPE")]
+    pub commit_type: Option<String>,
+
+    /// Conventional commit scope, e.g. "api" for "feat(api): message"
+    #[arg(short, long, requires = "commit_type")]
+    pub scope: Option<String>,
 }
 
 pub fn run(args: QuickCommitArgs) -> i32 {
+    let message = match format_message(&args) {
+        Ok(message) => message,
+        Err(e) => {
+            eprintln!("gg: {}", e);
+            return 1;
+        }
+    };
+
     // Stage changes
     let add_args = if args.all { "-A" } else { "-u" };
-    println!("Running: {}", format!("git add {}", add_args).bold());
+    if !config::is_quiet() {
+        println!("Running: {}", format!("git add {}", add_args).bold());
+    }
     if git::run(&["add", add_args]) != 0 {
         return 1;
     }
 
     // Commit
-    println!("Running: {}", format!("git commit -m \"{}\"", args.message).bold());
-    if git::run(&["commit", "-m", &args.message]) != 0 {
+    if !config::is_quiet() {
+        println!("Running: {}", format!("git commit -m \"{}\"", message).bold());
+    }
+    if git::run(&["commit", "-m", &message]) != 0 {
         return 1;
     }
 
     // Optionally push
     if args.push {
-        println!();
-        println!("Running: {}", "git push".bold());
+        if !config::is_quiet() {
+            println!();
+            println!("Running: {}", "git push".bold());
+        }
         return git::run(&["push"]);
     }
 
     0
 }
+
+/// Build the commit message, composing a conventional commit subject
+/// ("type(scope): message") when --type is given
+fn format_message(args: &QuickCommitArgs) -> Result<String, String> {
+    let Some(commit_type) = &args.commit_type else {
+        return Ok(args.message.clone());
+    };
+
+    let message = args.message.trim();
+    if message.is_empty() {
+        return Err("A commit message is required with --type".to_string());
+    }
+
+    match args.scope.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        Some(scope) => Ok(format!("{}({}): {}", commit_type.trim(), scope, message)),
+        None => Ok(format!("{}: {}", commit_type.trim(), message)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(message: &str, commit_type: Option<&str>, scope: Option<&str>) -> QuickCommitArgs {
+        QuickCommitArgs {
+            message: message.to_string(),
+            push: false,
+            all: false,
+            commit_type: commit_type.map(String::from),
+            scope: scope.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_format_message_plain() {
+        assert_eq!(format_message(&args("fix typo", None, None)).unwrap(), "fix typo");
+    }
+
+    #[test]
+    fn test_format_message_type_only() {
+        assert_eq!(format_message(&args("fix typo", Some("docs"), None)).unwrap(), "docs: fix typo");
+    }
+
+    #[test]
+    fn test_format_message_type_and_scope() {
+        assert_eq!(
+            format_message(&args("add login", Some("feat"), Some("auth"))).unwrap(),
+            "feat(auth): add login"
+        );
+    }
+
+    #[test]
+    fn test_format_message_type_requires_message() {
+        assert!(format_message(&args("  ", Some("fix"), None)).is_err());
+    }
+}
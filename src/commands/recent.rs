@@ -2,6 +2,7 @@ use clap::Args;
 use colored::Colorize;
 
 use crate::git;
+use crate::utils::{format_relative_time, get_main_branch_name, get_repo, unix_now};
 
 #[derive(Args)]
 pub struct RecentArgs {
@@ -40,12 +41,13 @@ fn run_inner(args: RecentArgs) -> Result<i32, Box<dyn std::error::Error>> {
             if let Some(to_idx) = rest.find(" to ") {
                 let to_branch = &rest[to_idx + 4..];
                 // Skip detached HEAD states
-                if !to_branch.contains(' ') && !to_branch.starts_with("HEAD") {
-                    if seen.insert(to_branch.to_string()) {
-                        branches.push(to_branch.to_string());
-                        if branches.len() >= args.count {
-                            break;
-                        }
+                if !to_branch.contains(' ')
+                    && !to_branch.starts_with("HEAD")
+                    && seen.insert(to_branch.to_string())
+                {
+                    branches.push(to_branch.to_string());
+                    if branches.len() >= args.count {
+                        break;
                     }
                 }
             }
@@ -57,11 +59,83 @@ fn run_inner(args: RecentArgs) -> Result<i32, Box<dyn std::error::Error>> {
         return Ok(0);
     }
 
+    let repo = get_repo()?;
+    let main_branch = get_main_branch_name(&repo);
+    let now = unix_now();
+    let width = branches.iter().map(|b| b.len()).max().unwrap_or(0);
+
     println!("{}", "Recent branches:".bold());
     for (i, branch) in branches.iter().enumerate() {
         let num = format!("{:>2}", i + 1);
-        println!("  {} {}", num.dimmed(), branch.cyan());
+        let name = format!("{:<width$}", branch, width = width);
+
+        let Some(info) = branch_info(&repo, branch, &main_branch) else {
+            println!("  {} {}  {}", num.dimmed(), name.cyan(), "(deleted)".dimmed());
+            continue;
+        };
+
+        let age = format_relative_time(now - info.commit_time);
+        let mut line = format!("  {} {}  {}", num.dimmed(), name.cyan(), age.dimmed());
+        if let Some((ahead, behind)) = info.ahead_behind {
+            line.push_str(&format!("  {}", format_ahead_behind(ahead, behind)));
+        }
+        println!("{}", line);
     }
 
     Ok(0)
 }
+
+/// Last commit and divergence from the main branch for a local branch
+struct BranchInfo {
+    /// Commit time in seconds since the Unix epoch
+    commit_time: i64,
+    /// Commits (ahead, behind) the main branch; None for the main branch itself
+    ahead_behind: Option<(usize, usize)>,
+}
+
+fn branch_info(repo: &git2::Repository, branch: &str, main_branch: &str) -> Option<BranchInfo> {
+    let commit = repo
+        .find_branch(branch, git2::BranchType::Local)
+        .ok()?
+        .get()
+        .peel_to_commit()
+        .ok()?;
+
+    let ahead_behind = if branch == main_branch {
+        None
+    } else {
+        repo.find_branch(main_branch, git2::BranchType::Local)
+            .ok()
+            .and_then(|main| main.get().target())
+            .and_then(|main_oid| repo.graph_ahead_behind(commit.id(), main_oid).ok())
+    };
+
+    Some(BranchInfo {
+        commit_time: commit.time().seconds(),
+        ahead_behind,
+    })
+}
+
+/// Format ahead/behind counts, e.g. "↑2 ↓1"
+fn format_ahead_behind(ahead: usize, behind: usize) -> String {
+    match (ahead, behind) {
+        (0, 0) => "up to date".dimmed().to_string(),
+        (a, 0) => format!("↑{}", a).green().to_string(),
+        (0, b) => format!("↓{}", b).red().to_string(),
+        (a, b) => format!("{} {}", format!("↑{}", a).green(), format!("↓{}", b).red()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_ahead_behind() {
+        colored::control::set_override(false);
+        assert_eq!(format_ahead_behind(0, 0), "up to date");
+        assert_eq!(format_ahead_behind(2, 0), "↑2");
+        assert_eq!(format_ahead_behind(0, 3), "↓3");
+        assert_eq!(format_ahead_behind(2, 3), "↑2 ↓3");
+    }
+}
@@ -1,4 +1,7 @@
+use std::path::{Path, PathBuf};
+
 use clap::Args;
+use colored::Colorize;
 
 use crate::git;
 
@@ -11,43 +14,169 @@ pub struct StandupArgs {
     /// Number of days to look back (default: auto-detect last workday)
     #[arg(short, long)]
     pub days: Option<u32>,
+
+    /// Start of an explicit date range, passed to git log --since (e.g. 2024-01-01)
+    #[arg(long, conflicts_with = "days")]
+    pub since: Option<String>,
+
+    /// End of the date range, passed to git log --until
+    #[arg(long)]
+    pub until: Option<String>,
+
+    /// Report on every git repository directly inside this directory
+    #[arg(long, value_name = "DIR")]
+    pub all_repos: Option<PathBuf>,
+}
+
+/// A commit as shown in the standup report
+#[derive(Debug, PartialEq)]
+pub(crate) struct StandupCommit {
+    pub date: String,
+    pub hash: String,
+    pub subject: String,
 }
 
 pub fn run(args: StandupArgs) -> i32 {
+    match run_inner(args) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("gg: {}", e);
+            1
+        }
+    }
+}
+
+fn run_inner(args: StandupArgs) -> Result<i32, Box<dyn std::error::Error>> {
     // Calculate since date
-    let since = match args.days {
-        Some(d) => format!("{} days ago", d),
-        None => calculate_last_workday(),
+    let since = match (&args.since, args.days) {
+        (Some(since), _) => since.clone(),
+        (None, Some(d)) => format!("{} days ago", d),
+        (None, None) => calculate_last_workday(),
     };
+    let until = args.until.as_deref();
 
-    if args.all {
-        git::run(&[
-            "log",
-            "--oneline",
-            "--since",
-            &since,
-            "--date=local",
-        ])
-    } else {
-        match git::capture(&["config", "user.email"]) {
-            Ok(email) => {
-                let author_arg = format!("--author={}", email);
-                git::run(&[
-                    "log",
-                    "--oneline",
-                    "--since",
-                    &since,
-                    "--date=local",
-                    &author_arg,
-                ])
+    let Some(dir) = &args.all_repos else {
+        let commits = standup_commits(Path::new("."), &since, until, args.all)?;
+        if commits.is_empty() {
+            println!("{}", "No commits found.".dimmed());
+        } else {
+            print_grouped(&commits);
+        }
+        return Ok(0);
+    };
+
+    let repos = find_repos(dir)?;
+    let mut printed = false;
+    for repo in &repos {
+        let commits = match standup_commits(repo, &since, until, args.all) {
+            Ok(commits) => commits,
+            Err(e) => {
+                eprintln!("gg: {}: {}", repo.display(), e);
+                continue;
             }
-            Err(_) => git::run(&[
-                "log",
-                "--oneline",
-                "--since",
-                &since,
-                "--date=local",
-            ]),
+        };
+        if commits.is_empty() {
+            continue;
+        }
+
+        if printed {
+            println!();
+        }
+        let name = repo.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+        println!("{}", name.cyan().bold());
+        print_grouped(&commits);
+        printed = true;
+    }
+
+    if !printed {
+        println!("{}", "No commits found.".dimmed());
+    }
+    Ok(0)
+}
+
+/// Immediate subdirectories of `dir` that are git repositories, sorted by name
+fn find_repos(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let mut repos: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(|e| format!("Could not read {}: {}", dir.display(), e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.join(".git").exists())
+        .collect();
+    repos.sort();
+    Ok(repos)
+}
+
+/// Read commits since `since` (and before `until`, if given) from the
+/// repository at `repo`. Limited to the configured user unless `all` is set.
+pub(crate) fn standup_commits(
+    repo: &Path,
+    since: &str,
+    until: Option<&str>,
+    all: bool,
+) -> Result<Vec<StandupCommit>, Box<dyn std::error::Error>> {
+    let repo_arg = repo.to_string_lossy();
+    let since_arg = format!("--since={}", since);
+    let mut log_args = vec![
+        "-C",
+        &repo_arg,
+        "log",
+        &since_arg,
+        "--date=short-local",
+        "--pretty=format:%ad%x09%h%x09%s",
+    ];
+
+    let until_arg = until.map(|until| format!("--until={}", until));
+    if let Some(until) = &until_arg {
+        log_args.push(until);
+    }
+
+    // Fall back to showing all authors if no email is configured
+    let author_arg = if all {
+        None
+    } else {
+        git::capture(&["-C", &repo_arg, "config", "user.email"])
+            .ok()
+            .map(|email| format!("--author={}", email))
+    };
+    if let Some(author) = &author_arg {
+        log_args.push(author);
+    }
+
+    let output = git::capture(&log_args)?;
+    Ok(parse_log(&output))
+}
+
+/// Parse `date<TAB>hash<TAB>subject` lines from git log
+fn parse_log(output: &str) -> Vec<StandupCommit> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, '\t');
+            Some(StandupCommit {
+                date: parts.next()?.to_string(),
+                hash: parts.next()?.to_string(),
+                subject: parts.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Group commits by date, keeping git log order (newest first)
+fn group_by_date(commits: &[StandupCommit]) -> Vec<(&str, Vec<&StandupCommit>)> {
+    let mut groups: Vec<(&str, Vec<&StandupCommit>)> = Vec::new();
+    for commit in commits {
+        match groups.last_mut() {
+            Some((date, group)) if *date == commit.date => group.push(commit),
+            _ => groups.push((&commit.date, vec![commit])),
+        }
+    }
+    groups
+}
+
+fn print_grouped(commits: &[StandupCommit]) {
+    for (date, group) in group_by_date(commits) {
+        println!("{}", date.bold());
+        for commit in group {
+            println!("  {} {}", commit.hash.yellow(), commit.subject);
         }
     }
 }
@@ -84,11 +213,52 @@ fn days_since_last_workday() -> u64 {
 mod tests {
     use super::*;
 
+    fn commit(date: &str, hash: &str, subject: &str) -> StandupCommit {
+        StandupCommit {
+            date: date.to_string(),
+            hash: hash.to_string(),
+            subject: subject.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_log() {
+        let output = "2024-03-05\tabc1234\tFix bug\n2024-03-04\tdef5678\tAdd\ttabs";
+        assert_eq!(
+            parse_log(output),
+            vec![
+                commit("2024-03-05", "abc1234", "Fix bug"),
+                commit("2024-03-04", "def5678", "Add\ttabs"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_log_empty() {
+        assert!(parse_log("").is_empty());
+    }
+
+    #[test]
+    fn test_group_by_date() {
+        let commits = vec![
+            commit("2024-03-05", "a", "One"),
+            commit("2024-03-05", "b", "Two"),
+            commit("2024-03-04", "c", "Three"),
+        ];
+
+        let groups = group_by_date(&commits);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].0, "2024-03-05");
+        assert_eq!(groups[0].1.len(), 2);
+        assert_eq!(groups[1].0, "2024-03-04");
+        assert_eq!(groups[1].1[0].hash, "c");
+    }
+
     #[test]
     fn test_days_since_last_workday_returns_valid_range() {
         let days = days_since_last_workday();
         // Should always be 1, 2, or 3
-        assert!(days >= 1 && days <= 3, "days_back was {}", days);
+        assert!((1..=3).contains(&days), "days_back was {}", days);
     }
 
     #[test]
@@ -1,10 +1,12 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use clap::Args;
 use colored::Colorize;
 use git2::StatusOptions;
 
 use crate::config::Theme;
+use crate::lfs::scanner::committed_as_content;
+use crate::lfs::{Pointer, Scanner};
 use crate::utils::get_repo;
 
 #[derive(Args)]
@@ -30,31 +32,83 @@ pub fn run(args: StatusArgs) -> i32 {
 }
 
 fn run_inner() -> Result<(), Box<dyn std::error::Error>> {
-    let repo = get_repo()?;
-    let theme = Theme::default();
+    let mut repo = get_repo()?;
+    let theme = Theme::load();
+
+    // stash_foreach needs a mutable repo, so count before borrowing statuses
+    let mut stash_count = 0;
+    repo.stash_foreach(|_, _, _| {
+        stash_count += 1;
+        true
+    })?;
 
     let mut opts = StatusOptions::new();
     opts.include_untracked(true);
+    opts.renames_head_to_index(true);
 
     let statuses = repo.statuses(Some(&mut opts))?;
 
+    // LFS annotations are best-effort: bare repos or a broken .gitattributes
+    // just mean no tags
+    let workdir = repo.workdir().map(Path::to_path_buf);
+    let scanner = workdir.as_ref().and_then(|dir| Scanner::new(dir).ok());
+    let tag_for = |path: &str| -> String {
+        match (&scanner, &workdir) {
+            (Some(scanner), Some(dir)) => lfs_tag(scanner, dir, path),
+            _ => String::new(),
+        }
+    };
+
     // Print branch info
     let head = repo.head()?;
-    let branch_name = head.shorthand().unwrap_or("HEAD");
-    println!(
-        "On branch: {}\n",
-        branch_name.color(theme.branch).bold()
-    );
+    if repo.head_detached().unwrap_or(false) {
+        let short_id = head
+            .target()
+            .map(|oid| oid.to_string()[..7].to_string())
+            .unwrap_or_default();
+        println!("HEAD detached at {}", short_id.color(theme.branch).bold());
+    } else {
+        let branch_name = head.shorthand().unwrap_or("HEAD");
+        println!("On branch: {}", branch_name.color(theme.branch).bold());
+        if let Some(line) = upstream_status(&repo, branch_name) {
+            println!("{}", line);
+        }
+    }
+    if stash_count > 0 {
+        println!("{}", format!("Stashes: {}", stash_count).dimmed());
+    }
+    println!();
 
     let mut staged = vec![];
     let mut unstaged = vec![];
     let mut untracked = vec![];
     let mut deleted = vec![];
+    let mut renamed = vec![];
+    let mut not_imported = vec![];
 
     for entry in statuses.iter() {
         let path = entry.path().unwrap_or("").to_owned();
         let status = entry.status();
 
+        if status.is_index_renamed() {
+            // entry.path() is the old name for renames, so read both from the diff
+            if let Some(delta) = entry.head_to_index() {
+                let name = |file: git2::DiffFile| {
+                    file.path().map(|p| p.display().to_string()).unwrap_or_default()
+                };
+                renamed.push((name(delta.old_file()), name(delta.new_file())));
+            }
+        }
+
+        if let Some(scanner) = &scanner {
+            if !status.is_wt_new()
+                && scanner.is_lfs_file(Path::new(&path))
+                && committed_as_content(&repo, &path)
+            {
+                not_imported.push(path.clone());
+            }
+        }
+
         if status.is_index_new() || status.is_index_modified() || status.is_index_deleted() {
             staged.push((path.clone(), status));
         }
@@ -68,8 +122,11 @@ fn run_inner() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
-    let has_changes =
-        !staged.is_empty() || !unstaged.is_empty() || !untracked.is_empty() || !deleted.is_empty();
+    let has_changes = !staged.is_empty()
+        || !renamed.is_empty()
+        || !unstaged.is_empty()
+        || !untracked.is_empty()
+        || !deleted.is_empty();
 
     if !staged.is_empty() {
         println!("{}", "Changes to be committed:".bold().color(theme.staged));
@@ -84,7 +141,20 @@ fn run_inner() -> Result<(), Box<dyn std::error::Error>> {
             };
             println!(
                 "{}",
-                format!("  {} {}", prefix, path_buf.display()).color(theme.staged)
+                format!("  {} {}{}", prefix, path_buf.display(), tag_for(path))
+                    .color(theme.staged)
+            );
+        }
+        println!();
+    }
+
+    if !renamed.is_empty() {
+        println!("{}", "Renamed files:".bold().color(theme.renamed));
+        for (old_path, new_path) in &renamed {
+            println!(
+                "{}",
+                format!("  {} -> {}{}", old_path, new_path, tag_for(new_path))
+                    .color(theme.renamed)
             );
         }
         println!();
@@ -99,7 +169,8 @@ fn run_inner() -> Result<(), Box<dyn std::error::Error>> {
             let path_buf = PathBuf::from(path);
             println!(
                 "{}",
-                format!("  modified: {}", path_buf.display()).color(theme.modified)
+                format!("  modified: {}{}", path_buf.display(), tag_for(path))
+                    .color(theme.modified)
             );
         }
         println!();
@@ -111,7 +182,7 @@ fn run_inner() -> Result<(), Box<dyn std::error::Error>> {
             let path_buf = PathBuf::from(path);
             println!(
                 "{}",
-                format!("  {}", path_buf.display()).color(theme.untracked)
+                format!("  {}{}", path_buf.display(), tag_for(path)).color(theme.untracked)
             );
         }
         println!();
@@ -123,15 +194,90 @@ fn run_inner() -> Result<(), Box<dyn std::error::Error>> {
             let path_buf = PathBuf::from(path);
             println!(
                 "{}",
-                format!("  {}", path_buf.display()).color(theme.deleted)
+                format!("  {}{}", path_buf.display(), tag_for(path)).color(theme.deleted)
             );
         }
         println!();
     }
 
+    if !not_imported.is_empty() {
+        println!(
+            "{} LFS-tracked files committed as real content, not pointers:",
+            "Warning:".yellow()
+        );
+        for path in &not_imported {
+            println!("  {}", path);
+        }
+        println!(
+            "{}",
+            "Run 'gg lfs import' to convert them to LFS pointers.".dimmed()
+        );
+        println!();
+    }
+
     if !has_changes {
         println!("nothing to commit, working tree clean");
     }
 
     Ok(())
 }
+
+/// Tag for LFS-tracked paths: "(lfs pointer)" when the working tree holds
+/// a pointer file, "(lfs)" when it holds the real content.
+fn lfs_tag(scanner: &Scanner, workdir: &Path, path: &str) -> String {
+    if !scanner.is_lfs_file(Path::new(path)) {
+        return String::new();
+    }
+
+    if Pointer::is_pointer_file(workdir.join(path)) {
+        " (lfs pointer)".to_string()
+    } else {
+        " (lfs)".to_string()
+    }
+}
+
+/// Describe how a branch relates to its upstream, like git status does.
+/// Returns None when the branch has no upstream.
+fn upstream_status(repo: &git2::Repository, branch_name: &str) -> Option<String> {
+    let branch = repo.find_branch(branch_name, git2::BranchType::Local).ok()?;
+    let upstream = branch.upstream().ok()?;
+    let upstream_name = upstream.name().ok().flatten()?.to_string();
+
+    let local = branch.get().target()?;
+    let remote = upstream.get().target()?;
+    let (ahead, behind) = repo.graph_ahead_behind(local, remote).ok()?;
+
+    Some(describe_ahead_behind(&upstream_name, ahead, behind))
+}
+
+fn describe_ahead_behind(upstream: &str, ahead: usize, behind: usize) -> String {
+    let commits = |n: usize| if n == 1 { "1 commit".to_string() } else { format!("{} commits", n) };
+
+    match (ahead, behind) {
+        (0, 0) => format!("Up to date with '{}'.", upstream),
+        (a, 0) => format!("Ahead of '{}' by {}.", upstream, commits(a)),
+        (0, b) => format!("Behind '{}' by {}.", upstream, commits(b)),
+        (a, b) => format!(
+            "Diverged from '{}': {} ahead, {} behind.",
+            upstream,
+            commits(a),
+            commits(b)
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_ahead_behind() {
+        assert_eq!(describe_ahead_behind("origin/main", 0, 0), "Up to date with 'origin/main'.");
+        assert_eq!(describe_ahead_behind("origin/main", 1, 0), "Ahead of 'origin/main' by 1 commit.");
+        assert_eq!(describe_ahead_behind("origin/main", 0, 3), "Behind 'origin/main' by 3 commits.");
+        assert_eq!(
+            describe_ahead_behind("origin/main", 2, 1),
+            "Diverged from 'origin/main': 2 commits ahead, 1 commit behind."
+        );
+    }
+}
@@ -6,8 +6,9 @@ use crate::git;
 
 #[derive(Args)]
 pub struct SwArgs {
-    /// Branch number to switch to (from gg recent)
-    pub number: Option<usize>,
+    /// Branch number (from gg recent) or part of a recent branch name
+    #[arg(value_name = "NUMBER|PATTERN")]
+    pub target: Option<String>,
 }
 
 pub fn run(args: SwArgs) -> i32 {
@@ -28,7 +29,15 @@ fn run_inner(args: SwArgs) -> Result<i32, Box<dyn std::error::Error>> {
         return Ok(0);
     }
 
-    let selected = match args.number {
+    let number = match args.target.as_deref() {
+        Some(target) => match target.parse::<usize>() {
+            Ok(n) => Some(n),
+            Err(_) => return switch_by_pattern(&branches, target),
+        },
+        None => None,
+    };
+
+    let selected = match number {
         Some(n) if n > 0 && n <= branches.len() => n - 1,
         Some(n) => {
             eprintln!("Invalid selection: {}. Choose 1-{}", n, branches.len());
@@ -62,6 +71,44 @@ fn run_inner(args: SwArgs) -> Result<i32, Box<dyn std::error::Error>> {
     Ok(git::run(&["checkout", branch]))
 }
 
+/// Switch to the single recent branch containing `pattern`
+fn switch_by_pattern(branches: &[String], pattern: &str) -> Result<i32, Box<dyn std::error::Error>> {
+    let matches = matching_branches(branches, pattern);
+
+    match matches.as_slice() {
+        [] => {
+            eprintln!("No recent branch matches '{}'", pattern);
+            Ok(1)
+        }
+        [branch] => {
+            println!("Switching to: {}", branch.cyan());
+            Ok(git::run(&["checkout", branch]))
+        }
+        _ => {
+            eprintln!("'{}' matches multiple recent branches:", pattern);
+            for branch in &matches {
+                eprintln!("  {}", branch.cyan());
+            }
+            Ok(1)
+        }
+    }
+}
+
+/// Recent branches containing `pattern` (case-insensitive).
+/// An exact name match wins over substring matches.
+fn matching_branches<'a>(branches: &'a [String], pattern: &str) -> Vec<&'a str> {
+    if let Some(exact) = branches.iter().find(|b| *b == pattern) {
+        return vec![exact];
+    }
+
+    let pattern = pattern.to_lowercase();
+    branches
+        .iter()
+        .filter(|b| b.to_lowercase().contains(&pattern))
+        .map(String::as_str)
+        .collect()
+}
+
 fn get_recent_branches(count: usize) -> Result<Vec<String>, Box<dyn std::error::Error>> {
     let reflog = git::capture(&[
         "reflog",
@@ -78,12 +125,13 @@ fn get_recent_branches(count: usize) -> Result<Vec<String>, Box<dyn std::error::
         if let Some(rest) = line.strip_prefix("checkout: moving from ") {
             if let Some(to_idx) = rest.find(" to ") {
                 let to_branch = &rest[to_idx + 4..];
-                if !to_branch.contains(' ') && !to_branch.starts_with("HEAD") {
-                    if seen.insert(to_branch.to_string()) {
-                        branches.push(to_branch.to_string());
-                        if branches.len() >= count {
-                            break;
-                        }
+                if !to_branch.contains(' ')
+                    && !to_branch.starts_with("HEAD")
+                    && seen.insert(to_branch.to_string())
+                {
+                    branches.push(to_branch.to_string());
+                    if branches.len() >= count {
+                        break;
                     }
                 }
             }
@@ -92,3 +140,45 @@ fn get_recent_branches(count: usize) -> Result<Vec<String>, Box<dyn std::error::
 
     Ok(branches)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn branches(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_matching_branches_single() {
+        let recent = branches(&["main", "feature/login", "fix/typo"]);
+        assert_eq!(matching_branches(&recent, "login"), vec!["feature/login"]);
+    }
+
+    #[test]
+    fn test_matching_branches_ambiguous() {
+        let recent = branches(&["feature/login", "feature/signup", "main"]);
+        assert_eq!(
+            matching_branches(&recent, "feat"),
+            vec!["feature/login", "feature/signup"]
+        );
+    }
+
+    #[test]
+    fn test_matching_branches_case_insensitive() {
+        let recent = branches(&["JIRA-123-fix"]);
+        assert_eq!(matching_branches(&recent, "jira"), vec!["JIRA-123-fix"]);
+    }
+
+    #[test]
+    fn test_matching_branches_exact_wins() {
+        let recent = branches(&["main", "main-old"]);
+        assert_eq!(matching_branches(&recent, "main"), vec!["main"]);
+    }
+
+    #[test]
+    fn test_matching_branches_none() {
+        let recent = branches(&["main"]);
+        assert!(matching_branches(&recent, "nope").is_empty());
+    }
+}
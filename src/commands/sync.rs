@@ -1,14 +1,18 @@
 use clap::Args;
 use colored::Colorize;
 
-use crate::git;
-use crate::utils::{get_branch_name, get_main_branch_name, get_repo, is_main_branch};
+use crate::{config, git};
+use crate::utils::{get_branch_name, get_main_branch_name, get_repo};
 
 #[derive(Args)]
 pub struct SyncArgs {
     /// Don't stash changes before syncing
     #[arg(long)]
     pub no_stash: bool,
+
+    /// Rebase onto this ref instead of the detected upstream (e.g. upstream/main)
+    #[arg(long, value_name = "REF")]
+    pub onto: Option<String>,
 }
 
 pub fn run(args: SyncArgs) -> i32 {
@@ -23,56 +27,127 @@ pub fn run(args: SyncArgs) -> i32 {
 
 fn run_inner(args: SyncArgs) -> Result<i32, Box<dyn std::error::Error>> {
     let repo = get_repo()?;
+    if repo.head_detached().unwrap_or(false) {
+        return Err("HEAD is detached. Check out a branch before syncing.".into());
+    }
     let branch_name = get_branch_name(&repo).ok_or("Could not determine current branch")?;
+    let main_branch = get_main_branch_name(&repo);
 
-    if is_main_branch(&branch_name) {
-        sync_on_main()
-    } else {
-        sync_on_branch(&branch_name, &repo, args.no_stash)
+    if let Some(onto) = &args.onto {
+        let target = SyncTarget {
+            remote: remote_of_ref(&repo, onto),
+            rebase_ref: onto.clone(),
+        };
+        return sync_onto(&target, args.no_stash);
     }
+
+    if branch_name == main_branch {
+        return sync_on_main();
+    }
+
+    match upstream_target(&repo, &main_branch) {
+        Some(target) => sync_onto(&target, args.no_stash),
+        None => sync_on_branch(&branch_name, &main_branch, args.no_stash),
+    }
+}
+
+/// A ref to rebase onto, and the remote to fetch before rebasing
+struct SyncTarget {
+    remote: Option<String>,
+    rebase_ref: String,
+}
+
+/// Find the remote-tracking ref for the main branch: its configured upstream
+/// (`branch.<main>.remote`/`merge`, e.g. `upstream/main` on a fork), else `origin/<main>`.
+fn upstream_target(repo: &git2::Repository, main_branch: &str) -> Option<SyncTarget> {
+    let refname = format!("refs/heads/{}", main_branch);
+    if let (Ok(upstream), Ok(remote)) = (
+        repo.branch_upstream_name(&refname),
+        repo.branch_upstream_remote(&refname),
+    ) {
+        if let (Some(upstream), Some(remote)) = (upstream.as_str(), remote.as_str()) {
+            if let Some(short) = upstream.strip_prefix("refs/remotes/") {
+                return Some(SyncTarget {
+                    remote: Some(remote.to_string()),
+                    rebase_ref: short.to_string(),
+                });
+            }
+        }
+    }
+
+    let origin_ref = format!("refs/remotes/origin/{}", main_branch);
+    repo.find_reference(&origin_ref).ok().map(|_| SyncTarget {
+        remote: Some("origin".to_string()),
+        rebase_ref: format!("origin/{}", main_branch),
+    })
+}
+
+/// Get the remote name for a ref like "upstream/main", if that remote exists
+fn remote_of_ref(repo: &git2::Repository, reference: &str) -> Option<String> {
+    let (remote, _) = reference.split_once('/')?;
+    repo.find_remote(remote).ok().map(|_| remote.to_string())
 }
 
 fn sync_on_main() -> Result<i32, Box<dyn std::error::Error>> {
-    println!("Running: {}", "git pull --rebase".bold());
-    println!();
+    if !config::is_quiet() {
+        println!("Running: {}", "git pull --rebase".bold());
+        println!();
+    }
     Ok(git::run(&["pull", "--rebase"]))
 }
 
+/// Fetch the target's remote and rebase the current branch onto it
+fn sync_onto(target: &SyncTarget, no_stash: bool) -> Result<i32, Box<dyn std::error::Error>> {
+    let mut commands: Vec<Vec<&str>> = vec![];
+
+    if !no_stash {
+        commands.push(vec!["stash"]);
+    }
+    if let Some(remote) = &target.remote {
+        commands.push(vec!["fetch", remote]);
+    }
+    commands.push(vec!["rebase", &target.rebase_ref]);
+    if !no_stash {
+        commands.push(vec!["stash", "pop"]);
+    }
+
+    run_commands(&commands)
+}
+
 fn sync_on_branch(
     branch_name: &str,
-    repo: &git2::Repository,
+    main_branch: &str,
     no_stash: bool,
 ) -> Result<i32, Box<dyn std::error::Error>> {
-    let main_branch = get_main_branch_name(repo);
-
-    // Build command sequence
-    let stash_cmd: &[&str] = &["stash"];
-    let checkout_main: Vec<&str> = vec!["checkout", main_branch];
-    let pull_rebase: &[&str] = &["pull", "--rebase"];
-    let checkout_branch: Vec<&str> = vec!["checkout", branch_name];
-    let rebase_main: Vec<&str> = vec!["rebase", main_branch];
-    let stash_pop: &[&str] = &["stash", "pop"];
-
-    let mut commands: Vec<&[&str]> = vec![];
+    let mut commands: Vec<Vec<&str>> = vec![];
 
     if !no_stash {
-        commands.push(stash_cmd);
+        commands.push(vec!["stash"]);
     }
-    commands.push(&checkout_main);
-    commands.push(pull_rebase);
-    commands.push(&checkout_branch);
-    commands.push(&rebase_main);
+    commands.push(vec!["checkout", main_branch]);
+    commands.push(vec!["pull", "--rebase"]);
+    commands.push(vec!["checkout", branch_name]);
+    commands.push(vec!["rebase", main_branch]);
     if !no_stash {
-        commands.push(stash_pop);
+        commands.push(vec!["stash", "pop"]);
     }
 
-    for cmd in &commands {
-        println!("Running: {}", format!("git {}", cmd.join(" ")).bold());
+    run_commands(&commands)
+}
+
+/// Run git commands in order, stopping at the first failure
+fn run_commands(commands: &[Vec<&str>]) -> Result<i32, Box<dyn std::error::Error>> {
+    for cmd in commands {
+        if !config::is_quiet() {
+            println!("Running: {}", format!("git {}", cmd.join(" ")).bold());
+        }
         let code = git::run(cmd);
         if code != 0 {
             return Ok(code);
         }
-        println!();
+        if !config::is_quiet() {
+            println!();
+        }
     }
 
     Ok(0)
@@ -1,6 +1,9 @@
+use std::path::Path;
+
 use clap::Args;
+use colored::Colorize;
 
-use crate::git;
+use super::standup::standup_commits;
 
 #[derive(Args)]
 pub struct TodayArgs {
@@ -10,33 +13,25 @@ pub struct TodayArgs {
 }
 
 pub fn run(args: TodayArgs) -> i32 {
-    let mut log_args = vec![
-        "log",
-        "--oneline",
-        "--since=midnight",
-        "--date=local",
-    ];
+    match run_inner(args) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("gg: {}", e);
+            1
+        }
+    }
+}
+
+fn run_inner(args: TodayArgs) -> Result<i32, Box<dyn std::error::Error>> {
+    let commits = standup_commits(Path::new("."), "midnight", None, args.all)?;
 
-    if !args.all {
-        log_args.push("--author");
-        // Use the configured git user
-        log_args.push("$(git config user.email)");
+    if commits.is_empty() {
+        println!("{}", "No commits today.".dimmed());
+        return Ok(0);
     }
 
-    // Use passthrough for colors
-    if args.all {
-        git::run(&["log", "--oneline", "--since=midnight", "--date=local"])
-    } else {
-        // Get current user email first
-        match git::capture(&["config", "user.email"]) {
-            Ok(email) => {
-                let author_arg = format!("--author={}", email);
-                git::run(&["log", "--oneline", "--since=midnight", "--date=local", &author_arg])
-            }
-            Err(_) => {
-                // Fall back to showing all
-                git::run(&["log", "--oneline", "--since=midnight", "--date=local"])
-            }
-        }
+    for commit in &commits {
+        println!("{} {}", commit.hash.yellow(), commit.subject);
     }
+    Ok(0)
 }
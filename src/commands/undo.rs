@@ -1,7 +1,11 @@
 use clap::Args;
 use colored::Colorize;
 
-use crate::git;
+use crate::utils::{format_relative_time, get_repo, unix_now};
+use crate::{config, git};
+
+/// Namespace for the refs saved before each undo
+const BACKUP_PREFIX: &str = "refs/gg/undo-backup/";
 
 #[derive(Args)]
 pub struct UndoArgs {
@@ -12,11 +16,55 @@ pub struct UndoArgs {
     /// Discard changes entirely (hard reset)
     #[arg(long)]
     pub hard: bool,
+
+    /// List the backups saved by previous undos
+    #[arg(long, conflicts_with_all = ["hard", "restore"])]
+    pub list: bool,
+
+    /// Reset back to a backup (from --list)
+    #[arg(long, value_name = "BACKUP", conflicts_with = "hard")]
+    pub restore: Option<String>,
+
+    /// Undo a merge commit at HEAD, keeping uncommitted changes (git reset --merge HEAD^1)
+    #[arg(long, conflicts_with_all = ["hard", "list", "restore"])]
+    pub undo_merge: bool,
 }
 
 pub fn run(args: UndoArgs) -> i32 {
+    match run_inner(args) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("gg: {}", e);
+            1
+        }
+    }
+}
+
+fn run_inner(args: UndoArgs) -> Result<i32, Box<dyn std::error::Error>> {
+    let repo = get_repo()?;
+
+    if args.list {
+        return list_backups(&repo);
+    }
+    if let Some(backup) = &args.restore {
+        return restore_backup(&repo, backup);
+    }
+    if args.undo_merge {
+        return undo_merge(&repo);
+    }
+
     let reset_ref = format!("HEAD~{}", args.count);
 
+    for merge in merges_in_range(&repo, args.count) {
+        eprintln!(
+            "{} Undoing {} commit(s) includes merge commit {} \"{}\". Commits it brought in from the merged branch will be undone too.",
+            "Warning:".yellow(),
+            args.count,
+            merge.short_id,
+            merge.summary
+        );
+    }
+
     let reset_args = if args.hard {
         vec!["reset", "--hard", &reset_ref]
     } else {
@@ -24,6 +72,193 @@ pub fn run(args: UndoArgs) -> i32 {
         vec!["reset", "--soft", &reset_ref]
     };
 
-    println!("Running: {}", format!("git {}", reset_args.join(" ")).bold());
-    git::run(&reset_args)
+    if repo.revparse_single(&reset_ref).is_err() {
+        return Err(format!("Cannot undo {} commit(s): not enough history", args.count).into());
+    }
+    let backup = create_backup(&repo)?;
+
+    if !config::is_quiet() {
+        println!("Running: {}", format!("git {}", reset_args.join(" ")).bold());
+    }
+    let code = git::run(&reset_args);
+    if code == 0 {
+        print_restore_hint(&backup);
+    }
+    Ok(code)
+}
+
+/// A merge commit found in the range being undone
+struct MergeCommit {
+    short_id: String,
+    summary: String,
+}
+
+/// Merge commits among the last `count` commits on the first-parent chain
+fn merges_in_range(repo: &git2::Repository, count: u32) -> Vec<MergeCommit> {
+    let mut merges = Vec::new();
+    let mut commit = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+
+    for _ in 0..count {
+        let Some(current) = commit else { break };
+        if current.parent_count() > 1 {
+            merges.push(MergeCommit {
+                short_id: current.id().to_string()[..7].to_string(),
+                summary: current.summary().unwrap_or("").to_string(),
+            });
+        }
+        commit = current.parent(0).ok();
+    }
+
+    merges
+}
+
+/// Reset a merge commit at HEAD back to its first parent
+fn undo_merge(repo: &git2::Repository) -> Result<i32, Box<dyn std::error::Error>> {
+    let head = repo.head()?.peel_to_commit()?;
+    if head.parent_count() < 2 {
+        return Err("HEAD is not a merge commit".into());
+    }
+
+    let backup = create_backup(repo)?;
+
+    // --merge keeps uncommitted changes that the merge didn't touch
+    let reset_args = ["reset", "--merge", "HEAD^1"];
+    if !config::is_quiet() {
+        println!("Running: {}", format!("git {}", reset_args.join(" ")).bold());
+    }
+    let code = git::run(&reset_args);
+    if code == 0 {
+        print_restore_hint(&backup);
+        println!(
+            "If the merge was already pushed, revert it instead: {}",
+            format!("git revert -m 1 {}", &head.id().to_string()[..7]).bold()
+        );
+    }
+    Ok(code)
+}
+
+/// Save HEAD under refs/gg/undo-backup/<timestamp> and return the ref name
+fn create_backup(repo: &git2::Repository) -> Result<String, Box<dyn std::error::Error>> {
+    let head = repo
+        .head()
+        .ok()
+        .and_then(|head| head.target())
+        .ok_or("No commits to undo")?;
+
+    let timestamp = unix_now();
+    let mut name = format!("{}{}", BACKUP_PREFIX, timestamp);
+    let mut suffix = 1;
+    while repo.find_reference(&name).is_ok() {
+        name = format!("{}{}-{}", BACKUP_PREFIX, timestamp, suffix);
+        suffix += 1;
+    }
+
+    repo.reference(&name, head, false, "gg undo: backup before reset")?;
+    Ok(name)
+}
+
+fn print_restore_hint(backup: &str) {
+    println!();
+    println!(
+        "Saved previous HEAD as {}. Restore it with: {}",
+        backup.cyan(),
+        format!("gg undo --restore {}", short_backup_name(backup)).bold()
+    );
+}
+
+/// "refs/gg/undo-backup/1700000000" -> "1700000000"
+fn short_backup_name(name: &str) -> &str {
+    name.strip_prefix(BACKUP_PREFIX).unwrap_or(name)
+}
+
+/// Accept a backup as the full ref, "undo-backup/<id>" or just "<id>"
+fn resolve_backup_name(backup: &str) -> String {
+    let id = backup
+        .strip_prefix(BACKUP_PREFIX)
+        .or_else(|| backup.strip_prefix("undo-backup/"))
+        .unwrap_or(backup);
+    format!("{}{}", BACKUP_PREFIX, id)
+}
+
+fn list_backups(repo: &git2::Repository) -> Result<i32, Box<dyn std::error::Error>> {
+    let mut backups = Vec::new();
+    for reference in repo.references_glob(&format!("{}*", BACKUP_PREFIX))? {
+        let reference = reference?;
+        let (Some(name), Ok(commit)) = (reference.name(), reference.peel_to_commit()) else {
+            continue;
+        };
+        backups.push((name.to_string(), commit));
+    }
+
+    if backups.is_empty() {
+        println!("No undo backups found.");
+        return Ok(0);
+    }
+
+    // Newest first (names are timestamps)
+    backups.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+    let now = unix_now();
+    println!("{}", "Undo backups:".bold());
+    for (name, commit) in &backups {
+        let id = short_backup_name(name);
+        let saved_at = id
+            .split('-')
+            .next()
+            .and_then(|ts| ts.parse::<i64>().ok())
+            .map(|ts| format_relative_time(now - ts))
+            .unwrap_or_default();
+        let sha = commit.id().to_string();
+        println!(
+            "  {}  {} {}  {}",
+            id.cyan(),
+            sha[..7].yellow(),
+            commit.summary().unwrap_or(""),
+            saved_at.dimmed()
+        );
+    }
+
+    Ok(0)
+}
+
+fn restore_backup(repo: &git2::Repository, backup: &str) -> Result<i32, Box<dyn std::error::Error>> {
+    let name = resolve_backup_name(backup);
+    if repo.find_reference(&name).is_err() {
+        return Err(format!("No undo backup named '{}'. See 'gg undo --list'.", backup).into());
+    }
+
+    // Restoring is itself undoable
+    let current = create_backup(repo)?;
+
+    // --keep moves HEAD back but refuses to overwrite local changes
+    let reset_args = ["reset", "--keep", name.as_str()];
+    if !config::is_quiet() {
+        println!("Running: {}", format!("git {}", reset_args.join(" ")).bold());
+    }
+    let code = git::run(&reset_args);
+    if code == 0 {
+        print_restore_hint(&current);
+    }
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_backup_name() {
+        assert_eq!(resolve_backup_name("1700000000"), "refs/gg/undo-backup/1700000000");
+        assert_eq!(resolve_backup_name("undo-backup/1700000000"), "refs/gg/undo-backup/1700000000");
+        assert_eq!(
+            resolve_backup_name("refs/gg/undo-backup/1700000000-1"),
+            "refs/gg/undo-backup/1700000000-1"
+        );
+    }
+
+    #[test]
+    fn test_short_backup_name() {
+        assert_eq!(short_backup_name("refs/gg/undo-backup/1700000000"), "1700000000");
+        assert_eq!(short_backup_name("other"), "other");
+    }
 }
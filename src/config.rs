@@ -1,5 +1,12 @@
 use colored::Color;
+use serde::Deserialize;
+use std::collections::HashMap;
 use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set from the global --quiet flag
+static QUIET: AtomicBool = AtomicBool::new(false);
 
 /// Theme colors for gg output
 #[allow(dead_code)]
@@ -8,6 +15,7 @@ pub struct Theme {
     pub modified: Color,
     pub untracked: Color,
     pub deleted: Color,
+    pub renamed: Color,
     pub branch: Color,
     pub command: Color,
 }
@@ -19,12 +27,108 @@ impl Default for Theme {
             modified: Color::Yellow,
             untracked: Color::Red,
             deleted: Color::Red,
+            renamed: Color::Blue,
             branch: Color::Cyan,
             command: Color::White,
         }
     }
 }
 
+impl Theme {
+    /// The default theme with any colors from the user config applied.
+    /// A missing or malformed config file gives the defaults.
+    pub fn load() -> Self {
+        let config = UserConfig::load().unwrap_or_default();
+        Self::from_config(&config.theme)
+    }
+
+    /// Apply color overrides on top of the defaults. Unknown color names are ignored.
+    pub fn from_config(config: &ThemeConfig) -> Self {
+        let mut theme = Self::default();
+        let overrides = [
+            (&mut theme.staged, &config.staged),
+            (&mut theme.modified, &config.modified),
+            (&mut theme.untracked, &config.untracked),
+            (&mut theme.deleted, &config.deleted),
+            (&mut theme.renamed, &config.renamed),
+            (&mut theme.branch, &config.branch),
+            (&mut theme.command, &config.command),
+        ];
+        for (color, name) in overrides {
+            if let Some(parsed) = name.as_deref().and_then(|n| n.parse().ok()) {
+                *color = parsed;
+            }
+        }
+        theme
+    }
+}
+
+/// Per-user settings from ~/.config/gg/config.toml
+#[derive(Debug, Default, Deserialize)]
+pub struct UserConfig {
+    #[serde(default)]
+    pub pr: PrConfig,
+    #[serde(default)]
+    pub theme: ThemeConfig,
+}
+
+/// Color names for the `[theme]` table, e.g. `staged = "bright green"`
+#[derive(Debug, Default, Deserialize)]
+pub struct ThemeConfig {
+    pub staged: Option<String>,
+    pub modified: Option<String>,
+    pub untracked: Option<String>,
+    pub deleted: Option<String>,
+    pub renamed: Option<String>,
+    pub branch: Option<String>,
+    pub command: Option<String>,
+}
+
+/// Settings for `gg pr`
+#[derive(Debug, Default, Deserialize)]
+pub struct PrConfig {
+    /// Hostname -> platform ("github", "gitlab" or "bitbucket") for self-hosted servers
+    #[serde(default)]
+    pub hosts: HashMap<String, String>,
+}
+
+impl UserConfig {
+    /// Load the user config. A missing file gives the defaults.
+    pub fn load() -> Result<Self, String> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(content) => Self::parse(&content)
+                .map_err(|e| format!("Invalid config {}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("Could not read {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Parse user config from TOML
+    pub fn parse(content: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(content)
+    }
+
+    /// Path of the user config file
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("gg").join("config.toml"))
+    }
+}
+
+/// When to color output, from the global --color flag
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ColorChoice {
+    /// Color when stdout is a terminal and NO_COLOR is unset
+    #[default]
+    Auto,
+    /// Always color, even when piped or NO_COLOR is set
+    Always,
+    /// Never color
+    Never,
+}
+
 /// Check if colors should be enabled.
 /// Respects NO_COLOR standard (https://no-color.org/) and TTY detection.
 pub fn colors_enabled() -> bool {
@@ -37,12 +141,26 @@ pub fn colors_enabled() -> bool {
     std::io::stdout().is_terminal()
 }
 
-/// Set up color handling based on environment.
-/// Call this early in main().
-pub fn setup_colors() {
-    if !colors_enabled() {
-        colored::control::set_override(false);
-    }
+/// Set up color handling from the --color flag and environment.
+/// Call this early in main(), before any colored output.
+pub fn setup_colors(choice: ColorChoice) {
+    let enabled = match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => colors_enabled(),
+    };
+    colored::control::set_override(enabled);
+}
+
+/// Suppress informational output for the rest of the process.
+/// Errors and final summaries are still printed.
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+/// Check if informational output should be suppressed.
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
 }
 
 #[cfg(test)]
@@ -56,9 +174,68 @@ mod tests {
         assert_eq!(theme.modified, Color::Yellow);
         assert_eq!(theme.untracked, Color::Red);
         assert_eq!(theme.deleted, Color::Red);
+        assert_eq!(theme.renamed, Color::Blue);
         assert_eq!(theme.branch, Color::Cyan);
     }
 
+    #[test]
+    fn test_user_config_parse_pr_hosts() {
+        let config = UserConfig::parse(
+            r#"
+[pr.hosts]
+"git.company.com" = "github"
+"gitlab.internal" = "gitlab"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.pr.hosts.get("git.company.com").map(String::as_str), Some("github"));
+        assert_eq!(config.pr.hosts.get("gitlab.internal").map(String::as_str), Some("gitlab"));
+    }
+
+    #[test]
+    fn test_user_config_parse_empty() {
+        let config = UserConfig::parse("").unwrap();
+        assert!(config.pr.hosts.is_empty());
+    }
+
+    #[test]
+    fn test_user_config_parse_theme() {
+        let config = UserConfig::parse(
+            r#"
+[theme]
+staged = "bright green"
+deleted = "magenta"
+"#,
+        )
+        .unwrap();
+
+        let theme = Theme::from_config(&config.theme);
+        assert_eq!(theme.staged, Color::BrightGreen);
+        assert_eq!(theme.deleted, Color::Magenta);
+        assert_eq!(theme.modified, Color::Yellow);
+    }
+
+    #[test]
+    fn test_theme_ignores_unknown_colors() {
+        let config = UserConfig::parse("[theme]\nstaged = \"not-a-color\"\n").unwrap();
+        let theme = Theme::from_config(&config.theme);
+        assert_eq!(theme.staged, Color::Green);
+    }
+
+    #[test]
+    fn test_color_choice_default_is_auto() {
+        assert_eq!(ColorChoice::default(), ColorChoice::Auto);
+    }
+
+    #[test]
+    fn test_quiet_toggle() {
+        set_quiet(true);
+        assert!(is_quiet());
+        set_quiet(false);
+        assert!(!is_quiet());
+    }
+
     #[test]
     fn test_colors_enabled_respects_no_color() {
         // Note: This test may be flaky depending on environment
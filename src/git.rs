@@ -1,21 +1,119 @@
-use std::process::{Command, Stdio};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
 
 /// Pass command directly to git, preserving colors and interactivity.
 /// This is the primary way to delegate unknown commands to git.
+/// If git is killed by a signal, returns 128 + the signal number like a shell.
 pub fn passthrough(args: &[String]) -> i32 {
-    let result = Command::new("git")
+    match run_inherited(args) {
+        Ok(status) => exit_code(status),
+        Err(e) => {
+            eprintln!("gg: failed to run git: {}", e);
+            1
+        }
+    }
+}
+
+/// Like `passthrough`, for when gg is only a shim around git: if git was
+/// interrupted or terminated by a signal, gg dies from the same signal so
+/// the calling shell sees exactly what it would have seen from git.
+pub fn forward(args: &[String]) -> i32 {
+    match run_inherited(args) {
+        Ok(status) => {
+            #[cfg(unix)]
+            if let Some(signal) = std::os::unix::process::ExitStatusExt::signal(&status) {
+                signals::reraise(signal);
+            }
+            exit_code(status)
+        }
+        Err(e) => {
+            eprintln!("gg: failed to run git: {}", e);
+            1
+        }
+    }
+}
+
+/// Run git with inherited stdio. Ctrl-C and Ctrl-\ reach git through the
+/// terminal's process group, so gg ignores them while waiting and lets git
+/// decide what they mean (e.g. aborting only the editor in `rebase -i`).
+fn run_inherited(args: &[String]) -> std::io::Result<ExitStatus> {
+    // Spawn before ignoring: ignored signals would be inherited by git
+    let mut child = Command::new("git")
         .args(args)
         .stdin(Stdio::inherit())
         .stdout(Stdio::inherit())
         .stderr(Stdio::inherit())
-        .spawn()
-        .and_then(|mut child| child.wait());
+        .spawn()?;
 
-    match result {
-        Ok(status) => status.code().unwrap_or(1),
-        Err(e) => {
-            eprintln!("gg: failed to run git: {}", e);
-            1
+    #[cfg(unix)]
+    let _ignored = signals::IgnoreInterrupts::new();
+    child.wait()
+}
+
+/// Exit code for a finished process: its own code, or 128 + the signal
+/// number if a signal killed it
+fn exit_code(status: ExitStatus) -> i32 {
+    if let Some(code) = status.code() {
+        return code;
+    }
+    #[cfg(unix)]
+    if let Some(signal) = std::os::unix::process::ExitStatusExt::signal(&status) {
+        return 128 + signal;
+    }
+    1
+}
+
+#[cfg(unix)]
+mod signals {
+    use std::sync::Mutex;
+
+    /// Nesting depth and the handlers to restore when it drops to zero,
+    /// so overlapping waits on different threads don't lose the originals
+    static IGNORED: Mutex<(usize, Vec<(i32, libc::sighandler_t)>)> = Mutex::new((0, Vec::new()));
+
+    /// Ignores SIGINT and SIGQUIT until dropped
+    pub struct IgnoreInterrupts;
+
+    impl IgnoreInterrupts {
+        pub fn new() -> Self {
+            let mut ignored = IGNORED.lock().unwrap_or_else(|e| e.into_inner());
+            if ignored.0 == 0 {
+                ignored.1 = [libc::SIGINT, libc::SIGQUIT]
+                    .into_iter()
+                    // SAFETY: installs the SIG_IGN disposition, which runs no code
+                    .map(|signal| (signal, unsafe { libc::signal(signal, libc::SIG_IGN) }))
+                    .collect();
+            }
+            ignored.0 += 1;
+            Self
+        }
+    }
+
+    impl Drop for IgnoreInterrupts {
+        fn drop(&mut self) {
+            let mut ignored = IGNORED.lock().unwrap_or_else(|e| e.into_inner());
+            ignored.0 -= 1;
+            if ignored.0 == 0 {
+                for (signal, handler) in ignored.1.drain(..) {
+                    // SAFETY: restores the handler that was installed before
+                    unsafe { libc::signal(signal, handler) };
+                }
+            }
+        }
+    }
+
+    /// Die from `signal` the way git did. Only signals a shell treats as
+    /// "the user or the pipe stopped it" are re-raised; for others, such as
+    /// crashes, the caller falls back to exiting with 128 + signal.
+    pub fn reraise(signal: i32) {
+        if ![libc::SIGINT, libc::SIGTERM, libc::SIGHUP, libc::SIGPIPE].contains(&signal) {
+            return;
+        }
+        // SAFETY: resets the signal to its default action and sends it to
+        // ourselves, which terminates the process
+        unsafe {
+            libc::signal(signal, libc::SIG_DFL);
+            libc::raise(signal);
         }
     }
 }
@@ -54,6 +152,76 @@ pub fn capture(args: &[&str]) -> Result<String, String> {
     }
 }
 
+/// Run a path-listing git command (e.g. `diff-tree --name-only`) in `dir` and
+/// return the paths. Output is NUL-separated and unquoted, so names with
+/// spaces, newlines or non-UTF-8 bytes come back intact. Empty if git fails.
+pub fn list_paths(dir: &Path, args: &[&str]) -> std::io::Result<Vec<PathBuf>> {
+    let (command, rest) = args.split_first().unwrap_or((&"", &[]));
+    let output = Command::new("git")
+        .args(["-c", "core.quotepath=false", command, "-z"])
+        .args(rest)
+        .current_dir(dir)
+        .output()?;
+
+    if !output.status.success() {
+        return Ok(Vec::new());
+    }
+    Ok(split_nul_paths(&output.stdout))
+}
+
+/// Stage `paths` in `dir`, passing them NUL-separated on stdin rather than
+/// as arguments so any number of paths fits. Returns whether git succeeded.
+pub fn add_paths(dir: &Path, paths: &[PathBuf]) -> std::io::Result<bool> {
+    use std::io::Write;
+
+    let mut child = Command::new("git")
+        .args(["add", "--pathspec-from-file=-", "--pathspec-file-nul"])
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .spawn()?;
+
+    let mut input = Vec::new();
+    for path in paths {
+        input.extend_from_slice(&path_to_bytes(path));
+        input.push(0);
+    }
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(&input)?;
+    }
+    Ok(child.wait()?.success())
+}
+
+/// Split NUL-terminated path output from git
+fn split_nul_paths(bytes: &[u8]) -> Vec<PathBuf> {
+    bytes
+        .split(|&b| b == 0)
+        .filter(|path| !path.is_empty())
+        .map(path_from_bytes)
+        .collect()
+}
+
+#[cfg(unix)]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+    PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
+#[cfg(unix)]
+fn path_to_bytes(path: &Path) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    path.as_os_str().as_bytes().to_vec()
+}
+
+#[cfg(not(unix))]
+fn path_to_bytes(path: &Path) -> Vec<u8> {
+    path.to_string_lossy().into_owned().into_bytes()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -71,6 +239,42 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_split_nul_paths() {
+        let paths = split_nul_paths(b"my model.bin\0caf\xc3\xa9/line\nbreak.bin\0");
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("my model.bin"), PathBuf::from("café/line\nbreak.bin")]
+        );
+        assert!(split_nul_paths(b"").is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_split_nul_paths_keeps_non_utf8_bytes() {
+        use std::os::unix::ffi::OsStrExt;
+        let paths = split_nul_paths(b"bad\xff.bin\0");
+        assert_eq!(paths[0].as_os_str().as_bytes(), b"bad\xff.bin");
+    }
+
+    #[test]
+    fn test_add_paths_stages_every_path() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let dir = temp.path();
+        assert!(Command::new("git").args(["init", "-q"]).current_dir(dir).status().unwrap().success());
+        let paths: Vec<PathBuf> = ["my model.bin", "line\nbreak.bin", "other.bin"]
+            .iter()
+            .map(|name| {
+                std::fs::write(dir.join(name), name).unwrap();
+                dir.join(name)
+            })
+            .collect();
+
+        assert!(add_paths(dir, &paths[..2]).unwrap());
+        let staged = list_paths(dir, &["diff", "--cached", "--name-only"]).unwrap();
+        assert_eq!(staged, vec![PathBuf::from("line\nbreak.bin"), PathBuf::from("my model.bin")]);
+    }
+
     #[test]
     fn test_run_returns_zero_on_success() {
         // git --version should succeed
@@ -96,6 +300,18 @@ mod tests {
         assert_ne!(code, 0);
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_exit_code_maps_signals_like_a_shell() {
+        use std::os::unix::process::ExitStatusExt;
+
+        // Raw wait statuses: exit code in the high byte, signal in the low bits
+        assert_eq!(exit_code(ExitStatus::from_raw(0)), 0);
+        assert_eq!(exit_code(ExitStatus::from_raw(3 << 8)), 3);
+        assert_eq!(exit_code(ExitStatus::from_raw(libc::SIGINT)), 130);
+        assert_eq!(exit_code(ExitStatus::from_raw(libc::SIGKILL)), 137);
+    }
+
     #[test]
     fn test_run_sequence_all_succeed() {
         let commands: &[&[&str]] = &[
@@ -1,11 +1,16 @@
 //! Local cache for LFS objects
 //!
 //! Caches downloaded LFS objects locally to avoid re-downloading.
-//! Location: ~/.cache/gg-lfs/<sha256-prefix>/<sha256>
+//! Location: ~/.cache/gg-lfs/<sha256-prefix>/<sha256>, overridable with
+//! `GG_LFS_CACHE_DIR` or `[cache] dir` in .gg/lfs.toml.
 
+use super::config::LinkMode;
+use super::{LfsConfig, Pointer};
 use std::fs::{self, File};
 use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -21,6 +26,18 @@ pub enum CacheError {
     NotFound(String),
 }
 
+/// Environment variable that overrides the cache directory
+pub const CACHE_DIR_ENV: &str = "GG_LFS_CACHE_DIR";
+
+/// How `link_to` actually placed an object
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placed {
+    /// Hardlinked or reflinked as requested
+    Linked,
+    /// Copied, either as requested or because linking wasn't possible
+    Copied,
+}
+
 /// Local cache for LFS objects
 #[derive(Debug)]
 pub struct Cache {
@@ -30,14 +47,29 @@ pub struct Cache {
 
 #[allow(dead_code)]
 impl Cache {
-    /// Create a new cache with default location (~/.cache/gg-lfs)
+    /// Create a new cache at the configured location: `GG_LFS_CACHE_DIR`, then
+    /// `[cache] dir` in the current repository's config, then ~/.cache/gg-lfs
     pub fn new() -> Result<Self, CacheError> {
-        let cache_dir = dirs::cache_dir().ok_or(CacheError::NoCacheDir)?;
-        let root = cache_dir.join("gg-lfs");
+        Self::with_root(Self::resolve_root()?)
+    }
 
-        fs::create_dir_all(&root)?;
+    fn resolve_root() -> Result<PathBuf, CacheError> {
+        if let Some(dir) = std::env::var_os(CACHE_DIR_ENV).filter(|dir| !dir.is_empty()) {
+            return Ok(PathBuf::from(dir));
+        }
+        if let Some(dir) = Self::configured_root() {
+            return Ok(dir);
+        }
+        let cache_dir = dirs::cache_dir().ok_or(CacheError::NoCacheDir)?;
+        Ok(cache_dir.join("gg-lfs"))
+    }
 
-        Ok(Self { root })
+    /// `[cache] dir` from the current repository, relative paths resolved
+    /// against the repository root
+    fn configured_root() -> Option<PathBuf> {
+        let repo_root = crate::utils::repo_root().ok()?;
+        let dir = LfsConfig::load(&repo_root).ok()?.cache?.dir?;
+        Some(repo_root.join(dir))
     }
 
     /// Create a cache at a specific location
@@ -47,6 +79,11 @@ impl Cache {
         Ok(Self { root })
     }
 
+    /// Root directory objects are stored under
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
     /// Get the temporary directory for in-progress operations
     pub fn temp_dir(&self) -> PathBuf {
         self.root.join("tmp")
@@ -74,6 +111,19 @@ impl Cache {
         }
     }
 
+    /// Like `get`, but re-hashes the object first. A corrupt entry is removed
+    /// and reported as missing so the caller fetches it again.
+    pub fn get_verified(&self, oid: &str) -> Option<PathBuf> {
+        let path = self.get(oid)?;
+        match Pointer::from_file(&path) {
+            Ok(pointer) if pointer.sha256() == oid => Some(path),
+            _ => {
+                let _ = fs::remove_file(&path);
+                None
+            }
+        }
+    }
+
     /// Store content in the cache
     pub fn put(&self, oid: &str, content: &[u8]) -> Result<PathBuf, CacheError> {
         let path = self.object_path(oid);
@@ -83,6 +133,8 @@ impl Cache {
             fs::create_dir_all(parent)?;
         }
 
+        // Replace rather than overwrite: the entry may be hardlinked into a working tree
+        let _ = fs::remove_file(&path);
         let mut file = File::create(&path)?;
         file.write_all(content)?;
         file.flush()?;
@@ -99,10 +151,29 @@ impl Cache {
             fs::create_dir_all(parent)?;
         }
 
+        // Replace rather than overwrite: the entry may be hardlinked into a working tree
+        let _ = fs::remove_file(&path);
         fs::copy(source, &path)?;
         Ok(path)
     }
 
+    /// Move a file into the cache, e.g. one written under `temp_dir()`.
+    /// Falls back to copying when the file is on another filesystem.
+    pub fn adopt_file<P: AsRef<Path>>(&self, oid: &str, source: P) -> Result<PathBuf, CacheError> {
+        let path = self.object_path(oid);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // Renaming over the entry replaces it, leaving any hardlinks to it intact
+        if fs::rename(source.as_ref(), &path).is_err() {
+            self.put_file(oid, source.as_ref())?;
+            fs::remove_file(source)?;
+        }
+        Ok(path)
+    }
+
     /// Read content from the cache
     pub fn read(&self, oid: &str) -> Result<Vec<u8>, CacheError> {
         let path = self.object_path(oid);
@@ -130,6 +201,42 @@ impl Cache {
         Ok(bytes)
     }
 
+    /// Place a cached object at `dest`, replacing any existing file. Hardlink
+    /// and reflink fall back to copying when the filesystem can't link, e.g.
+    /// when the cache and working tree are on different devices.
+    pub fn link_to<P: AsRef<Path>>(&self, oid: &str, dest: P, mode: LinkMode) -> Result<Placed, CacheError> {
+        let path = self.object_path(oid);
+        let dest = dest.as_ref();
+
+        if !path.exists() {
+            return Err(CacheError::NotFound(oid.to_string()));
+        }
+
+        if mode != LinkMode::Copy {
+            // Link next to the destination, then rename over it atomically
+            let file_name = dest.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+            let staging = dest.with_file_name(format!(".{}.gg-link-{}", file_name, std::process::id()));
+            let _ = fs::remove_file(&staging);
+
+            let linked = match mode {
+                LinkMode::Hardlink => fs::hard_link(&path, &staging).is_ok(),
+                LinkMode::Reflink => reflink(&path, &staging),
+                LinkMode::Copy => false,
+            };
+            if linked {
+                if let Err(e) = fs::rename(&staging, dest) {
+                    let _ = fs::remove_file(&staging);
+                    return Err(e.into());
+                }
+                return Ok(Placed::Linked);
+            }
+            let _ = fs::remove_file(&staging);
+        }
+
+        fs::copy(&path, dest)?;
+        Ok(Placed::Copied)
+    }
+
     /// Remove an object from the cache
     pub fn remove(&self, oid: &str) -> Result<bool, CacheError> {
         let path = self.object_path(oid);
@@ -184,50 +291,66 @@ impl Cache {
         Ok(count)
     }
 
-    /// Clear the entire cache
-    pub fn clear(&self) -> Result<usize, CacheError> {
-        let count = self.count()?;
+    /// Every cached object as (oid, path, size), walking the shard
+    /// directories lazily. Temporary files and unreadable entries are skipped.
+    pub fn iter_objects(&self) -> impl Iterator<Item = (String, PathBuf, u64)> {
+        let temp_dir = self.temp_dir();
+
+        fs::read_dir(&self.root)
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(move |path| path.is_dir() && *path != temp_dir)
+            .flat_map(|shard| fs::read_dir(shard).into_iter().flatten().filter_map(Result::ok))
+            .filter_map(|entry| {
+                let metadata = entry.metadata().ok().filter(|metadata| metadata.is_file())?;
+                let oid = entry.file_name().to_string_lossy().to_string();
+                Some((oid, entry.path(), metadata.len()))
+            })
+    }
+
+    /// OIDs of objects not accessed in the given number of days
+    pub fn stale_objects(&self, days: u32) -> Result<Vec<String>, CacheError> {
+        let cutoff = prune_cutoff(days);
+        let temp_dir = self.temp_dir();
+        let mut stale = Vec::new();
 
         for entry in fs::read_dir(&self.root)? {
             let entry = entry?;
             let path = entry.path();
 
-            if path.is_dir() {
-                fs::remove_dir_all(&path)?;
-            } else if path.is_file() {
-                fs::remove_file(&path)?;
+            if path.is_dir() && path != temp_dir {
+                for file_entry in fs::read_dir(&path)? {
+                    let file_entry = file_entry?;
+                    if file_entry.path().is_file() && accessed_before(&file_entry, cutoff) {
+                        stale.push(file_entry.file_name().to_string_lossy().to_string());
+                    }
+                }
             }
         }
 
-        Ok(count)
+        Ok(stale)
     }
 
-    /// Prune objects not accessed in the given number of days
+    /// Prune objects not accessed in the given number of days, along with
+    /// leftover temporary files
     pub fn prune(&self, days: u32) -> Result<usize, CacheError> {
-        use std::time::{Duration, SystemTime};
-
-        let cutoff = SystemTime::now() - Duration::from_secs(days as u64 * 24 * 60 * 60);
         let mut pruned = 0;
 
-        for entry in fs::read_dir(&self.root)? {
-            let entry = entry?;
-            let path = entry.path();
+        for oid in self.stale_objects(days)? {
+            if self.remove(&oid)? {
+                pruned += 1;
+            }
+        }
 
-            if path.is_dir() {
-                for file_entry in fs::read_dir(&path)? {
-                    let file_entry = file_entry?;
-                    let file_path = file_entry.path();
-
-                    if file_path.is_file() {
-                        if let Ok(metadata) = file_entry.metadata() {
-                            if let Ok(accessed) = metadata.accessed() {
-                                if accessed < cutoff {
-                                    fs::remove_file(&file_path)?;
-                                    pruned += 1;
-                                }
-                            }
-                        }
-                    }
+        let cutoff = prune_cutoff(days);
+        if let Ok(entries) = fs::read_dir(self.temp_dir()) {
+            for entry in entries {
+                let entry = entry?;
+                if entry.path().is_file() && accessed_before(&entry, cutoff) {
+                    fs::remove_file(entry.path())?;
+                    pruned += 1;
                 }
             }
         }
@@ -236,6 +359,34 @@ impl Cache {
     }
 }
 
+/// The access time before which objects count as stale
+fn prune_cutoff(days: u32) -> SystemTime {
+    SystemTime::now() - Duration::from_secs(days as u64 * 24 * 60 * 60)
+}
+
+/// Whether a cache entry was last accessed before `cutoff`. Entries whose
+/// access time can't be read are kept.
+fn accessed_before(entry: &fs::DirEntry, cutoff: SystemTime) -> bool {
+    entry
+        .metadata()
+        .and_then(|metadata| metadata.accessed())
+        .is_ok_and(|accessed| accessed < cutoff)
+}
+
+/// Clone `source` to `dest` sharing extents, via the system `cp`.
+/// Returns false where copy-on-write clones aren't supported.
+fn reflink(source: &Path, dest: &Path) -> bool {
+    let flag = if cfg!(target_os = "macos") { "-c" } else { "--reflink=always" };
+    Command::new("cp")
+        .arg(flag)
+        .arg(source)
+        .arg(dest)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
 impl Default for Cache {
     fn default() -> Self {
         Self::new().expect("Failed to create default cache")
@@ -247,6 +398,21 @@ mod tests {
     use super::*;
     use tempfile::TempDir;
 
+    #[test]
+    fn test_cache_get_verified_drops_corrupt_entry() {
+        let temp = TempDir::new().unwrap();
+        let cache = Cache::with_root(temp.path()).unwrap();
+
+        // sha256("hello")
+        let oid = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        cache.put(oid, b"hello").unwrap();
+        assert!(cache.get_verified(oid).is_some());
+
+        cache.put(oid, b"jello").unwrap();
+        assert!(cache.get_verified(oid).is_none());
+        assert!(!cache.contains(oid));
+    }
+
     #[test]
     fn test_cache_put_and_get() {
         let temp = TempDir::new().unwrap();
@@ -295,6 +461,26 @@ mod tests {
         assert_eq!(cache.size().unwrap(), 11); // 5 + 6 bytes
     }
 
+    #[test]
+    fn test_cache_iter_objects() {
+        let temp = TempDir::new().unwrap();
+        let cache = Cache::with_root(temp.path()).unwrap();
+
+        cache.put("aa11", b"one").unwrap();
+        cache.put("aa22", b"two!").unwrap();
+        cache.put("bb33", b"three").unwrap();
+        fs::create_dir_all(cache.temp_dir()).unwrap();
+        fs::write(cache.temp_dir().join("partial"), b"tmp").unwrap();
+
+        let mut objects: Vec<_> = cache.iter_objects().collect();
+        objects.sort();
+
+        let oids: Vec<_> = objects.iter().map(|(oid, _, _)| oid.as_str()).collect();
+        assert_eq!(oids, ["aa11", "aa22", "bb33"]);
+        assert_eq!(objects[2].1, cache.get("bb33").unwrap());
+        assert_eq!(objects[2].2, 5);
+    }
+
     #[test]
     fn test_cache_put_file() {
         let temp = TempDir::new().unwrap();
@@ -312,6 +498,20 @@ mod tests {
         assert_eq!(cache.read(oid).unwrap(), b"file content here");
     }
 
+    #[test]
+    fn test_cache_adopt_file_moves_into_place() {
+        let temp = TempDir::new().unwrap();
+        let cache = Cache::with_root(temp.path().join("cache")).unwrap();
+        fs::create_dir_all(cache.temp_dir()).unwrap();
+        let source = cache.temp_dir().join("download");
+        fs::write(&source, b"adopted").unwrap();
+
+        let path = cache.adopt_file("abc123", &source).unwrap();
+        assert!(!source.exists());
+        assert_eq!(fs::read(path).unwrap(), b"adopted");
+        assert!(cache.contains("abc123"));
+    }
+
     #[test]
     fn test_cache_copy_to() {
         let temp = TempDir::new().unwrap();
@@ -328,38 +528,53 @@ mod tests {
     }
 
     #[test]
-    fn test_cache_copy_to_not_found() {
+    fn test_cache_link_to_hardlink_replaces_destination() {
         let temp = TempDir::new().unwrap();
-        let cache = Cache::with_root(temp.path()).unwrap();
+        let cache = Cache::with_root(temp.path().join("cache")).unwrap();
+        let oid = "abc123";
+        cache.put(oid, b"cached data").unwrap();
 
-        let dest = temp.path().join("out.bin");
-        let result = cache.copy_to("nonexistent", &dest);
-        assert!(result.is_err());
+        let dest = temp.path().join("restored.bin");
+        fs::write(&dest, "pointer text").unwrap();
+        assert_eq!(cache.link_to(oid, &dest, LinkMode::Hardlink).unwrap(), Placed::Linked);
+        assert_eq!(fs::read(&dest).unwrap(), b"cached data");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            let linked = fs::metadata(&dest).unwrap();
+            assert_eq!(linked.ino(), fs::metadata(cache.get(oid).unwrap()).unwrap().ino());
+        }
+
+        // Re-caching replaces the entry instead of writing through the link
+        cache.put(oid, b"new data").unwrap();
+        assert_eq!(fs::read(&dest).unwrap(), b"cached data");
     }
 
     #[test]
-    fn test_cache_clear() {
+    fn test_cache_link_to_reflink_always_yields_content() {
         let temp = TempDir::new().unwrap();
-        let cache = Cache::with_root(temp.path()).unwrap();
-
-        cache.put("oid1", b"one").unwrap();
-        cache.put("oid2", b"two").unwrap();
-        cache.put("oid3", b"three").unwrap();
+        let cache = Cache::with_root(temp.path().join("cache")).unwrap();
+        let oid = "abc123";
+        cache.put(oid, b"cached data").unwrap();
 
-        assert_eq!(cache.count().unwrap(), 3);
+        // Linked on btrfs/XFS/APFS, copied elsewhere
+        let dest = temp.path().join("restored.bin");
+        cache.link_to(oid, &dest, LinkMode::Reflink).unwrap();
+        assert_eq!(fs::read(&dest).unwrap(), b"cached data");
+        assert_eq!(fs::read_dir(temp.path()).unwrap().count(), 2, "staging file left behind");
 
-        let cleared = cache.clear().unwrap();
-        assert_eq!(cleared, 3);
-        assert_eq!(cache.count().unwrap(), 0);
+        assert_eq!(cache.link_to(oid, &dest, LinkMode::Copy).unwrap(), Placed::Copied);
     }
 
     #[test]
-    fn test_cache_clear_empty() {
+    fn test_cache_copy_to_not_found() {
         let temp = TempDir::new().unwrap();
         let cache = Cache::with_root(temp.path()).unwrap();
 
-        let cleared = cache.clear().unwrap();
-        assert_eq!(cleared, 0);
+        let dest = temp.path().join("out.bin");
+        let result = cache.copy_to("nonexistent", &dest);
+        assert!(result.is_err());
     }
 
     #[test]
@@ -375,6 +590,25 @@ mod tests {
         assert!(cache.contains("oid1"));
     }
 
+    #[test]
+    fn test_cache_stale_objects() {
+        let temp = TempDir::new().unwrap();
+        let cache = Cache::with_root(temp.path()).unwrap();
+
+        let old = cache.put("aa11", b"old").unwrap();
+        cache.put("bb22", b"recent").unwrap();
+        let day_ago = SystemTime::now() - Duration::from_secs(2 * 24 * 60 * 60);
+        fs::File::options()
+            .write(true)
+            .open(&old)
+            .unwrap()
+            .set_times(fs::FileTimes::new().set_accessed(day_ago))
+            .unwrap();
+
+        assert_eq!(cache.stale_objects(1).unwrap(), vec!["aa11".to_string()]);
+        assert!(cache.stale_objects(30).unwrap().is_empty());
+    }
+
     #[test]
     fn test_cache_read_not_found() {
         let temp = TempDir::new().unwrap();
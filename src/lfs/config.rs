@@ -1,10 +1,16 @@
 //! LFS configuration
 //!
-//! Configuration is stored in .gg/lfs.toml in the repository root.
+//! Configuration is stored in .gg/lfs.toml in the repository root, layered
+//! over optional user-level defaults in ~/.config/gg/lfs.toml. Repo-local
+//! values win field by field; `[storage.credentials]` is replaced as a whole.
+//! A git-lfs `.lfsconfig` is read only to detect git-lfs repositories and
+//! to pick up `lfs.concurrenttransfers`.
 
+use crate::lfs::pointer::LFS_VERSION;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -22,6 +28,9 @@ pub enum ConfigError {
     #[error("Configuration not found at {0}")]
     NotFound(PathBuf),
 
+    #[error("Configuration not found at {0}, but .lfsconfig shows this repository uses git-lfs. Run 'gg lfs install', then 'gg lfs migrate' to move its objects to gg lfs")]
+    GitLfsRepo(PathBuf),
+
     #[error("Invalid configuration: {0}")]
     Invalid(String),
 
@@ -30,24 +39,41 @@ pub enum ConfigError {
 }
 
 /// Storage provider type
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum StorageProvider {
+    #[default]
     S3,
     // Future: Gcs, Azure, etc.
 }
 
-impl Default for StorageProvider {
-    fn default() -> Self {
-        Self::S3
+/// How object keys are laid out in the bucket
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeyLayout {
+    /// `{prefix}/{oid[..2]}/{oid}`
+    #[default]
+    Gg,
+    /// `{prefix}/objects/{oid[..2]}/{oid[2..4]}/{oid}`, as used by git-lfs servers
+    GitLfs,
+}
+
+impl KeyLayout {
+    fn is_default(&self) -> bool {
+        *self == KeyLayout::Gg
     }
 }
 
-/// Inline credential configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Credential configuration: inline keys, or a named AWS profile
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CredentialsConfig {
-    pub access_key_id: String,
-    pub secret_access_key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_key_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_access_key: Option<String>,
+    /// Profile from ~/.aws/config or ~/.aws/credentials, including SSO profiles
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
 }
 
 /// Storage configuration
@@ -68,38 +94,166 @@ pub struct StorageConfig {
     #[serde(default)]
     pub prefix: Option<String>,
 
+    /// Object key scheme under the prefix; "git-lfs" shares a bucket with a git-lfs server
+    #[serde(default, skip_serializing_if = "KeyLayout::is_default")]
+    pub key_layout: KeyLayout,
+
     /// Optional custom endpoint (for S3-compatible services like MinIO)
     #[serde(default)]
     pub endpoint: Option<String>,
 
-    /// Optional inline credentials (alternative to env vars / ~/.aws/credentials)
+    /// Use path-style addressing (`endpoint/bucket/key`), needed by MinIO and Ceph
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub force_path_style: bool,
+
+    /// Send and check no SHA-256 checksums, for S3-compatible services that reject them
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub disable_checksums: bool,
+
+    /// Endpoint of a read-only mirror for downloads and existence checks (default: `endpoint`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_endpoint: Option<String>,
+
+    /// Bucket of a read-only mirror for downloads and existence checks (default: `bucket`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_bucket: Option<String>,
+
+    /// Cache-Control header set on uploaded objects, e.g. "public, max-age=31536000"
+    #[serde(default)]
+    pub cache_control: Option<String>,
+
+    /// Seconds to wait for a connection to S3 (default: 10)
+    #[serde(default)]
+    pub connect_timeout_secs: Option<u64>,
+
+    /// Seconds a single S3 request may take, including retries (default: 300)
+    #[serde(default)]
+    pub operation_timeout_secs: Option<u64>,
+
+    /// Seconds checkout waits for one object before leaving its pointer (default: 120)
+    #[serde(default)]
+    pub smudge_timeout_secs: Option<u64>,
+
+    /// Optional inline credentials or AWS profile (alternative to env vars / ~/.aws/credentials)
     #[serde(default)]
     pub credentials: Option<CredentialsConfig>,
 }
 
+/// Concurrent transfers when `.lfsconfig` doesn't set `lfs.concurrenttransfers` (git-lfs's default)
+pub const DEFAULT_TRANSFER_JOBS: usize = 8;
+
+/// Connect timeout used when `connect_timeout_secs` is not set
+pub const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+
+/// Operation timeout used when `operation_timeout_secs` is not set
+pub const DEFAULT_OPERATION_TIMEOUT_SECS: u64 = 300;
+
+/// Smudge download timeout used when neither `GG_LFS_SMUDGE_TIMEOUT` nor
+/// `smudge_timeout_secs` is set
+pub const DEFAULT_SMUDGE_TIMEOUT_SECS: u64 = 120;
+
+/// Environment variable overriding `smudge_timeout_secs`
+pub const SMUDGE_TIMEOUT_ENV: &str = "GG_LFS_SMUDGE_TIMEOUT";
+
 fn default_region() -> String {
     "us-east-1".to_string()
 }
 
+/// How `pull` places cached objects in the working tree
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkMode {
+    /// Independent copy of the cached object
+    #[default]
+    Copy,
+    /// Hardlink to the cached object: instant, but shares its storage
+    Hardlink,
+    /// Copy-on-write clone (btrfs, XFS, APFS)
+    Reflink,
+}
+
+impl LinkMode {
+    fn is_copy(&self) -> bool {
+        *self == LinkMode::Copy
+    }
+}
+
+/// Local object cache configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheConfig {
+    /// Cache directory, relative to the repository root if not absolute.
+    /// `GG_LFS_CACHE_DIR` takes precedence.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<PathBuf>,
+
+    /// How `pull` restores files from the cache; falls back to copying
+    /// when linking isn't possible (e.g. the cache is on another filesystem)
+    #[serde(default, skip_serializing_if = "LinkMode::is_copy")]
+    pub link_mode: LinkMode,
+}
+
+/// Settings for how files become pointers
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FilterConfig {
+    /// `version` URL written into new pointers, for forks of the LFS spec.
+    /// Pointers with any version are still read.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_url: Option<String>,
+
+    /// Globs for paths that stay out of LFS even when a tracked pattern
+    /// matches them; matched like `.gitattributes` patterns
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+}
+
 /// Main LFS configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LfsConfig {
     /// Storage configuration
     pub storage: StorageConfig,
+
+    /// Optional local cache settings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache: Option<CacheConfig>,
+
+    /// Optional pointer and filter settings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lfs: Option<FilterConfig>,
 }
 
 #[allow(dead_code)]
 impl LfsConfig {
-    /// Find and load configuration from repository
+    /// Find and load configuration from repository, over the user defaults
     pub fn load<P: AsRef<Path>>(repo_root: P) -> Result<Self, ConfigError> {
+        Self::load_with_defaults(repo_root, Self::user_config_path().as_deref())
+    }
+
+    /// Load the repository config layered over the config at `user_path`.
+    /// Either file may be missing, but not both.
+    pub fn load_with_defaults<P: AsRef<Path>>(
+        repo_root: P,
+        user_path: Option<&Path>,
+    ) -> Result<Self, ConfigError> {
         let config_path = Self::config_path(repo_root.as_ref());
+        let layers: Vec<&Path> = user_path
+            .into_iter()
+            .chain([config_path.as_path()])
+            .filter(|path| path.exists())
+            .collect();
 
-        if !config_path.exists() {
+        if layers.is_empty() {
+            if GitLfsConfig::load(repo_root.as_ref()).is_some() {
+                return Err(ConfigError::GitLfsRepo(config_path));
+            }
             return Err(ConfigError::NotFound(config_path));
         }
 
-        let content = fs::read_to_string(&config_path)?;
-        let config: LfsConfig = toml::from_str(&content)?;
+        let mut merged = toml::Table::new();
+        for path in layers {
+            let content = fs::read_to_string(path)?;
+            merge_tables(&mut merged, toml::from_str(&content)?);
+        }
+        let config: LfsConfig = toml::Value::Table(merged).try_into()?;
 
         config.validate()?;
         Ok(config)
@@ -122,9 +276,53 @@ impl LfsConfig {
         repo_root.join(".gg").join("lfs.toml")
     }
 
-    /// Check if configuration exists
+    /// Path of the user-level defaults shared by all repositories
+    pub fn user_config_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("gg").join("lfs.toml"))
+    }
+
+    /// What to run when a repository has no configuration yet
+    pub fn setup_hint(repo_root: &Path) -> &'static str {
+        if GitLfsConfig::load(repo_root).is_some() {
+            "This repository uses git-lfs (.lfsconfig). Run 'gg lfs install', then 'gg lfs migrate'."
+        } else {
+            "Run 'gg lfs install' to set up."
+        }
+    }
+
+    /// Default number of concurrent transfers: `lfs.concurrenttransfers`
+    /// from `.lfsconfig`, else `DEFAULT_TRANSFER_JOBS`
+    pub fn transfer_jobs(repo_root: &Path) -> usize {
+        GitLfsConfig::load(repo_root)
+            .and_then(|config| config.concurrent_transfers)
+            .filter(|&jobs| jobs > 0)
+            .unwrap_or(DEFAULT_TRANSFER_JOBS)
+    }
+
+    /// How long the smudge filter waits for a download: `GG_LFS_SMUDGE_TIMEOUT`,
+    /// then `smudge_timeout_secs`, then the default. Zero or invalid values are ignored.
+    pub fn smudge_timeout(&self) -> Duration {
+        let env = std::env::var(SMUDGE_TIMEOUT_ENV).ok();
+        resolve_smudge_timeout(env.as_deref(), self.storage.smudge_timeout_secs)
+    }
+
+    /// Version URL for new pointers: `[lfs] version_url`, else git-lfs's
+    pub fn pointer_version(&self) -> &str {
+        self.lfs
+            .as_ref()
+            .and_then(|lfs| lfs.version_url.as_deref())
+            .unwrap_or(LFS_VERSION)
+    }
+
+    /// Globs for paths kept out of LFS: `[lfs] exclude`
+    pub fn excludes(&self) -> &[String] {
+        self.lfs.as_ref().map(|lfs| lfs.exclude.as_slice()).unwrap_or_default()
+    }
+
+    /// Check if configuration exists, in the repository or the user defaults
     pub fn exists<P: AsRef<Path>>(repo_root: P) -> bool {
         Self::config_path(repo_root.as_ref()).exists()
+            || Self::user_config_path().is_some_and(|path| path.exists())
     }
 
     /// Validate the configuration
@@ -137,6 +335,37 @@ impl LfsConfig {
             return Err(ConfigError::Invalid("region cannot be empty".to_string()));
         }
 
+        if let Some(version) = self.lfs.as_ref().and_then(|lfs| lfs.version_url.as_deref()) {
+            if version.is_empty() || version.contains(char::is_whitespace) {
+                return Err(ConfigError::Invalid(
+                    "lfs.version_url must be a non-empty URL without whitespace".to_string(),
+                ));
+            }
+        }
+
+        for pattern in self.excludes() {
+            globset::Glob::new(pattern)
+                .map_err(|e| ConfigError::Invalid(format!("lfs.exclude: {}", e)))?;
+        }
+
+        if let Some(creds) = &self.storage.credentials {
+            match (&creds.profile, &creds.access_key_id, &creds.secret_access_key) {
+                (Some(_), None, None) | (None, Some(_), Some(_)) => {}
+                (Some(_), _, _) => {
+                    return Err(ConfigError::Invalid(
+                        "credentials: set either profile or access_key_id/secret_access_key, not both"
+                            .to_string(),
+                    ));
+                }
+                _ => {
+                    return Err(ConfigError::Invalid(
+                        "credentials: access_key_id and secret_access_key must both be set"
+                            .to_string(),
+                    ));
+                }
+            }
+        }
+
         Ok(())
     }
 
@@ -148,39 +377,171 @@ impl LfsConfig {
                 bucket: "my-lfs-bucket".to_string(),
                 region: "us-east-1".to_string(),
                 prefix: Some("lfs/".to_string()),
+                key_layout: KeyLayout::Gg,
                 endpoint: None,
+                force_path_style: false,
+                disable_checksums: false,
+                read_endpoint: None,
+                read_bucket: None,
+                cache_control: None,
+                connect_timeout_secs: None,
+                operation_timeout_secs: None,
+                smudge_timeout_secs: None,
                 credentials: None,
             },
+            cache: None,
+            lfs: None,
         }
     }
 
-    /// Generate template TOML content with comments
+    /// Generate template TOML content with comments.
+    /// Values come from `template()`, so the file matches the typed defaults.
     pub fn template_toml() -> String {
-        r#"# gg-lfs Configuration
-# See: https://github.com/yourusername/git-gud
+        let template = Self::template();
+        let storage = template.storage;
+        let quote = |value: &str| toml::Value::String(value.to_string()).to_string();
+        let optional = |key: &str, value: &Option<String>, example: &str| match value {
+            Some(value) => format!("{} = {}", key, quote(value)),
+            None => format!("# {} = {}", key, quote(example)),
+        };
+
+        let optional_secs = |key: &str, value: Option<u64>, example: u64| match value {
+            Some(value) => format!("{} = {}", key, value),
+            None => format!("# {} = {}", key, example),
+        };
+
+        let provider = toml::Value::try_from(&storage.provider)
+            .map(|value| value.to_string())
+            .unwrap_or_else(|_| quote("s3"));
+
+        let credentials = match &storage.credentials {
+            Some(credentials) => {
+                let mut table = "[storage.credentials]".to_string();
+                let fields = [
+                    ("access_key_id", &credentials.access_key_id),
+                    ("secret_access_key", &credentials.secret_access_key),
+                    ("profile", &credentials.profile),
+                ];
+                for (key, value) in fields {
+                    if let Some(value) = value {
+                        table.push_str(&format!("\n{} = {}", key, quote(value)));
+                    }
+                }
+                table
+            }
+            None => "# [storage.credentials]\n# access_key_id = \"AKIA...\"\n# secret_access_key = \"...\"\n# Or use a named AWS profile (including SSO) instead of keys:\n# profile = \"my-profile\""
+                .to_string(),
+        };
+
+        let cache_dir = match template.cache.and_then(|cache| cache.dir) {
+            Some(dir) => format!("[cache]\ndir = {}", quote(&dir.to_string_lossy())),
+            None => "# [cache]\n# dir = \"/mnt/ci-cache/gg-lfs\"\n# link_mode = \"hardlink\"  # or \"reflink\"; default \"copy\"".to_string(),
+        };
+
+        format!(
+            r#"# gg-lfs Configuration
+# See: {repository}
 
 [storage]
 # Storage provider: "s3" (more coming soon)
-provider = "s3"
+provider = {provider}
 
 # S3 bucket name (required)
-bucket = "my-lfs-bucket"
+bucket = {bucket}
 
 # AWS region (default: us-east-1)
-region = "us-east-1"
+region = {region}
 
 # Optional prefix for object keys
-# prefix = "project-name/"
+{prefix}
+
+# Key layout under the prefix: "gg" (ab/<oid>) or "git-lfs" (objects/ab/cd/<oid>)
+{key_layout}
 
 # Optional custom endpoint for S3-compatible services (MinIO, DigitalOcean Spaces, etc.)
-# endpoint = "https://nyc3.digitaloceanspaces.com"
+{endpoint}
+
+# Path-style addressing (endpoint/bucket/key), required by MinIO and Ceph
+{force_path_style}
+
+# Skip SHA-256 checksums, for S3-compatible services that reject them
+{disable_checksums}
 
-# Credentials (optional - can also use env vars or ~/.aws/credentials)
-# [storage.credentials]
-# access_key_id = "AKIA..."
-# secret_access_key = "..."
-"#
-        .to_string()
+# Optional read mirror: downloads and existence checks use it,
+# uploads and deletes still go to the bucket above
+{read_endpoint}
+{read_bucket}
+
+# Optional Cache-Control header for uploaded objects (useful behind a CDN)
+{cache_control}
+
+# Optional timeouts in seconds, so a hung network fails instead of stalling
+{connect_timeout}
+{operation_timeout}
+
+# Seconds a checkout waits for one object before keeping its pointer
+# (GG_LFS_SMUDGE_TIMEOUT overrides)
+{smudge_timeout}
+
+# Credentials (optional - can also use env vars, AWS_PROFILE or ~/.aws/credentials)
+{credentials}
+
+# Local object cache location (default: ~/.cache/gg-lfs; GG_LFS_CACHE_DIR overrides)
+{cache_dir}
+
+# [lfs]
+# Version URL written into new pointers, only for forks of the LFS spec
+# (default: {lfs_version})
+# version_url = "https://lfs.example.com/spec/v1"
+# Paths kept out of LFS even when a tracked pattern matches them
+# exclude = ["vendor/**"]
+"#,
+            repository = env!("CARGO_PKG_REPOSITORY"),
+            lfs_version = LFS_VERSION,
+            provider = provider,
+            bucket = quote(&storage.bucket),
+            region = quote(&storage.region),
+            prefix = optional("prefix", &storage.prefix, "project-name/"),
+            endpoint = optional("endpoint", &storage.endpoint, "https://nyc3.digitaloceanspaces.com"),
+            read_endpoint = optional("read_endpoint", &storage.read_endpoint, "https://s3.eu-west-1.amazonaws.com"),
+            read_bucket = optional("read_bucket", &storage.read_bucket, "my-lfs-bucket-eu"),
+            key_layout = match storage.key_layout {
+                KeyLayout::Gg => "# key_layout = \"git-lfs\"".to_string(),
+                KeyLayout::GitLfs => "key_layout = \"git-lfs\"".to_string(),
+            },
+            force_path_style = if storage.force_path_style {
+                "force_path_style = true".to_string()
+            } else {
+                "# force_path_style = true".to_string()
+            },
+            disable_checksums = if storage.disable_checksums {
+                "disable_checksums = true".to_string()
+            } else {
+                "# disable_checksums = true".to_string()
+            },
+            cache_control = optional(
+                "cache_control",
+                &storage.cache_control,
+                "public, max-age=31536000, immutable"
+            ),
+            connect_timeout = optional_secs(
+                "connect_timeout_secs",
+                storage.connect_timeout_secs,
+                DEFAULT_CONNECT_TIMEOUT_SECS
+            ),
+            operation_timeout = optional_secs(
+                "operation_timeout_secs",
+                storage.operation_timeout_secs,
+                DEFAULT_OPERATION_TIMEOUT_SECS
+            ),
+            smudge_timeout = optional_secs(
+                "smudge_timeout_secs",
+                storage.smudge_timeout_secs,
+                DEFAULT_SMUDGE_TIMEOUT_SECS
+            ),
+            credentials = credentials,
+            cache_dir = cache_dir,
+        )
     }
 
     /// Write a template configuration file
@@ -193,6 +554,113 @@ region = "us-east-1"
 
         Ok(config_path)
     }
+
+    /// Generate a repository config that only overrides the prefix of the
+    /// user defaults at `user_path`
+    pub fn override_toml(user_path: &Path, prefix: &str) -> String {
+        format!(
+            r#"# gg-lfs Configuration
+# Storage settings come from {user_path}.
+# Values set here override them field by field.
+
+[storage]
+prefix = {prefix}
+"#,
+            user_path = user_path.display(),
+            prefix = toml::Value::String(prefix.to_string()),
+        )
+    }
+
+    /// Write a repository config that only overrides the prefix
+    pub fn write_override<P: AsRef<Path>>(
+        repo_root: P,
+        user_path: &Path,
+        prefix: &str,
+    ) -> Result<PathBuf, ConfigError> {
+        let config_dir = repo_root.as_ref().join(".gg");
+        fs::create_dir_all(&config_dir)?;
+
+        let config_path = config_dir.join("lfs.toml");
+        fs::write(&config_path, Self::override_toml(user_path, prefix))?;
+
+        Ok(config_path)
+    }
+}
+
+/// The parts of a git-lfs `.lfsconfig` that gg understands
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GitLfsConfig {
+    /// `lfs.url`: the git-lfs server
+    pub url: Option<String>,
+    /// `lfs.concurrenttransfers`
+    pub concurrent_transfers: Option<usize>,
+}
+
+impl GitLfsConfig {
+    /// Read `.lfsconfig` from the repository root, if there is one
+    pub fn load(repo_root: &Path) -> Option<Self> {
+        let content = fs::read_to_string(repo_root.join(".lfsconfig")).ok()?;
+        Some(Self::parse(&content))
+    }
+
+    /// Parse `.lfsconfig` (git config syntax), keeping only the `[lfs]` keys gg uses
+    pub fn parse(content: &str) -> Self {
+        let mut config = Self::default();
+        let mut in_lfs = false;
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            // Subsections like [lfs "https://host"] hold per-URL settings
+            if let Some(section) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+                in_lfs = section.trim().eq_ignore_ascii_case("lfs");
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            if !in_lfs {
+                continue;
+            }
+            let value = value.trim().trim_matches('"').to_string();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "url" => config.url = Some(value),
+                "concurrenttransfers" => config.concurrent_transfers = value.parse().ok(),
+                _ => {}
+            }
+        }
+
+        config
+    }
+}
+
+/// Overlay `overrides` onto `base`, merging nested tables key by key.
+/// Credentials are replaced whole, so a repo's profile never mixes with
+/// inline keys from the user defaults.
+fn merge_tables(base: &mut toml::Table, overrides: toml::Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(table)) if key != "credentials" => {
+                merge_tables(existing, table);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Pick the smudge timeout from the environment value, then the config
+fn resolve_smudge_timeout(env: Option<&str>, configured: Option<u64>) -> Duration {
+    let from_env = env.and_then(|value| value.trim().parse::<u64>().ok());
+    let secs = [from_env, configured]
+        .into_iter()
+        .flatten()
+        .find(|&secs| secs > 0)
+        .unwrap_or(DEFAULT_SMUDGE_TIMEOUT_SECS);
+    Duration::from_secs(secs)
 }
 
 #[cfg(test)]
@@ -207,6 +675,26 @@ mod tests {
         assert!(!config.storage.bucket.is_empty());
     }
 
+    #[test]
+    fn test_template_toml_matches_template() {
+        let parsed: LfsConfig = toml::from_str(&LfsConfig::template_toml()).unwrap();
+
+        assert_eq!(
+            toml::to_string(&parsed).unwrap(),
+            toml::to_string(&LfsConfig::template()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_template_toml_keeps_comments() {
+        let content = LfsConfig::template_toml();
+
+        assert!(content.contains("# S3 bucket name (required)"));
+        assert!(content.contains("# endpoint = "));
+        assert!(content.contains("# [storage.credentials]"));
+        assert!(!content.contains("yourusername"));
+    }
+
     #[test]
     fn test_config_save_and_load() {
         let temp = TempDir::new().unwrap();
@@ -222,11 +710,63 @@ mod tests {
     #[test]
     fn test_config_not_found() {
         let temp = TempDir::new().unwrap();
-        let result = LfsConfig::load(temp.path());
+        let result = LfsConfig::load_with_defaults(temp.path(), None);
 
         assert!(matches!(result, Err(ConfigError::NotFound(_))));
     }
 
+    #[test]
+    fn test_config_not_found_detects_git_lfs_repo() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join(".lfsconfig"), "[lfs]\n\turl = https://lfs.example.com/repo\n").unwrap();
+
+        let result = LfsConfig::load_with_defaults(temp.path(), None);
+        assert!(matches!(result, Err(ConfigError::GitLfsRepo(_))));
+        assert!(result.unwrap_err().to_string().contains("gg lfs migrate"));
+        assert!(LfsConfig::setup_hint(temp.path()).contains("gg lfs migrate"));
+    }
+
+    #[test]
+    fn test_git_lfs_config_parse() {
+        let config = GitLfsConfig::parse(
+            r#"
+# shared git-lfs settings
+[lfs]
+    url = "https://lfs.example.com/repo"
+    ConcurrentTransfers = 3
+[lfs "https://lfs.example.com/repo"]
+    locksverify = false
+[core]
+    url = ignored
+"#,
+        );
+
+        assert_eq!(config.url.as_deref(), Some("https://lfs.example.com/repo"));
+        assert_eq!(config.concurrent_transfers, Some(3));
+    }
+
+    #[test]
+    fn test_transfer_jobs_defaults_and_lfsconfig() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(LfsConfig::transfer_jobs(temp.path()), DEFAULT_TRANSFER_JOBS);
+
+        fs::write(temp.path().join(".lfsconfig"), "[lfs]\nconcurrenttransfers = 2\n").unwrap();
+        assert_eq!(LfsConfig::transfer_jobs(temp.path()), 2);
+
+        fs::write(temp.path().join(".lfsconfig"), "[lfs]\nconcurrenttransfers = 0\n").unwrap();
+        assert_eq!(LfsConfig::transfer_jobs(temp.path()), DEFAULT_TRANSFER_JOBS);
+    }
+
+    #[test]
+    fn test_resolve_smudge_timeout() {
+        let default = Duration::from_secs(DEFAULT_SMUDGE_TIMEOUT_SECS);
+        assert_eq!(resolve_smudge_timeout(None, None), default);
+        assert_eq!(resolve_smudge_timeout(None, Some(30)), Duration::from_secs(30));
+        assert_eq!(resolve_smudge_timeout(Some("5"), Some(30)), Duration::from_secs(5));
+        assert_eq!(resolve_smudge_timeout(Some("soon"), Some(30)), Duration::from_secs(30));
+        assert_eq!(resolve_smudge_timeout(Some("0"), None), default);
+    }
+
     #[test]
     fn test_config_validation() {
         let mut config = LfsConfig::template();
@@ -236,6 +776,204 @@ mod tests {
         assert!(matches!(config.validate(), Err(ConfigError::Invalid(_))));
     }
 
+    #[test]
+    fn test_config_credentials_profile() {
+        let config: LfsConfig = toml::from_str(
+            r#"
+[storage]
+bucket = "test-bucket"
+
+[storage.credentials]
+profile = "work-sso"
+"#,
+        )
+        .unwrap();
+
+        assert!(config.validate().is_ok());
+        let creds = config.storage.credentials.unwrap();
+        assert_eq!(creds.profile.as_deref(), Some("work-sso"));
+        assert!(creds.access_key_id.is_none());
+    }
+
+    #[test]
+    fn test_config_key_layout() {
+        let config: LfsConfig = toml::from_str(
+            r#"
+[storage]
+bucket = "test-bucket"
+key_layout = "git-lfs"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.storage.key_layout, KeyLayout::GitLfs);
+        assert_eq!(LfsConfig::template().storage.key_layout, KeyLayout::Gg);
+        assert!(toml::from_str::<LfsConfig>("[storage]\nbucket = \"b\"\nkey_layout = \"flat\"\n").is_err());
+    }
+
+    #[test]
+    fn test_config_force_path_style() {
+        let config: LfsConfig = toml::from_str(
+            r#"
+[storage]
+bucket = "test-bucket"
+endpoint = "http://localhost:9000"
+force_path_style = true
+"#,
+        )
+        .unwrap();
+        assert!(config.storage.force_path_style);
+
+        // Off by default, and left out of saved configs unless enabled
+        assert!(!LfsConfig::template().storage.force_path_style);
+        assert!(!toml::to_string(&LfsConfig::template()).unwrap().contains("force_path_style"));
+        assert!(LfsConfig::template_toml().contains("# force_path_style = true"));
+    }
+
+    #[test]
+    fn test_config_disable_checksums() {
+        let config: LfsConfig = toml::from_str(
+            r#"
+[storage]
+bucket = "test-bucket"
+endpoint = "http://localhost:9000"
+disable_checksums = true
+"#,
+        )
+        .unwrap();
+        assert!(config.storage.disable_checksums);
+
+        // Checksums stay on by default, and the setting is left out of saved configs
+        assert!(!LfsConfig::template().storage.disable_checksums);
+        assert!(!toml::to_string(&LfsConfig::template()).unwrap().contains("disable_checksums"));
+        assert!(LfsConfig::template_toml().contains("# disable_checksums = true"));
+    }
+
+    #[test]
+    fn test_config_read_mirror() {
+        let config: LfsConfig = toml::from_str(
+            r#"
+[storage]
+bucket = "primary"
+read_endpoint = "https://mirror.example.com"
+read_bucket = "mirror"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.storage.read_endpoint.as_deref(), Some("https://mirror.example.com"));
+        assert_eq!(config.storage.read_bucket.as_deref(), Some("mirror"));
+
+        // Unset by default, and left out of saved configs
+        assert!(!toml::to_string(&LfsConfig::template()).unwrap().contains("read_"));
+        assert!(LfsConfig::template_toml().contains("# read_bucket = "));
+    }
+
+    #[test]
+    fn test_config_pointer_version() {
+        let config: LfsConfig = toml::from_str(
+            r#"
+[storage]
+bucket = "test-bucket"
+
+[lfs]
+version_url = "https://lfs.example.com/spec/v1"
+"#,
+        )
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.pointer_version(), "https://lfs.example.com/spec/v1");
+
+        // git-lfs's URL by default, and left out of saved configs
+        assert_eq!(LfsConfig::template().pointer_version(), LFS_VERSION);
+        assert!(!toml::to_string(&LfsConfig::template()).unwrap().contains("[lfs]"));
+        assert!(LfsConfig::template_toml().contains("# version_url = "));
+
+        let mut config = LfsConfig::template();
+        config.lfs = Some(FilterConfig { version_url: Some("https://a b".to_string()), ..Default::default() });
+        assert!(matches!(config.validate(), Err(ConfigError::Invalid(_))));
+    }
+
+    #[test]
+    fn test_config_excludes() {
+        let config: LfsConfig = toml::from_str(
+            r#"
+[storage]
+bucket = "test-bucket"
+
+[lfs]
+exclude = ["vendor/**", "*.keep.png"]
+"#,
+        )
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.excludes(), ["vendor/**", "*.keep.png"]);
+        assert!(LfsConfig::template().excludes().is_empty());
+
+        let mut config = LfsConfig::template();
+        config.lfs = Some(FilterConfig { exclude: vec!["a/**[".to_string()], ..Default::default() });
+        assert!(matches!(config.validate(), Err(ConfigError::Invalid(_))));
+    }
+
+    #[test]
+    fn test_config_cache_dir() {
+        let config: LfsConfig = toml::from_str(
+            r#"
+[storage]
+bucket = "test-bucket"
+
+[cache]
+dir = "/mnt/ci-cache/gg-lfs"
+"#,
+        )
+        .unwrap();
+
+        let dir = config.cache.and_then(|cache| cache.dir);
+        assert_eq!(dir, Some(PathBuf::from("/mnt/ci-cache/gg-lfs")));
+    }
+
+    #[test]
+    fn test_config_cache_link_mode() {
+        let config: LfsConfig = toml::from_str(
+            r#"
+[storage]
+bucket = "test-bucket"
+
+[cache]
+link_mode = "hardlink"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.cache.unwrap().link_mode, LinkMode::Hardlink);
+
+        let default: CacheConfig = toml::from_str("").unwrap();
+        assert_eq!(default.link_mode, LinkMode::Copy);
+        assert!(toml::from_str::<CacheConfig>("link_mode = \"symlink\"").is_err());
+    }
+
+    #[test]
+    fn test_config_credentials_validation() {
+        let mut config = LfsConfig::template();
+
+        config.storage.credentials = Some(CredentialsConfig {
+            access_key_id: Some("AKIA".to_string()),
+            secret_access_key: Some("secret".to_string()),
+            profile: Some("work".to_string()),
+        });
+        assert!(matches!(config.validate(), Err(ConfigError::Invalid(_))));
+
+        config.storage.credentials = Some(CredentialsConfig {
+            access_key_id: Some("AKIA".to_string()),
+            ..Default::default()
+        });
+        assert!(matches!(config.validate(), Err(ConfigError::Invalid(_))));
+
+        config.storage.credentials = Some(CredentialsConfig {
+            access_key_id: Some("AKIA".to_string()),
+            secret_access_key: Some("secret".to_string()),
+            profile: None,
+        });
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     fn test_config_parse() {
         let toml_content = r#"
@@ -251,4 +989,83 @@ prefix = "myproject/"
         assert_eq!(config.storage.region, "eu-west-1");
         assert_eq!(config.storage.prefix, Some("myproject/".to_string()));
     }
+
+    fn write_config(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_config_repo_overrides_user_defaults_field_by_field() {
+        let temp = TempDir::new().unwrap();
+        let user_path = temp.path().join("user").join("lfs.toml");
+        write_config(
+            &user_path,
+            r#"
+[storage]
+bucket = "shared-bucket"
+region = "eu-west-1"
+prefix = "default/"
+connect_timeout_secs = 5
+"#,
+        );
+        let repo = temp.path().join("repo");
+        write_config(&LfsConfig::config_path(&repo), "[storage]\nprefix = \"my-repo/\"\n");
+
+        let config = LfsConfig::load_with_defaults(&repo, Some(&user_path)).unwrap();
+        assert_eq!(config.storage.bucket, "shared-bucket");
+        assert_eq!(config.storage.region, "eu-west-1");
+        assert_eq!(config.storage.prefix.as_deref(), Some("my-repo/"));
+        assert_eq!(config.storage.connect_timeout_secs, Some(5));
+    }
+
+    #[test]
+    fn test_config_user_defaults_alone() {
+        let temp = TempDir::new().unwrap();
+        let user_path = temp.path().join("lfs.toml");
+        write_config(&user_path, "[storage]\nbucket = \"shared-bucket\"\n");
+
+        let config = LfsConfig::load_with_defaults(temp.path(), Some(&user_path)).unwrap();
+        assert_eq!(config.storage.bucket, "shared-bucket");
+
+        let missing = temp.path().join("missing.toml");
+        let result = LfsConfig::load_with_defaults(temp.path(), Some(&missing));
+        assert!(matches!(result, Err(ConfigError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_config_repo_credentials_replace_user_credentials() {
+        let temp = TempDir::new().unwrap();
+        let user_path = temp.path().join("lfs.toml");
+        write_config(
+            &user_path,
+            r#"
+[storage]
+bucket = "shared-bucket"
+
+[storage.credentials]
+access_key_id = "AKIA"
+secret_access_key = "secret"
+"#,
+        );
+        write_config(
+            &LfsConfig::config_path(temp.path()),
+            "[storage.credentials]\nprofile = \"work-sso\"\n",
+        );
+
+        let config = LfsConfig::load_with_defaults(temp.path(), Some(&user_path)).unwrap();
+        let creds = config.storage.credentials.unwrap();
+        assert_eq!(creds.profile.as_deref(), Some("work-sso"));
+        assert!(creds.access_key_id.is_none());
+    }
+
+    #[test]
+    fn test_override_toml_only_sets_prefix() {
+        let content = LfsConfig::override_toml(Path::new("/home/me/.config/gg/lfs.toml"), "my-repo/");
+        let table: toml::Table = toml::from_str(&content).unwrap();
+
+        assert_eq!(table.len(), 1);
+        assert_eq!(table["storage"]["prefix"].as_str(), Some("my-repo/"));
+        assert!(content.contains("/home/me/.config/gg/lfs.toml"));
+    }
 }
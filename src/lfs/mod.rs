@@ -7,9 +7,36 @@ pub mod cache;
 pub mod config;
 pub mod pointer;
 pub mod scanner;
+pub mod stat_index;
 pub mod storage;
 
 pub use cache::Cache;
 pub use config::LfsConfig;
 pub use pointer::Pointer;
 pub use scanner::Scanner;
+
+use std::sync::OnceLock;
+use tokio::runtime::Runtime;
+
+/// The async runtime shared by all LFS commands, created on first use
+pub fn runtime() -> std::io::Result<&'static Runtime> {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+    if let Some(rt) = RUNTIME.get() {
+        return Ok(rt);
+    }
+    let rt = Runtime::new()?;
+    Ok(RUNTIME.get_or_init(|| rt))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runtime_is_shared() {
+        let first = runtime().unwrap();
+        let second = runtime().unwrap();
+        assert!(std::ptr::eq(first, second));
+    }
+}
@@ -21,6 +21,23 @@ pub const LFS_VERSION: &str = "https://git-lfs.github.com/spec/v1";
 /// Maximum size for a pointer file (per LFS spec)
 pub const MAX_POINTER_SIZE: usize = 1024;
 
+/// Read buffer used when hashing content
+pub const DEFAULT_HASH_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Upper bound for `GG_LFS_HASH_BUFFER_SIZE`, so a typo can't allocate gigabytes
+pub const MAX_HASH_BUFFER_SIZE: usize = 64 * 1024 * 1024;
+
+/// Hash buffer size for the clean filter: `GG_LFS_HASH_BUFFER_SIZE` in bytes,
+/// or the default when unset or invalid
+pub fn hash_buffer_size() -> usize {
+    std::env::var("GG_LFS_HASH_BUFFER_SIZE")
+        .ok()
+        .and_then(|value| value.trim().parse::<usize>().ok())
+        .filter(|&size| size > 0)
+        .map(|size| size.min(MAX_HASH_BUFFER_SIZE))
+        .unwrap_or(DEFAULT_HASH_BUFFER_SIZE)
+}
+
 #[derive(Error, Debug)]
 pub enum PointerError {
     #[error("IO error: {0}")]
@@ -37,17 +54,28 @@ pub enum PointerError {
 
     #[error("Invalid OID format: {0}")]
     InvalidOid(String),
+
+    #[error("Duplicate key: {0}")]
+    DuplicateKey(String),
+
+    #[error("Key out of order: {0}")]
+    KeyOrder(String),
+
+    #[error("Missing trailing newline")]
+    MissingTrailingNewline,
 }
 
 /// Represents an LFS pointer
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Pointer {
-    /// The version URL (always LFS_VERSION for compatibility)
+    /// The version URL (LFS_VERSION unless configured otherwise)
     pub version: String,
     /// The object ID (sha256:hexdigest)
     pub oid: String,
     /// The original file size in bytes
     pub size: u64,
+    /// Unknown keys (e.g. git-lfs `ext-0-...` fields), preserved for re-emitting
+    pub extensions: Vec<(String, String)>,
 }
 
 #[allow(dead_code)]
@@ -61,13 +89,23 @@ impl Pointer {
     /// Create a pointer by streaming content from a reader.
     /// Optionally writes the content to `cache_path` while hashing.
     pub fn from_reader<R: Read>(
+        reader: R,
+        cache_path: Option<&Path>,
+    ) -> Result<Self, PointerError> {
+        Self::from_reader_with_buffer(reader, cache_path, DEFAULT_HASH_BUFFER_SIZE)
+    }
+
+    /// Like `from_reader`, reading `buf_size` bytes at a time. Larger buffers
+    /// help throughput on fast disks; smaller ones save memory.
+    pub fn from_reader_with_buffer<R: Read>(
         mut reader: R,
         cache_path: Option<&Path>,
+        buf_size: usize,
     ) -> Result<Self, PointerError> {
         let mut hasher = Sha256::new();
         let mut size: u64 = 0;
-        let mut cache_file = cache_path.map(|p| File::create(p)).transpose()?;
-        let mut buf = [0u8; 64 * 1024];
+        let mut cache_file = cache_path.map(File::create).transpose()?;
+        let mut buf = vec![0u8; buf_size.max(1)];
 
         loop {
             let n = reader.read(&mut buf)?;
@@ -88,6 +126,7 @@ impl Pointer {
             version: LFS_VERSION.to_string(),
             oid,
             size,
+            extensions: Vec::new(),
         })
     }
 
@@ -102,9 +141,26 @@ impl Pointer {
             version: LFS_VERSION.to_string(),
             oid,
             size: content.len() as u64,
+            extensions: Vec::new(),
         }
     }
 
+    /// Create a pointer for an object whose hash is already known
+    pub fn from_oid(sha256: &str, size: u64) -> Self {
+        Self {
+            version: LFS_VERSION.to_string(),
+            oid: format!("sha256:{}", sha256),
+            size,
+            extensions: Vec::new(),
+        }
+    }
+
+    /// Use another version URL, e.g. the one configured in `[lfs] version_url`
+    pub fn with_version(mut self, version: &str) -> Self {
+        self.version = version.to_string();
+        self
+    }
+
     /// Parse a pointer from a pointer file
     pub fn parse<P: AsRef<Path>>(path: P) -> Result<Self, PointerError> {
         let path = path.as_ref();
@@ -125,6 +181,7 @@ impl Pointer {
         let mut version = None;
         let mut oid = None;
         let mut size = None;
+        let mut extensions: Vec<(String, String)> = Vec::new();
 
         for line in reader.lines() {
             let line = line?;
@@ -163,7 +220,11 @@ impl Pointer {
                     })?);
                 }
                 _ => {
-                    // Ignore unknown keys (allows for extensions)
+                    // Keep unknown keys (allows for extensions), last value wins
+                    match extensions.iter_mut().find(|(k, _)| k == key) {
+                        Some(entry) => entry.1 = value.to_string(),
+                        None => extensions.push((key.to_string(), value.to_string())),
+                    }
                 }
             }
         }
@@ -172,9 +233,49 @@ impl Pointer {
             version: version.ok_or_else(|| PointerError::MissingField("version".to_string()))?,
             oid: oid.ok_or_else(|| PointerError::MissingField("oid".to_string()))?,
             size: size.ok_or_else(|| PointerError::MissingField("size".to_string()))?,
+            extensions,
         })
     }
 
+    /// Parse pointer content strictly per the LFS spec.
+    /// Unlike `parse_content`, rejects duplicate keys, keys out of order
+    /// (`version` first, the rest alphabetical) and a missing trailing newline.
+    pub fn parse_content_strict<R: Read>(mut reader: R) -> Result<Self, PointerError> {
+        let mut content = String::new();
+        reader.read_to_string(&mut content)?;
+
+        if !content.ends_with('\n') {
+            return Err(PointerError::MissingTrailingNewline);
+        }
+
+        let mut prev_key: Option<&str> = None;
+        for line in content.lines() {
+            let key = match line.split_once(' ') {
+                Some((key, _)) => key,
+                None => {
+                    return Err(PointerError::InvalidFormat(format!(
+                        "Invalid line format: {}",
+                        line
+                    )))
+                }
+            };
+
+            match prev_key {
+                None if key != "version" => return Err(PointerError::KeyOrder(key.to_string())),
+                Some(prev) if key == prev || key == "version" => {
+                    return Err(PointerError::DuplicateKey(key.to_string()))
+                }
+                Some(prev) if prev != "version" && key < prev => {
+                    return Err(PointerError::KeyOrder(key.to_string()))
+                }
+                _ => {}
+            }
+            prev_key = Some(key);
+        }
+
+        Self::parse_content(content.as_bytes())
+    }
+
     /// Write the pointer to a file
     pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), PointerError> {
         let content = self.to_string();
@@ -210,10 +311,19 @@ impl Pointer {
 
 impl std::fmt::Display for Pointer {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // Version must come first, then alphabetically sorted keys
+        // Version must come first, then all other keys sorted alphabetically,
+        // so git-lfs `ext-N-...` keys go before `oid`. git-lfs writes the same
+        // bytes, which keeps the pointer blobs both tools commit identical.
         writeln!(f, "version {}", self.version)?;
-        writeln!(f, "oid {}", self.oid)?;
-        writeln!(f, "size {}", self.size)
+
+        let size = self.size.to_string();
+        let mut fields: Vec<(&str, &str)> = vec![("oid", &self.oid), ("size", &size)];
+        fields.extend(self.extensions.iter().map(|(key, value)| (key.as_str(), value.as_str())));
+        fields.sort_by(|a, b| a.0.cmp(b.0));
+        for (key, value) in fields {
+            writeln!(f, "{} {}", key, value)?;
+        }
+        Ok(())
     }
 }
 
@@ -232,6 +342,17 @@ mod tests {
         assert_eq!(pointer.size, 13);
     }
 
+    #[test]
+    fn test_pointer_with_custom_version_round_trips() {
+        let pointer = Pointer::from_bytes(b"hello").with_version("https://lfs.example.com/spec/v1");
+        let text = pointer.to_string();
+        assert!(text.starts_with("version https://lfs.example.com/spec/v1\n"));
+
+        // Parsing accepts any version line and keeps it
+        let parsed = Pointer::parse_content(Cursor::new(text)).unwrap();
+        assert_eq!(parsed, pointer);
+    }
+
     #[test]
     fn test_pointer_parse_valid() {
         let content = "version https://git-lfs.github.com/spec/v1\noid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\nsize 12345\n";
@@ -262,6 +383,7 @@ mod tests {
             oid: "sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393"
                 .to_string(),
             size: 12345,
+            extensions: Vec::new(),
         };
 
         let output = pointer.to_string();
@@ -278,6 +400,7 @@ mod tests {
             oid: "sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393"
                 .to_string(),
             size: 100,
+            extensions: Vec::new(),
         };
 
         assert_eq!(
@@ -326,6 +449,36 @@ mod tests {
         assert_eq!(pointer.size, 100);
     }
 
+    #[test]
+    fn test_pointer_parse_captures_extensions() {
+        let content = "version https://git-lfs.github.com/spec/v1\noid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\nsize 100\next-1-foo sha256:aaaa\next-0-bar sha256:bbbb\n";
+        let pointer = Pointer::parse_content(Cursor::new(content)).unwrap();
+        assert_eq!(
+            pointer.extensions,
+            vec![
+                ("ext-1-foo".to_string(), "sha256:aaaa".to_string()),
+                ("ext-0-bar".to_string(), "sha256:bbbb".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pointer_extensions_round_trip() {
+        let content = "version https://git-lfs.github.com/spec/v1\noid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\nsize 100\next-1-foo sha256:aaaa\next-0-bar sha256:bbbb\n";
+        let pointer = Pointer::parse_content(Cursor::new(content)).unwrap();
+
+        let output = pointer.to_string();
+        assert_eq!(
+            output,
+            "version https://git-lfs.github.com/spec/v1\next-0-bar sha256:bbbb\next-1-foo sha256:aaaa\noid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\nsize 100\n"
+        );
+        assert!(Pointer::parse_content_strict(Cursor::new(&output)).is_ok());
+
+        let reparsed = Pointer::parse_content(Cursor::new(output)).unwrap();
+        assert_eq!(reparsed.size, pointer.size);
+        assert_eq!(reparsed.extensions.len(), 2);
+    }
+
     #[test]
     fn test_pointer_parse_invalid_oid_short_hex() {
         let content = "version https://git-lfs.github.com/spec/v1\noid sha256:abcdef\nsize 100\n";
@@ -353,6 +506,7 @@ mod tests {
             oid: "sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393"
                 .to_string(),
             size: 12345,
+            extensions: Vec::new(),
         };
         original.write(&file_path).unwrap();
 
@@ -412,6 +566,61 @@ mod tests {
         assert!(!Pointer::is_pointer_file(&path));
     }
 
+    #[test]
+    fn test_pointer_parse_strict_valid() {
+        let content = "version https://git-lfs.github.com/spec/v1\noid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\nsize 12345\n";
+        let pointer = Pointer::parse_content_strict(Cursor::new(content)).unwrap();
+        assert_eq!(pointer.size, 12345);
+    }
+
+    #[test]
+    fn test_pointer_parse_strict_duplicate_key() {
+        let content = "version https://git-lfs.github.com/spec/v1\noid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\nsize 1\nsize 2\n";
+        let result = Pointer::parse_content_strict(Cursor::new(content));
+        assert!(matches!(result, Err(PointerError::DuplicateKey(k)) if k == "size"));
+
+        // The lenient parser still accepts it (last value wins)
+        let pointer = Pointer::parse_content(Cursor::new(content)).unwrap();
+        assert_eq!(pointer.size, 2);
+    }
+
+    #[test]
+    fn test_pointer_parse_strict_out_of_order() {
+        let content = "version https://git-lfs.github.com/spec/v1\nsize 100\noid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\n";
+        let result = Pointer::parse_content_strict(Cursor::new(content));
+        assert!(matches!(result, Err(PointerError::KeyOrder(k)) if k == "oid"));
+    }
+
+    #[test]
+    fn test_pointer_parse_strict_version_not_first() {
+        let content = "oid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\nversion https://git-lfs.github.com/spec/v1\nsize 100\n";
+        let result = Pointer::parse_content_strict(Cursor::new(content));
+        assert!(matches!(result, Err(PointerError::KeyOrder(_))));
+    }
+
+    #[test]
+    fn test_pointer_parse_strict_missing_trailing_newline() {
+        let content = "version https://git-lfs.github.com/spec/v1\noid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\nsize 100";
+        let result = Pointer::parse_content_strict(Cursor::new(content));
+        assert!(matches!(result, Err(PointerError::MissingTrailingNewline)));
+    }
+
+    #[test]
+    fn test_pointer_parse_strict_accepts_display_output() {
+        let pointer = Pointer::from_bytes(b"round trip");
+        let parsed = Pointer::parse_content_strict(Cursor::new(pointer.to_string())).unwrap();
+        assert_eq!(pointer, parsed);
+    }
+
+    #[test]
+    fn test_pointer_matches_git_lfs_bytes() {
+        // `printf hello | git lfs clean`
+        let expected = "version https://git-lfs.github.com/spec/v1\n\
+                        oid sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824\n\
+                        size 5\n";
+        assert_eq!(Pointer::from_bytes(b"hello").to_string(), expected);
+    }
+
     #[test]
     fn test_pointer_parse_blank_lines_ignored() {
         let content = "\nversion https://git-lfs.github.com/spec/v1\n\noid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\n\nsize 100\n\n";
@@ -419,4 +628,36 @@ mod tests {
         let pointer = Pointer::parse_content(reader).unwrap();
         assert_eq!(pointer.size, 100);
     }
+
+    #[test]
+    fn test_pointer_from_reader_with_buffer_sizes_agree() {
+        // Deterministic pseudo-random content, larger than the default buffer
+        let mut state: u32 = 0x2545_f491;
+        let content: Vec<u8> = (0..300_000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect();
+        let expected = Pointer::from_bytes(&content);
+        let temp = tempfile::TempDir::new().unwrap();
+
+        for buf_size in [1, 7, 4096, DEFAULT_HASH_BUFFER_SIZE, 1024 * 1024] {
+            let cache_path = temp.path().join(format!("cache-{}", buf_size));
+            let pointer =
+                Pointer::from_reader_with_buffer(Cursor::new(&content), Some(&cache_path), buf_size)
+                    .unwrap();
+
+            assert_eq!(pointer, expected, "buffer size {}", buf_size);
+            assert_eq!(fs::read(&cache_path).unwrap(), content, "buffer size {}", buf_size);
+        }
+    }
+
+    #[test]
+    fn test_pointer_from_reader_zero_buffer_still_reads() {
+        let pointer = Pointer::from_reader_with_buffer(Cursor::new(b"hello"), None, 0).unwrap();
+        assert_eq!(pointer, Pointer::from_bytes(b"hello"));
+    }
 }
@@ -2,6 +2,8 @@
 //!
 //! Scans the repository for files matching LFS patterns defined in .gitattributes
 
+use crate::lfs::pointer::{Pointer, MAX_POINTER_SIZE};
+use crate::lfs::LfsConfig;
 use globset::Glob;
 use ignore::WalkBuilder;
 use std::fs::{self, File};
@@ -26,6 +28,8 @@ pub enum ScannerError {
 pub struct LfsPattern {
     /// The glob pattern
     pub pattern: String,
+    /// 1-based line in .gitattributes the pattern was read from
+    pub line: Option<usize>,
     /// The compiled pattern for matching
     compiled: globset::GlobMatcher,
 }
@@ -39,6 +43,7 @@ impl LfsPattern {
 
         Ok(Self {
             pattern: pattern.to_string(),
+            line: None,
             compiled,
         })
     }
@@ -50,6 +55,30 @@ impl LfsPattern {
     }
 }
 
+/// Paths kept out of LFS by `[lfs] exclude`, even when a pattern tracks them
+#[derive(Debug, Default)]
+pub struct Excludes(Vec<LfsPattern>);
+
+impl Excludes {
+    /// Compile exclude globs. Invalid ones are skipped; loading the config
+    /// already rejects them.
+    pub fn new(patterns: &[String]) -> Self {
+        Self(patterns.iter().filter_map(|pattern| LfsPattern::new(pattern).ok()).collect())
+    }
+
+    /// The excludes configured for a repository, if it has a usable config
+    pub fn load(repo_root: &Path) -> Self {
+        LfsConfig::load(repo_root)
+            .map(|config| Self::new(config.excludes()))
+            .unwrap_or_default()
+    }
+
+    /// Whether a repository-relative path is excluded
+    pub fn matches(&self, path: &Path) -> bool {
+        self.0.iter().any(|pattern| pattern.matches(path))
+    }
+}
+
 /// Scanner for finding LFS-tracked files
 #[derive(Debug)]
 pub struct Scanner {
@@ -57,6 +86,8 @@ pub struct Scanner {
     repo_root: PathBuf,
     /// Patterns that mark files for LFS
     patterns: Vec<LfsPattern>,
+    /// Paths kept out of LFS regardless of the patterns
+    excludes: Excludes,
 }
 
 impl Scanner {
@@ -68,9 +99,11 @@ impl Scanner {
             return Err(ScannerError::NoRepository);
         }
 
+        let excludes = Excludes::load(&repo_root);
         let mut scanner = Self {
             repo_root,
             patterns: Vec::new(),
+            excludes,
         };
 
         scanner.load_patterns()?;
@@ -89,7 +122,7 @@ impl Scanner {
         let file = File::open(&gitattributes)?;
         let reader = BufReader::new(file);
 
-        for line in reader.lines() {
+        for (index, line) in reader.lines().enumerate() {
             let line = line?;
             let line = line.trim();
 
@@ -104,7 +137,8 @@ impl Scanner {
             if line.contains("filter=gg-lfs") || line.contains("filter=lfs") {
                 let parts: Vec<&str> = line.split_whitespace().collect();
                 if let Some(pattern) = parts.first() {
-                    if let Ok(lfs_pattern) = LfsPattern::new(pattern) {
+                    if let Ok(mut lfs_pattern) = LfsPattern::new(pattern) {
+                        lfs_pattern.line = Some(index + 1);
                         self.patterns.push(lfs_pattern);
                     }
                 }
@@ -114,14 +148,19 @@ impl Scanner {
         Ok(())
     }
 
-    /// Check if a file path matches any LFS pattern
+    /// Check if a file path matches any LFS pattern and isn't excluded
     pub fn is_lfs_file(&self, path: &Path) -> bool {
-        for pattern in &self.patterns {
-            if pattern.matches(path) {
-                return true;
-            }
-        }
-        false
+        self.matching_pattern(path).is_some() && !self.is_excluded(path)
+    }
+
+    /// Whether `[lfs] exclude` keeps a path out of LFS
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        self.excludes.matches(path)
+    }
+
+    /// The first pattern that marks a file path for LFS, if any
+    pub fn matching_pattern(&self, path: &Path) -> Option<&LfsPattern> {
+        self.patterns.iter().find(|pattern| pattern.matches(path))
     }
 
     /// Get all patterns
@@ -209,6 +248,18 @@ impl Scanner {
     /// Scan the repository for files matching LFS patterns.
     /// Respects .gitignore, .git/info/exclude, and global git excludes.
     pub fn scan_files(&self) -> Result<Vec<PathBuf>, ScannerError> {
+        self.walk(|_, rel| self.is_lfs_file(rel))
+    }
+
+    /// Find pointer files that no LFS pattern tracks any more, e.g. after
+    /// `gg lfs untrack`. Git won't smudge them, so they stay as pointer text.
+    pub fn scan_orphaned_pointers(&self) -> Result<Vec<PathBuf>, ScannerError> {
+        self.walk(|path, rel| !self.is_lfs_file(rel) && Pointer::is_pointer_file(path))
+    }
+
+    /// Walk the working tree (skipping ignored files and .git), collecting
+    /// files for which `keep(path, relative)` returns true
+    fn walk(&self, keep: impl Fn(&Path, &Path) -> bool) -> Result<Vec<PathBuf>, ScannerError> {
         let mut files = Vec::new();
 
         for entry in WalkBuilder::new(&self.repo_root)
@@ -216,16 +267,17 @@ impl Scanner {
             .git_ignore(true)
             .git_global(true)
             .git_exclude(true)
+            .filter_entry(|entry| entry.file_name() != ".git")
             .build()
         {
             let entry = entry.map_err(|e| {
-                ScannerError::Io(io::Error::new(io::ErrorKind::Other, e))
+                ScannerError::Io(io::Error::other(e))
             })?;
             if !entry.path().is_file() {
                 continue;
             }
             if let Ok(rel) = entry.path().strip_prefix(&self.repo_root) {
-                if self.is_lfs_file(rel) {
+                if keep(entry.path(), rel) {
                     files.push(entry.into_path());
                 }
             }
@@ -235,6 +287,22 @@ impl Scanner {
     }
 }
 
+/// Whether HEAD stores this path as a regular blob instead of an LFS pointer.
+pub fn committed_as_content(repo: &git2::Repository, path: &str) -> bool {
+    let Some(tree) = repo.head().ok().and_then(|head| head.peel_to_tree().ok()) else {
+        return false;
+    };
+    let Ok(entry) = tree.get_path(Path::new(path)) else {
+        return false;
+    };
+    let Ok(blob) = repo.find_blob(entry.id()) else {
+        return false;
+    };
+
+    let content = blob.content();
+    content.len() > MAX_POINTER_SIZE || Pointer::parse_content(content).is_err()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -253,4 +321,65 @@ mod tests {
         assert!(pattern.matches(Path::new("assets/image.psd")));
         assert!(!pattern.matches(Path::new("src/main.rs")));
     }
+
+    #[test]
+    fn test_scanner_matching_pattern_returns_first_match() {
+        let temp = tempfile::TempDir::new().unwrap();
+        fs::create_dir(temp.path().join(".git")).unwrap();
+        fs::write(
+            temp.path().join(".gitattributes"),
+            "*.md text\nassets/* filter=gg-lfs diff=gg-lfs merge=gg-lfs -text\n*.psd filter=gg-lfs diff=gg-lfs merge=gg-lfs -text\n",
+        )
+        .unwrap();
+        let scanner = Scanner::new(temp.path()).unwrap();
+
+        let matched = |path: &str| scanner.matching_pattern(Path::new(path)).map(|p| p.pattern.as_str());
+        assert_eq!(matched("assets/image.psd"), Some("assets/*"));
+        assert_eq!(matched("art/image.psd"), Some("*.psd"));
+        assert_eq!(matched("README.md"), None);
+        assert_eq!(scanner.matching_pattern(Path::new("a.psd")).unwrap().line, Some(3));
+    }
+
+    #[test]
+    fn test_scanner_honors_configured_excludes() {
+        let temp = tempfile::TempDir::new().unwrap();
+        fs::create_dir(temp.path().join(".git")).unwrap();
+        fs::create_dir(temp.path().join(".gg")).unwrap();
+        fs::write(
+            temp.path().join(".gitattributes"),
+            "*.psd filter=gg-lfs diff=gg-lfs merge=gg-lfs -text\n",
+        )
+        .unwrap();
+        fs::write(
+            temp.path().join(".gg").join("lfs.toml"),
+            "[storage]\nbucket = \"b\"\n\n[lfs]\nexclude = [\"vendor/**\"]\n",
+        )
+        .unwrap();
+        let scanner = Scanner::new(temp.path()).unwrap();
+
+        assert!(scanner.is_lfs_file(Path::new("art/a.psd")));
+        assert!(scanner.is_excluded(Path::new("vendor/ui/a.psd")));
+        assert!(!scanner.is_lfs_file(Path::new("vendor/ui/a.psd")));
+        // Still reported as the matching pattern, e.g. for check-attr
+        assert!(scanner.matching_pattern(Path::new("vendor/ui/a.psd")).is_some());
+    }
+
+    #[test]
+    fn test_scanner_finds_orphaned_pointers() {
+        let temp = tempfile::TempDir::new().unwrap();
+        fs::create_dir(temp.path().join(".git")).unwrap();
+        fs::write(
+            temp.path().join(".gitattributes"),
+            "*.psd filter=gg-lfs diff=gg-lfs merge=gg-lfs -text\n",
+        )
+        .unwrap();
+        let pointer = Pointer::from_bytes(b"hello").to_string();
+        fs::write(temp.path().join("tracked.psd"), &pointer).unwrap();
+        fs::write(temp.path().join("orphan.bin"), &pointer).unwrap();
+        fs::write(temp.path().join("notes.txt"), "just text").unwrap();
+        fs::write(temp.path().join(".git").join("stray"), &pointer).unwrap();
+        let scanner = Scanner::new(temp.path()).unwrap();
+
+        assert_eq!(scanner.scan_orphaned_pointers().unwrap(), vec![temp.path().join("orphan.bin")]);
+    }
 }
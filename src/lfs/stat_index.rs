@@ -0,0 +1,190 @@
+//! Index of previously computed OIDs for working-tree files
+//!
+//! `git add` runs the clean filter on every matching file, even when nothing
+//! changed. The index remembers each file's OID keyed by size, mtime and
+//! inode so an unchanged file doesn't have to be hashed again.
+//! Location: .gg/stat-index (one tab-separated entry per line)
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Files modified this recently may still change within the same timestamp,
+/// so their attributes are not trusted
+const RACY_WINDOW: Duration = Duration::from_secs(2);
+
+/// The file attributes an index entry is keyed by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileStat {
+    pub size: u64,
+    pub mtime_ns: u128,
+    pub inode: u64,
+}
+
+impl FileStat {
+    /// Read the attributes of a file. Returns None if it can't be stat'ed or
+    /// changed so recently that its timestamp can't be trusted yet.
+    /// Take the stat before reading the content it will be recorded with.
+    pub fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        let modified = metadata.modified().ok()?;
+        if SystemTime::now().duration_since(modified).unwrap_or_default() < RACY_WINDOW {
+            return None;
+        }
+        let mtime_ns = modified.duration_since(UNIX_EPOCH).ok()?.as_nanos();
+
+        #[cfg(unix)]
+        let inode = std::os::unix::fs::MetadataExt::ino(&metadata);
+        #[cfg(not(unix))]
+        let inode = 0;
+
+        Some(Self { size: metadata.len(), mtime_ns, inode })
+    }
+}
+
+/// Map from repository-relative path to the stat and OID last seen for it
+#[derive(Debug)]
+pub struct StatIndex {
+    path: PathBuf,
+    entries: HashMap<String, (FileStat, String)>,
+    dirty: bool,
+}
+
+impl StatIndex {
+    /// Load the index for a repository. A missing or unreadable index is empty.
+    pub fn load(repo_root: &Path) -> Self {
+        let path = repo_root.join(".gg").join("stat-index");
+        let entries = fs::read_to_string(&path)
+            .map(|content| content.lines().filter_map(parse_entry).collect())
+            .unwrap_or_default();
+
+        Self { path, entries, dirty: false }
+    }
+
+    /// The recorded OID for `relative`, if its attributes still match `stat`
+    pub fn lookup(&self, relative: &str, stat: &FileStat) -> Option<&str> {
+        match self.entries.get(relative) {
+            Some((recorded, oid)) if recorded == stat => Some(oid),
+            _ => None,
+        }
+    }
+
+    /// Remember the OID computed for a file
+    pub fn record(&mut self, relative: &str, stat: FileStat, oid: &str) {
+        // Entries are newline-separated, so such paths simply aren't indexed
+        if relative.contains('\n') {
+            return;
+        }
+        self.entries.insert(relative.to_string(), (stat, oid.to_string()));
+        self.dirty = true;
+    }
+
+    /// Forget a file, e.g. after its content turned out not to match
+    pub fn remove(&mut self, relative: &str) {
+        if self.entries.remove(relative).is_some() {
+            self.dirty = true;
+        }
+    }
+
+    /// Write the index back if it changed. Writes to a temp file and renames
+    /// it so concurrent readers never see a partial index.
+    pub fn save(&mut self) -> io::Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut content = String::new();
+        for (relative, (stat, oid)) in &self.entries {
+            content.push_str(&format!(
+                "{}\t{}\t{}\t{}\t{}\n",
+                stat.size, stat.mtime_ns, stat.inode, oid, relative
+            ));
+        }
+
+        let temp = self.path.with_extension(format!("tmp-{}", std::process::id()));
+        fs::write(&temp, content)?;
+        fs::rename(&temp, &self.path)?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+/// Parse one `size mtime inode oid path` line; the path is last so it may contain tabs
+fn parse_entry(line: &str) -> Option<(String, (FileStat, String))> {
+    let mut fields = line.splitn(5, '\t');
+    let size = fields.next()?.parse().ok()?;
+    let mtime_ns = fields.next()?.parse().ok()?;
+    let inode = fields.next()?.parse().ok()?;
+    let oid = fields.next()?.to_string();
+    let relative = fields.next()?.to_string();
+
+    Some((relative, (FileStat { size, mtime_ns, inode }, oid)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const OID: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    fn old_stat(size: u64) -> FileStat {
+        FileStat { size, mtime_ns: 1_600_000_000_000_000_000, inode: 42 }
+    }
+
+    #[test]
+    fn test_stat_index_round_trip() {
+        let temp = TempDir::new().unwrap();
+        let mut index = StatIndex::load(temp.path());
+        index.record("assets/my model.bin", old_stat(5), OID);
+        index.save().unwrap();
+
+        let index = StatIndex::load(temp.path());
+        assert_eq!(index.lookup("assets/my model.bin", &old_stat(5)), Some(OID));
+    }
+
+    #[test]
+    fn test_stat_index_misses_on_changed_attributes() {
+        let temp = TempDir::new().unwrap();
+        let mut index = StatIndex::load(temp.path());
+        index.record("a.bin", old_stat(5), OID);
+
+        assert_eq!(index.lookup("a.bin", &old_stat(6)), None);
+        let touched = FileStat { mtime_ns: old_stat(5).mtime_ns + 1, ..old_stat(5) };
+        assert_eq!(index.lookup("a.bin", &touched), None);
+        let replaced = FileStat { inode: 43, ..old_stat(5) };
+        assert_eq!(index.lookup("a.bin", &replaced), None);
+        assert_eq!(index.lookup("b.bin", &old_stat(5)), None);
+    }
+
+    #[test]
+    fn test_file_stat_distrusts_fresh_files() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("fresh.bin");
+        fs::write(&path, "hello").unwrap();
+        assert_eq!(FileStat::of(&path), None);
+
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(60)).unwrap();
+        assert_eq!(FileStat::of(&path).map(|stat| stat.size), Some(5));
+    }
+
+    #[test]
+    fn test_stat_index_ignores_corrupt_lines() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join(".gg")).unwrap();
+        fs::write(
+            temp.path().join(".gg").join("stat-index"),
+            format!("garbage\n5\t1600000000000000000\t42\t{}\ta.bin\n", OID),
+        )
+        .unwrap();
+
+        let index = StatIndex::load(temp.path());
+        assert_eq!(index.lookup("a.bin", &old_stat(5)), Some(OID));
+    }
+}
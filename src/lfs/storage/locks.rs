@@ -0,0 +1,87 @@
+//! File locks kept in storage
+//!
+//! `gg lfs lock` stores `<prefix>/locks/<sha256 of path>.json` holding the
+//! locked path, its owner and when it was taken. Creating a lock is
+//! conditional, so two people can't both take the same one, and so is
+//! removing one, so an unlock can't remove a lock taken since. Locks are
+//! advisory: they warn before pushing someone else's file, nothing more.
+
+use super::StorageError;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Directory holding lock objects, next to the objects under the prefix
+pub const LOCKS_DIR: &str = "locks";
+
+/// A path locked by one user
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Lock {
+    /// Path relative to the repository root, with `/` separators
+    pub path: String,
+    /// Who holds the lock (their git `user.email`, else `user.name`)
+    pub owner: String,
+    /// When the lock was taken, in seconds since the Unix epoch
+    pub locked_at: i64,
+    /// ETag of the stored lock, when it was read from the bucket
+    #[serde(skip)]
+    pub etag: Option<String>,
+}
+
+impl Lock {
+    /// Lock `path` for `owner` now
+    pub fn new(path: &str, owner: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            owner: owner.to_string(),
+            locked_at: crate::utils::unix_now(),
+            etag: None,
+        }
+    }
+
+    /// Parse a lock as stored in the bucket
+    pub fn parse(content: &[u8]) -> Result<Self, StorageError> {
+        serde_json::from_slice(content).map_err(|e| {
+            StorageError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("invalid lock: {}", e),
+            ))
+        })
+    }
+
+    /// Serialize for upload
+    pub fn to_json(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    /// Object name of the lock on `path`, under `LOCKS_DIR`. Hashing keeps
+    /// any path a single flat key.
+    pub fn object_name(path: &str) -> String {
+        format!("{:x}.json", Sha256::digest(path.as_bytes()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_round_trip() {
+        let lock = Lock { path: "art/hero.psd".to_string(), owner: "ana@example.com".to_string(), locked_at: 1700000000, etag: None };
+
+        let json = lock.to_json();
+        assert_eq!(
+            String::from_utf8_lossy(&json),
+            r#"{"path":"art/hero.psd","owner":"ana@example.com","locked_at":1700000000}"#
+        );
+        assert_eq!(Lock::parse(&json).unwrap(), lock);
+        assert!(Lock::parse(b"not json").is_err());
+    }
+
+    #[test]
+    fn test_lock_object_name_is_stable_per_path() {
+        let name = Lock::object_name("art/hero.psd");
+        assert_eq!(name.len(), 64 + ".json".len());
+        assert_eq!(name, Lock::object_name("art/hero.psd"));
+        assert_ne!(name, Lock::object_name("art/hero2.psd"));
+    }
+}
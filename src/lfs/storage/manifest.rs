@@ -0,0 +1,83 @@
+//! List of objects known to be in storage
+//!
+//! Push reads `<prefix>/manifest.json` once instead of sending a HEAD request
+//! per object, then writes back what it uploaded. The manifest is only a
+//! cache: concurrent pushers can overwrite each other's additions, so an OID
+//! missing from it is still checked with a HEAD request before uploading.
+
+use super::StorageError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Object name of the manifest, next to the objects under the prefix
+pub const MANIFEST_NAME: &str = "manifest.json";
+
+/// OIDs recorded as present in storage
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct ObjectManifest {
+    oids: BTreeSet<String>,
+}
+
+impl ObjectManifest {
+    /// Parse a manifest as stored in the bucket
+    pub fn parse(content: &[u8]) -> Result<Self, StorageError> {
+        serde_json::from_slice(content).map_err(|e| {
+            StorageError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("invalid {}: {}", MANIFEST_NAME, e),
+            ))
+        })
+    }
+
+    /// Serialize for upload
+    pub fn to_json(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    /// Whether an object is recorded as present
+    pub fn contains(&self, oid: &str) -> bool {
+        self.oids.contains(oid)
+    }
+
+    /// Record an object as present. Returns false if it already was.
+    pub fn insert(&mut self, oid: &str) -> bool {
+        self.oids.insert(oid.to_string())
+    }
+
+    /// Add every object recorded in `other`, e.g. by another pusher
+    pub fn merge(&mut self, other: ObjectManifest) {
+        self.oids.extend(other.oids);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_round_trip() {
+        let mut manifest = ObjectManifest::default();
+        assert!(manifest.insert("bbb"));
+        assert!(manifest.insert("aaa"));
+        assert!(!manifest.insert("aaa"));
+
+        let json = manifest.to_json();
+        assert_eq!(String::from_utf8_lossy(&json), r#"{"oids":["aaa","bbb"]}"#);
+        assert_eq!(ObjectManifest::parse(&json).unwrap(), manifest);
+    }
+
+    #[test]
+    fn test_manifest_merge_keeps_both_sides() {
+        let mut ours = ObjectManifest::parse(br#"{"oids":["aaa"]}"#).unwrap();
+        let theirs = ObjectManifest::parse(br#"{"oids":["aaa","ccc"]}"#).unwrap();
+
+        ours.merge(theirs);
+        assert!(ours.contains("aaa"));
+        assert!(ours.contains("ccc"));
+    }
+
+    #[test]
+    fn test_manifest_rejects_garbage() {
+        assert!(matches!(ObjectManifest::parse(b"not json"), Err(StorageError::Io(_))));
+    }
+}
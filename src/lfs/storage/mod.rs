@@ -2,12 +2,20 @@
 //!
 //! Provides a trait for storage operations and implementations for different providers.
 
+pub mod locks;
+pub mod manifest;
 pub mod s3;
 
+use crate::lfs::config::{DEFAULT_CONNECT_TIMEOUT_SECS, DEFAULT_OPERATION_TIMEOUT_SECS};
 use async_trait::async_trait;
+use std::io::{Read, Write};
 use std::path::Path;
+use std::sync::OnceLock;
+use std::time::Duration;
 use thiserror::Error;
 
+pub use locks::Lock;
+pub use manifest::ObjectManifest;
 pub use s3::{S3Config, S3Credentials, S3Storage};
 
 #[derive(Error, Debug)]
@@ -30,6 +38,9 @@ pub enum StorageError {
 
     #[error("AWS SDK error: {0}")]
     AwsSdk(String),
+
+    #[error("Verification failed: {0}")]
+    Verification(String),
 }
 
 /// Result of an upload operation
@@ -50,12 +61,22 @@ pub struct UploadResult {
 pub struct DownloadResult {
     /// The object ID
     pub oid: String,
-    /// Size in bytes
+    /// Bytes transferred, not counting any resumed from a partial file
     pub size: u64,
     /// Path where the file was downloaded
     pub path: std::path::PathBuf,
 }
 
+/// Metadata for a stored object
+#[derive(Debug, Clone)]
+pub struct ObjectInfo {
+    /// Stored size in bytes
+    pub size: u64,
+    /// SHA-256 of the content as lowercase hex, when the backend recorded a
+    /// checksum for the whole object
+    pub sha256: Option<String>,
+}
+
 /// Trait for LFS storage backends
 #[async_trait]
 #[allow(dead_code)]
@@ -63,12 +84,126 @@ pub trait Storage: Send + Sync {
     /// Upload a file to storage
     async fn upload(&self, oid: &str, source: &Path) -> Result<UploadResult, StorageError>;
 
+    /// Upload a file to storage, calling `progress` with the number of bytes
+    /// sent as each chunk completes. Backends without chunked uploads report
+    /// the whole file once the upload finishes.
+    async fn upload_with_progress(
+        &self,
+        oid: &str,
+        source: &Path,
+        progress: &(dyn Fn(u64) + Send + Sync),
+    ) -> Result<UploadResult, StorageError> {
+        let result = self.upload(oid, source).await?;
+        progress(result.size);
+        Ok(result)
+    }
+
+    /// Upload a file that lives at `name` in the repository. Backends can use
+    /// the name to pick a content type; the default ignores it.
+    async fn upload_named(
+        &self,
+        oid: &str,
+        source: &Path,
+        _name: &Path,
+        progress: &(dyn Fn(u64) + Send + Sync),
+    ) -> Result<UploadResult, StorageError> {
+        self.upload_with_progress(oid, source, progress).await
+    }
+
+    /// Upload `size` bytes read from `reader` without requiring a file on disk.
+    /// The default spills the stream to a temp file and calls `upload`.
+    async fn upload_stream(
+        &self,
+        oid: &str,
+        reader: &mut (dyn Read + Send),
+        size: u64,
+    ) -> Result<UploadResult, StorageError> {
+        let temp_path = std::env::temp_dir()
+            .join(format!("gg-lfs-upload-{}-{}", std::process::id(), oid));
+
+        let spilled = std::fs::File::create(&temp_path)
+            .and_then(|mut file| std::io::copy(&mut reader.take(size), &mut file));
+        let result = match spilled {
+            Ok(n) if n == size => self.upload(oid, &temp_path).await,
+            Ok(n) => Err(StorageError::Io(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                format!("expected {} bytes, read {}", size, n),
+            ))),
+            Err(e) => Err(StorageError::Io(e)),
+        };
+
+        let _ = std::fs::remove_file(&temp_path);
+        result
+    }
+
     /// Download a file from storage
     async fn download(&self, oid: &str, dest: &Path) -> Result<DownloadResult, StorageError>;
 
+    /// Download a file, continuing from the bytes already present at `dest`.
+    /// The default ignores any partial file and downloads from the start.
+    async fn download_resume(&self, oid: &str, dest: &Path) -> Result<DownloadResult, StorageError> {
+        self.download(oid, dest).await
+    }
+
+    /// Stream an object into `writer` as it arrives, returning the number of
+    /// bytes written. The default downloads to a temp file and copies it.
+    async fn download_to_writer(
+        &self,
+        oid: &str,
+        writer: &mut (dyn Write + Send),
+    ) -> Result<u64, StorageError> {
+        let temp_path = std::env::temp_dir()
+            .join(format!("gg-lfs-download-{}-{}", std::process::id(), oid));
+
+        let result = match self.download(oid, &temp_path).await {
+            Ok(_) => std::fs::File::open(&temp_path)
+                .and_then(|mut file| std::io::copy(&mut file, writer))
+                .map_err(StorageError::Io),
+            Err(e) => Err(e),
+        };
+
+        let _ = std::fs::remove_file(&temp_path);
+        result
+    }
+
     /// Check if an object exists in storage
     async fn exists(&self, oid: &str) -> Result<bool, StorageError>;
 
+    /// Look up a stored object's metadata, or None if it doesn't exist
+    async fn stat(&self, oid: &str) -> Result<Option<ObjectInfo>, StorageError>;
+
+    /// Read the manifest of objects known to be stored, or None if there
+    /// isn't one yet. Backends that don't keep a manifest always return None.
+    async fn read_manifest(&self) -> Result<Option<ObjectManifest>, StorageError> {
+        Ok(None)
+    }
+
+    /// Replace the stored manifest. The default does nothing.
+    async fn write_manifest(&self, _manifest: &ObjectManifest) -> Result<(), StorageError> {
+        Ok(())
+    }
+
+    /// Read the lock on a path, or None if it isn't locked
+    async fn read_lock(&self, _path: &str) -> Result<Option<Lock>, StorageError> {
+        Err(locks_unsupported(self.provider_name()))
+    }
+
+    /// Store a lock unless its path is already locked. Returns false if it was.
+    async fn create_lock(&self, _lock: &Lock) -> Result<bool, StorageError> {
+        Err(locks_unsupported(self.provider_name()))
+    }
+
+    /// Remove `lock`, unless the stored lock changed since it was read.
+    /// Returns false if it did.
+    async fn delete_lock(&self, _lock: &Lock) -> Result<bool, StorageError> {
+        Err(locks_unsupported(self.provider_name()))
+    }
+
+    /// Every current lock
+    async fn list_locks(&self) -> Result<Vec<Lock>, StorageError> {
+        Err(locks_unsupported(self.provider_name()))
+    }
+
     /// Delete an object from storage
     async fn delete(&self, oid: &str) -> Result<(), StorageError>;
 
@@ -76,21 +211,363 @@ pub trait Storage: Send + Sync {
     fn provider_name(&self) -> &str;
 }
 
+/// Error for backends without file locking
+fn locks_unsupported(provider: &str) -> StorageError {
+    StorageError::Config(format!("{} storage doesn't support file locking", provider))
+}
+
+/// Check that a stored object has the expected size and, when the backend
+/// recorded a checksum, that it matches the OID
+pub async fn verify_object(storage: &dyn Storage, oid: &str, size: u64) -> Result<(), StorageError> {
+    let info = storage
+        .stat(oid)
+        .await?
+        .ok_or_else(|| StorageError::Verification("object not found after upload".to_string()))?;
+
+    if info.size != size {
+        return Err(StorageError::Verification(format!(
+            "stored size {} does not match expected {}",
+            info.size, size
+        )));
+    }
+    if let Some(sha256) = info.sha256 {
+        if sha256 != oid {
+            return Err(StorageError::Verification(format!(
+                "stored checksum {} does not match OID",
+                sha256
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Content type for objects whose original name is unknown
+pub const DEFAULT_CONTENT_TYPE: &str = "application/octet-stream";
+
+/// Guess a content type from a file's extension, for objects that may be
+/// served straight from the bucket (e.g. through a CDN)
+pub fn content_type_for(name: &Path) -> &'static str {
+    let extension = name
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+
+    match extension.as_deref() {
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("svg") => "image/svg+xml",
+        Some("ico") => "image/x-icon",
+        Some("bmp") => "image/bmp",
+        Some("tif" | "tiff") => "image/tiff",
+        Some("psd") => "image/vnd.adobe.photoshop",
+        Some("woff") => "font/woff",
+        Some("woff2") => "font/woff2",
+        Some("ttf") => "font/ttf",
+        Some("otf") => "font/otf",
+        Some("mp3") => "audio/mpeg",
+        Some("wav") => "audio/wav",
+        Some("ogg") => "audio/ogg",
+        Some("mp4") => "video/mp4",
+        Some("webm") => "video/webm",
+        Some("mov") => "video/quicktime",
+        Some("pdf") => "application/pdf",
+        Some("zip") => "application/zip",
+        Some("gz") => "application/gzip",
+        Some("json") => "application/json",
+        Some("wasm") => "application/wasm",
+        Some("glb") => "model/gltf-binary",
+        Some("gltf") => "model/gltf+json",
+        _ => DEFAULT_CONTENT_TYPE,
+    }
+}
+
 /// Create a storage backend from LFS config
 pub async fn create_storage(
     config: &crate::lfs::LfsConfig,
 ) -> Result<Box<dyn Storage>, StorageError> {
-    let s3_config = S3Config {
+    let storage = S3Storage::new(s3_config(config)).await?;
+    Ok(Box::new(storage))
+}
+
+/// Storage backend built once per process and reused by later calls.
+/// gg only talks to one repository's storage per process, so the first
+/// config wins.
+pub async fn shared_storage(
+    config: &crate::lfs::LfsConfig,
+) -> Result<&'static dyn Storage, StorageError> {
+    static STORAGE: OnceLock<Box<dyn Storage>> = OnceLock::new();
+
+    if let Some(storage) = STORAGE.get() {
+        return Ok(storage.as_ref());
+    }
+    let storage = create_storage(config).await?;
+    Ok(STORAGE.get_or_init(|| storage).as_ref())
+}
+
+/// Translate LFS config into S3 settings.
+/// A profile wins over keys, though `LfsConfig::validate` rejects having both.
+pub fn s3_config(config: &crate::lfs::LfsConfig) -> S3Config {
+    let credentials = config.storage.credentials.as_ref().and_then(|c| {
+        if let Some(profile) = &c.profile {
+            return Some(S3Credentials::Profile(profile.clone()));
+        }
+        Some(S3Credentials::Static {
+            access_key_id: c.access_key_id.clone()?,
+            secret_access_key: c.secret_access_key.clone()?,
+        })
+    });
+
+    S3Config {
         bucket: config.storage.bucket.clone(),
         region: config.storage.region.clone(),
         prefix: config.storage.prefix.clone(),
+        key_layout: config.storage.key_layout,
         endpoint: config.storage.endpoint.clone(),
-        credentials: config.storage.credentials.as_ref().map(|c| S3Credentials {
-            access_key_id: c.access_key_id.clone(),
-            secret_access_key: c.secret_access_key.clone(),
-        }),
-    };
+        force_path_style: config.storage.force_path_style,
+        checksums: !config.storage.disable_checksums,
+        read_endpoint: config.storage.read_endpoint.clone(),
+        read_bucket: config.storage.read_bucket.clone(),
+        cache_control: config.storage.cache_control.clone(),
+        credentials,
+        connect_timeout: Duration::from_secs(
+            config.storage.connect_timeout_secs.unwrap_or(DEFAULT_CONNECT_TIMEOUT_SECS),
+        ),
+        operation_timeout: Duration::from_secs(
+            config.storage.operation_timeout_secs.unwrap_or(DEFAULT_OPERATION_TIMEOUT_SECS),
+        ),
+    }
+}
 
-    let storage = S3Storage::new(s3_config).await?;
-    Ok(Box::new(storage))
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    /// Storage that accepts every upload without sending anything
+    struct NullStorage;
+
+    #[async_trait]
+    impl Storage for NullStorage {
+        async fn upload(&self, oid: &str, source: &Path) -> Result<UploadResult, StorageError> {
+            Ok(UploadResult {
+                oid: oid.to_string(),
+                size: std::fs::metadata(source)?.len(),
+                uploaded: true,
+            })
+        }
+
+        async fn download(&self, oid: &str, _dest: &Path) -> Result<DownloadResult, StorageError> {
+            Err(StorageError::NotFound(oid.to_string()))
+        }
+
+        async fn exists(&self, _oid: &str) -> Result<bool, StorageError> {
+            Ok(false)
+        }
+
+        async fn stat(&self, _oid: &str) -> Result<Option<ObjectInfo>, StorageError> {
+            Ok(None)
+        }
+
+        async fn delete(&self, _oid: &str) -> Result<(), StorageError> {
+            Ok(())
+        }
+
+        fn provider_name(&self) -> &str {
+            "null"
+        }
+    }
+
+    #[test]
+    fn test_download_resume_default_delegates_to_download() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let temp = tempfile::TempDir::new().unwrap();
+        let dest = temp.path().join("abc.part");
+
+        let result = rt.block_on(NullStorage.download_resume("abc", &dest));
+        assert!(matches!(result, Err(StorageError::NotFound(oid)) if oid == "abc"));
+    }
+
+    #[test]
+    fn test_upload_with_progress_default_reports_full_size() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let source = temp.path().join("data.bin");
+        std::fs::write(&source, vec![0u8; 1234]).unwrap();
+
+        let reported = AtomicU64::new(0);
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let result = rt
+            .block_on(NullStorage.upload_with_progress("abc", &source, &|n| {
+                reported.fetch_add(n, Ordering::SeqCst);
+            }))
+            .unwrap();
+
+        assert_eq!(result.size, 1234);
+        assert_eq!(reported.load(Ordering::SeqCst), 1234);
+    }
+
+    #[test]
+    fn test_upload_stream_default_spills_to_temp_file() {
+        let content = vec![7u8; 4321];
+        let mut reader = std::io::Cursor::new(content);
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let result = rt
+            .block_on(NullStorage.upload_stream("stream-test", &mut reader, 4321))
+            .unwrap();
+
+        assert_eq!(result.size, 4321);
+        assert!(result.uploaded);
+    }
+
+    #[test]
+    fn test_upload_stream_default_rejects_short_reader() {
+        let mut reader = std::io::Cursor::new(vec![1u8; 10]);
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let result = rt.block_on(NullStorage.upload_stream("short-test", &mut reader, 100));
+
+        assert!(matches!(result, Err(StorageError::Io(_))));
+    }
+
+    #[test]
+    fn test_locks_default_to_unsupported() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        let result = rt.block_on(NullStorage.create_lock(&Lock::new("a.bin", "me")));
+        assert!(matches!(result, Err(StorageError::Config(message)) if message.contains("null")));
+        assert!(rt.block_on(NullStorage.list_locks()).is_err());
+    }
+
+    /// Storage whose only object is described by the given metadata
+    struct StatStorage(ObjectInfo);
+
+    #[async_trait]
+    impl Storage for StatStorage {
+        async fn upload(&self, oid: &str, source: &Path) -> Result<UploadResult, StorageError> {
+            NullStorage.upload(oid, source).await
+        }
+
+        async fn download(&self, oid: &str, dest: &Path) -> Result<DownloadResult, StorageError> {
+            NullStorage.download(oid, dest).await
+        }
+
+        async fn exists(&self, _oid: &str) -> Result<bool, StorageError> {
+            Ok(true)
+        }
+
+        async fn stat(&self, _oid: &str) -> Result<Option<ObjectInfo>, StorageError> {
+            Ok(Some(self.0.clone()))
+        }
+
+        async fn delete(&self, _oid: &str) -> Result<(), StorageError> {
+            Ok(())
+        }
+
+        fn provider_name(&self) -> &str {
+            "stat"
+        }
+    }
+
+    #[test]
+    fn test_verify_object() {
+        // sha256("hello")
+        let oid = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let verify = |storage: &dyn Storage| rt.block_on(verify_object(storage, oid, 5));
+
+        let good = StatStorage(ObjectInfo { size: 5, sha256: Some(oid.to_string()) });
+        assert!(verify(&good).is_ok());
+
+        let no_checksum = StatStorage(ObjectInfo { size: 5, sha256: None });
+        assert!(verify(&no_checksum).is_ok());
+
+        let truncated = StatStorage(ObjectInfo { size: 3, sha256: None });
+        assert!(matches!(verify(&truncated), Err(StorageError::Verification(_))));
+
+        let corrupt = StatStorage(ObjectInfo { size: 5, sha256: Some("0".repeat(64)) });
+        assert!(matches!(verify(&corrupt), Err(StorageError::Verification(_))));
+
+        assert!(matches!(verify(&NullStorage), Err(StorageError::Verification(_))));
+    }
+
+    #[test]
+    fn test_content_type_for() {
+        assert_eq!(content_type_for(Path::new("assets/logo.png")), "image/png");
+        assert_eq!(content_type_for(Path::new("fonts/Inter.WOFF2")), "font/woff2");
+        assert_eq!(content_type_for(Path::new("models/ship.fbx")), DEFAULT_CONTENT_TYPE);
+        assert_eq!(content_type_for(Path::new("Makefile")), DEFAULT_CONTENT_TYPE);
+    }
+
+    #[test]
+    fn test_upload_named_default_delegates_to_upload() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("object");
+        std::fs::write(&path, b"named").unwrap();
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let result = rt
+            .block_on(NullStorage.upload_named("named-test", &path, Path::new("logo.png"), &|_| {}))
+            .unwrap();
+
+        assert_eq!(result.size, 5);
+    }
+
+    #[test]
+    fn test_s3_config_timeouts() {
+        let mut config = crate::lfs::LfsConfig::template();
+        let s3 = s3_config(&config);
+        assert_eq!(s3.connect_timeout, Duration::from_secs(DEFAULT_CONNECT_TIMEOUT_SECS));
+        assert_eq!(s3.operation_timeout, Duration::from_secs(DEFAULT_OPERATION_TIMEOUT_SECS));
+
+        config.storage.connect_timeout_secs = Some(3);
+        config.storage.operation_timeout_secs = Some(60);
+        let s3 = s3_config(&config);
+        assert_eq!(s3.connect_timeout, Duration::from_secs(3));
+        assert_eq!(s3.operation_timeout, Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_s3_config_force_path_style() {
+        let mut config = crate::lfs::LfsConfig::template();
+        assert!(!s3_config(&config).force_path_style);
+
+        config.storage.force_path_style = true;
+        assert!(s3_config(&config).force_path_style);
+    }
+
+    #[test]
+    fn test_s3_config_checksums() {
+        let mut config = crate::lfs::LfsConfig::template();
+        assert!(s3_config(&config).checksums);
+
+        config.storage.disable_checksums = true;
+        assert!(!s3_config(&config).checksums);
+    }
+
+    #[test]
+    fn test_s3_config_maps_credentials() {
+        let mut config = crate::lfs::LfsConfig::template();
+        assert!(s3_config(&config).credentials.is_none());
+
+        config.storage.credentials = Some(crate::lfs::config::CredentialsConfig {
+            profile: Some("work-sso".to_string()),
+            ..Default::default()
+        });
+        assert!(matches!(
+            s3_config(&config).credentials,
+            Some(S3Credentials::Profile(ref p)) if p == "work-sso"
+        ));
+
+        config.storage.credentials = Some(crate::lfs::config::CredentialsConfig {
+            access_key_id: Some("AKIA".to_string()),
+            secret_access_key: Some("secret".to_string()),
+            profile: None,
+        });
+        assert!(matches!(
+            s3_config(&config).credentials,
+            Some(S3Credentials::Static { ref access_key_id, .. }) if access_key_id == "AKIA"
+        ));
+    }
 }
@@ -1,18 +1,54 @@
 //! AWS S3 storage backend
 
-use super::{DownloadResult, Storage, StorageError, UploadResult};
+use super::locks::{Lock, LOCKS_DIR};
+use super::manifest::MANIFEST_NAME;
+use super::{
+    content_type_for, DownloadResult, ObjectInfo, ObjectManifest, Storage, StorageError,
+    UploadResult, DEFAULT_CONTENT_TYPE,
+};
+use crate::lfs::config::KeyLayout;
 use async_trait::async_trait;
-use aws_sdk_s3::primitives::ByteStream;
+use base64::Engine;
+use aws_config::timeout::TimeoutConfig;
+use aws_sdk_s3::config::{RequestChecksumCalculation, ResponseChecksumValidation};
+use aws_sdk_s3::primitives::{ByteStream, Length};
+use aws_sdk_s3::types::{
+    ChecksumAlgorithm, ChecksumMode, CompletedMultipartUpload, CompletedPart,
+};
+use aws_sdk_s3::error::SdkError;
+use aws_sdk_s3::operation::get_object::GetObjectOutput;
 use aws_sdk_s3::Client;
+use std::io::{Read, Write};
 use std::path::Path;
+use std::time::Duration;
 use tokio::fs::File;
 use tokio::io::AsyncWriteExt;
 
-/// Inline credentials for S3
+/// Files larger than this are uploaded in parts so progress can be reported
+const MULTIPART_THRESHOLD: u64 = 16 * 1024 * 1024;
+
+/// Size of each part in a multipart upload
+const MULTIPART_PART_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Where the bytes of a multipart upload come from
+enum PartSource<'a> {
+    /// Read each part directly from a file at its offset
+    File(&'a Path),
+    /// Read each part sequentially from a stream
+    Reader(&'a mut (dyn Read + Send)),
+}
+
+/// Explicitly configured S3 credentials. Without these the default AWS
+/// chain is used (env vars, AWS_PROFILE, ~/.aws/credentials, instance roles).
 #[derive(Debug, Clone)]
-pub struct S3Credentials {
-    pub access_key_id: String,
-    pub secret_access_key: String,
+pub enum S3Credentials {
+    /// Inline keys from .gg/lfs.toml
+    Static {
+        access_key_id: String,
+        secret_access_key: String,
+    },
+    /// Named profile from ~/.aws/config or ~/.aws/credentials, including SSO
+    Profile(String),
 }
 
 /// S3 storage configuration
@@ -24,95 +60,523 @@ pub struct S3Config {
     pub region: String,
     /// Optional prefix for object keys
     pub prefix: Option<String>,
+    /// Key scheme under the prefix
+    pub key_layout: KeyLayout,
     /// Optional custom endpoint (for S3-compatible services)
     pub endpoint: Option<String>,
-    /// Optional inline credentials
+    /// Address objects as `endpoint/bucket/key` instead of `bucket.endpoint/key`
+    pub force_path_style: bool,
+    /// Send SHA-256 checksums on upload and ask S3 to validate downloads
+    pub checksums: bool,
+    /// Endpoint of a read-only mirror, used by downloads and `exists`
+    pub read_endpoint: Option<String>,
+    /// Bucket of a read-only mirror, used by downloads and `exists`
+    pub read_bucket: Option<String>,
+    /// Optional Cache-Control header for uploaded objects
+    pub cache_control: Option<String>,
+    /// Optional explicit credentials
     pub credentials: Option<S3Credentials>,
+    /// How long to wait for a connection
+    pub connect_timeout: Duration,
+    /// How long a single request may take, including retries
+    pub operation_timeout: Duration,
+}
+
+impl S3Config {
+    /// Build the AWS SDK config: region, endpoint and credential provider
+    pub async fn load_aws_config(&self) -> aws_config::SdkConfig {
+        let mut builder =
+            aws_config::from_env().region(aws_config::Region::new(self.region.clone()));
+
+        // Set custom endpoint if provided
+        if let Some(endpoint) = &self.endpoint {
+            builder = builder.endpoint_url(endpoint);
+        }
+
+        builder = builder.timeout_config(
+            TimeoutConfig::builder()
+                .connect_timeout(self.connect_timeout)
+                .operation_timeout(self.operation_timeout)
+                .build(),
+        );
+
+        match &self.credentials {
+            Some(S3Credentials::Static {
+                access_key_id,
+                secret_access_key,
+            }) => {
+                let credentials = aws_sdk_s3::config::Credentials::new(
+                    access_key_id,
+                    secret_access_key,
+                    None,
+                    None,
+                    "gg-lfs-config",
+                );
+                builder = builder.credentials_provider(credentials);
+            }
+            Some(S3Credentials::Profile(profile)) => {
+                let provider = aws_config::profile::ProfileFileCredentialsProvider::builder()
+                    .profile_name(profile)
+                    .build();
+                builder = builder.credentials_provider(provider);
+            }
+            // The default chain already reads AWS_PROFILE
+            None => {}
+        }
+
+        builder.load().await
+    }
+
+    /// Build an S3 client from a loaded SDK config, applying path-style addressing
+    pub fn client(&self, aws_config: &aws_config::SdkConfig) -> Client {
+        Client::from_conf(self.client_builder(aws_config).build())
+    }
+
+    /// Build the client for the read mirror. Without a `read_endpoint` this
+    /// talks to the same endpoint as `client`.
+    pub fn read_client(&self, aws_config: &aws_config::SdkConfig) -> Client {
+        let mut builder = self.client_builder(aws_config);
+        if let Some(endpoint) = &self.read_endpoint {
+            builder = builder.endpoint_url(endpoint);
+        }
+        Client::from_conf(builder.build())
+    }
+
+    /// Client settings shared by `client` and `read_client`. With checksums
+    /// off, the SDK also stops adding its default CRC32 checksums.
+    fn client_builder(&self, aws_config: &aws_config::SdkConfig) -> aws_sdk_s3::config::Builder {
+        let mut builder = aws_sdk_s3::config::Builder::from(aws_config)
+            .force_path_style(self.force_path_style);
+        if !self.checksums {
+            builder = builder
+                .request_checksum_calculation(RequestChecksumCalculation::WhenRequired)
+                .response_checksum_validation(ResponseChecksumValidation::WhenRequired);
+        }
+        builder
+    }
+
+    /// The algorithm uploads declare, if checksums are on
+    fn checksum_algorithm(&self) -> Option<ChecksumAlgorithm> {
+        self.checksums.then_some(ChecksumAlgorithm::Sha256)
+    }
+
+    /// The `x-amz-checksum-sha256` value to send for an object, if checksums are on
+    fn oid_checksum(&self, oid: &str) -> Option<String> {
+        if self.checksums {
+            oid_checksum(oid)
+        } else {
+            None
+        }
+    }
+
+    /// The bucket downloads and existence checks read from
+    pub fn read_bucket(&self) -> &str {
+        self.read_bucket.as_deref().unwrap_or(&self.bucket)
+    }
+
+    /// Describe where credentials will come from, for `gg lfs verify` and `gg lfs env`
+    pub fn credential_source(&self) -> String {
+        match &self.credentials {
+            Some(S3Credentials::Static { .. }) => "inline keys in .gg/lfs.toml".to_string(),
+            Some(S3Credentials::Profile(profile)) => format!("AWS profile '{}'", profile),
+            None => {
+                let env = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+                if env("AWS_ACCESS_KEY_ID").is_some() {
+                    "AWS_ACCESS_KEY_ID environment variables".to_string()
+                } else if let Some(profile) = env("AWS_PROFILE") {
+                    format!("AWS profile '{}' (from AWS_PROFILE)", profile)
+                } else {
+                    "default AWS credential chain (~/.aws, SSO, instance role)".to_string()
+                }
+            }
+        }
+    }
 }
 
-/// AWS S3 storage backend
+/// AWS S3 storage backend. Writes go to the primary bucket; downloads and
+/// `exists` go to the read mirror when one is configured.
 pub struct S3Storage {
     client: Client,
+    read_client: Client,
     config: S3Config,
 }
 
 impl S3Storage {
     /// Create a new S3 storage backend
     pub async fn new(config: S3Config) -> Result<Self, StorageError> {
-        let mut aws_config_builder = aws_config::from_env();
+        let aws_config = config.load_aws_config().await;
 
-        // Set region
-        aws_config_builder =
-            aws_config_builder.region(aws_config::Region::new(config.region.clone()));
+        let client = config.client(&aws_config);
+        let read_client = if config.read_endpoint.is_some() {
+            config.read_client(&aws_config)
+        } else {
+            client.clone()
+        };
 
-        // Set custom endpoint if provided
-        if let Some(endpoint) = &config.endpoint {
-            aws_config_builder = aws_config_builder.endpoint_url(endpoint);
-        }
-
-        // Use inline credentials if provided
-        if let Some(creds) = &config.credentials {
-            let credentials = aws_sdk_s3::config::Credentials::new(
-                &creds.access_key_id,
-                &creds.secret_access_key,
-                None,
-                None,
-                "gg-lfs-config",
-            );
-            aws_config_builder = aws_config_builder.credentials_provider(credentials);
+        Ok(Self { client, read_client, config })
+    }
+
+    /// Check for an object with a HEAD request against one target
+    async fn head(&self, client: &Client, bucket: &str, oid: &str) -> Result<bool, StorageError> {
+        let key = self.object_key(oid);
+
+        match client.head_object().bucket(bucket).key(&key).send().await {
+            Ok(_) => Ok(true),
+            Err(e) if is_not_found(&e) => Ok(false),
+            Err(e) => Err(StorageError::AwsSdk(e.to_string())),
         }
+    }
+
+    /// Whether the primary bucket already has an object. Uploads check the
+    /// primary, since a mirror may lag behind or hold objects the primary lost.
+    async fn exists_in_primary(&self, oid: &str) -> Result<bool, StorageError> {
+        self.head(&self.client, &self.config.bucket, oid).await
+    }
 
-        let aws_config = aws_config_builder.load().await;
+    /// Read a small metadata object (manifest, lock) from the primary
+    /// bucket, or None if it doesn't exist
+    async fn get_small(&self, key: &str) -> Result<Option<(Vec<u8>, Option<String>)>, StorageError> {
+        let response = match self.client.get_object().bucket(&self.config.bucket).key(key).send().await {
+            Ok(response) => response,
+            Err(e) if is_not_found(&e) => return Ok(None),
+            Err(e) => return Err(StorageError::AwsSdk(e.to_string())),
+        };
 
-        let client = Client::new(&aws_config);
+        let content = response
+            .body
+            .collect()
+            .await
+            .map_err(|e| StorageError::Network(e.to_string()))?
+            .into_bytes();
+        Ok(Some((content.to_vec(), response.e_tag)))
+    }
 
-        Ok(Self { client, config })
+    /// Read the lock stored under `key`, remembering its ETag
+    async fn get_lock(&self, key: &str) -> Result<Option<Lock>, StorageError> {
+        match self.get_small(key).await? {
+            Some((content, etag)) => Ok(Some(Lock { etag, ..Lock::parse(&content)? })),
+            None => Ok(None),
+        }
     }
 
     /// Get the full object key with prefix
     fn object_key(&self, oid: &str) -> String {
-        // Use first 2 chars of hash as directory for better S3 performance
-        let prefix = &oid[..2.min(oid.len())];
+        object_key(self.config.prefix.as_deref(), self.config.key_layout, oid)
+    }
+
+    /// Request an object from the read target, starting at byte `offset`.
+    /// An offset at or past the end of the object (416) fetches all of it;
+    /// the response's Content-Range says which one came back.
+    async fn get(&self, oid: &str, offset: u64) -> Result<GetObjectOutput, StorageError> {
+        let key = self.object_key(oid);
+        let mut offset = offset;
+
+        loop {
+            let mut request = self
+                .read_client
+                .get_object()
+                .bucket(self.config.read_bucket())
+                .key(&key);
+            if offset > 0 {
+                request = request.range(format!("bytes={}-", offset));
+            } else {
+                // The SDK validates the body against the stored checksum as it streams
+                request = request.set_checksum_mode(
+                    self.config.checksums.then_some(ChecksumMode::Enabled),
+                );
+            }
+
+            match request.send().await {
+                Ok(response) => {
+                    // A full-object checksum that isn't the OID means the wrong object
+                    check_stored_checksum(oid, response.checksum_sha256())?;
+                    return Ok(response);
+                }
+                // The partial file is already complete (or stale) - start over
+                Err(e) if offset > 0 && is_range_not_satisfiable(&e) => offset = 0,
+                Err(e) if is_not_found(&e) => return Err(StorageError::NotFound(oid.to_string())),
+                Err(e) => return Err(StorageError::AwsSdk(e.to_string())),
+            }
+        }
+    }
+
+    /// Stream an object to `dest`, starting at byte `offset`. A non-zero
+    /// offset sends a range request and appends to the existing file.
+    async fn download_from(
+        &self,
+        oid: &str,
+        dest: &Path,
+        offset: u64,
+    ) -> Result<DownloadResult, StorageError> {
+        let response = self.get(oid, offset).await?;
+        // Without a Content-Range the whole object came back, so start over
+        let offset = if response.content_range().is_some() { offset } else { 0 };
+
+        // Ensure parent directory exists
+        if let Some(parent) = dest.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let mut file = if offset > 0 {
+            tokio::fs::OpenOptions::new().append(true).open(dest).await?
+        } else {
+            File::create(dest).await?
+        };
+
+        // Stream body to file (avoids loading entire object into memory)
+        let mut body_stream = response.body.into_async_read();
+        let written = tokio::io::copy(&mut body_stream, &mut file).await?;
+        file.flush().await?;
+
+        Ok(DownloadResult {
+            oid: oid.to_string(),
+            size: written,
+            path: dest.to_path_buf(),
+        })
+    }
+
+    /// Upload a large object as a multipart upload, reporting each finished part.
+    /// S3 checks each part against its SHA-256 as it arrives.
+    async fn upload_multipart(
+        &self,
+        key: &str,
+        mut source: PartSource<'_>,
+        size: u64,
+        content_type: &str,
+        progress: &(dyn Fn(u64) + Send + Sync),
+    ) -> Result<(), StorageError> {
+        let created = self
+            .client
+            .create_multipart_upload()
+            .bucket(&self.config.bucket)
+            .key(key)
+            .set_checksum_algorithm(self.config.checksum_algorithm())
+            .content_type(content_type)
+            .set_cache_control(self.config.cache_control.clone())
+            .send()
+            .await
+            .map_err(|e| StorageError::AwsSdk(e.to_string()))?;
+
+        let upload_id = created
+            .upload_id()
+            .ok_or_else(|| StorageError::AwsSdk("missing multipart upload id".to_string()))?
+            .to_string();
+
+        let mut parts = Vec::new();
+        let mut offset = 0;
+        let mut part_number = 1;
+
+        while offset < size {
+            let length = MULTIPART_PART_SIZE.min(size - offset);
+
+            let result = async {
+                let body = match &mut source {
+                    PartSource::File(path) => ByteStream::read_from()
+                        .path(path)
+                        .offset(offset)
+                        .length(Length::Exact(length))
+                        .build()
+                        .await
+                        .map_err(|e| StorageError::Io(std::io::Error::other(e)))?,
+                    PartSource::Reader(reader) => {
+                        let mut buf = vec![0u8; length as usize];
+                        reader.read_exact(&mut buf)?;
+                        ByteStream::from(buf)
+                    }
+                };
+
+                self.client
+                    .upload_part()
+                    .bucket(&self.config.bucket)
+                    .key(key)
+                    .upload_id(&upload_id)
+                    .part_number(part_number)
+                    .set_checksum_algorithm(self.config.checksum_algorithm())
+                    .body(body)
+                    .send()
+                    .await
+                    .map_err(|e| StorageError::AwsSdk(e.to_string()))
+            }
+            .await;
+
+            let part = match result {
+                Ok(part) => part,
+                Err(e) => {
+                    self.abort_multipart(key, &upload_id).await;
+                    return Err(e);
+                }
+            };
+
+            parts.push(
+                CompletedPart::builder()
+                    .set_e_tag(part.e_tag().map(String::from))
+                    .set_checksum_sha256(part.checksum_sha256().map(String::from))
+                    .part_number(part_number)
+                    .build(),
+            );
+            progress(length);
+
+            offset += length;
+            part_number += 1;
+        }
+
+        let completed = self
+            .client
+            .complete_multipart_upload()
+            .bucket(&self.config.bucket)
+            .key(key)
+            .upload_id(&upload_id)
+            .multipart_upload(
+                CompletedMultipartUpload::builder()
+                    .set_parts(Some(parts))
+                    .build(),
+            )
+            .send()
+            .await;
+        if let Err(e) = completed {
+            self.abort_multipart(key, &upload_id).await;
+            return Err(StorageError::AwsSdk(e.to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Abort a multipart upload so its parts don't linger and accrue storage
+    /// costs. Failing to abort is ignored; the upload has already failed.
+    async fn abort_multipart(&self, key: &str, upload_id: &str) {
+        let _ = self
+            .client
+            .abort_multipart_upload()
+            .bucket(&self.config.bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .send()
+            .await;
+    }
+
+    /// Upload a file as a single request or in parts, depending on its size
+    async fn upload_file(
+        &self,
+        oid: &str,
+        source: &Path,
+        content_type: &str,
+        progress: &(dyn Fn(u64) + Send + Sync),
+    ) -> Result<UploadResult, StorageError> {
+        let key = self.object_key(oid);
+
+        let metadata = tokio::fs::metadata(source).await?;
+        let size = metadata.len();
+
+        // Check if already exists
+        if self.exists_in_primary(oid).await? {
+            return Ok(UploadResult {
+                oid: oid.to_string(),
+                size,
+                uploaded: false,
+            });
+        }
+
+        if size > MULTIPART_THRESHOLD {
+            self.upload_multipart(&key, PartSource::File(source), size, content_type, progress)
+                .await?;
+        } else {
+            // Read file and upload
+            let body = ByteStream::from_path(source)
+                .await
+                .map_err(|e| StorageError::Io(std::io::Error::other(e)))?;
+
+            let response = self
+                .client
+                .put_object()
+                .bucket(&self.config.bucket)
+                .key(&key)
+                .body(body)
+                .content_type(content_type)
+                .set_cache_control(self.config.cache_control.clone())
+                .set_checksum_algorithm(self.config.checksum_algorithm())
+                .set_checksum_sha256(self.config.oid_checksum(oid))
+                .send()
+                .await
+                .map_err(|e| StorageError::AwsSdk(e.to_string()))?;
+            check_stored_checksum(oid, response.checksum_sha256())?;
 
-        match &self.config.prefix {
-            Some(p) => format!("{}/{}/{}", p.trim_end_matches('/'), prefix, oid),
-            None => format!("{}/{}", prefix, oid),
+            progress(size);
         }
+
+        Ok(UploadResult {
+            oid: oid.to_string(),
+            size,
+            uploaded: true,
+        })
     }
 }
 
 #[async_trait]
 impl Storage for S3Storage {
     async fn upload(&self, oid: &str, source: &Path) -> Result<UploadResult, StorageError> {
+        self.upload_with_progress(oid, source, &|_| {}).await
+    }
+
+    async fn upload_with_progress(
+        &self,
+        oid: &str,
+        source: &Path,
+        progress: &(dyn Fn(u64) + Send + Sync),
+    ) -> Result<UploadResult, StorageError> {
+        self.upload_file(oid, source, DEFAULT_CONTENT_TYPE, progress).await
+    }
+
+    async fn upload_named(
+        &self,
+        oid: &str,
+        source: &Path,
+        name: &Path,
+        progress: &(dyn Fn(u64) + Send + Sync),
+    ) -> Result<UploadResult, StorageError> {
+        self.upload_file(oid, source, content_type_for(name), progress).await
+    }
+
+    async fn upload_stream(
+        &self,
+        oid: &str,
+        reader: &mut (dyn Read + Send),
+        size: u64,
+    ) -> Result<UploadResult, StorageError> {
         let key = self.object_key(oid);
 
         // Check if already exists
-        if self.exists(oid).await? {
-            let metadata = tokio::fs::metadata(source).await?;
+        if self.exists_in_primary(oid).await? {
             return Ok(UploadResult {
                 oid: oid.to_string(),
-                size: metadata.len(),
+                size,
                 uploaded: false,
             });
         }
 
-        // Read file and upload
-        let body = ByteStream::from_path(source)
-            .await
-            .map_err(|e| StorageError::Io(std::io::Error::new(std::io::ErrorKind::Other, e)))?;
-
-        let metadata = tokio::fs::metadata(source).await?;
-        let size = metadata.len();
+        if size > MULTIPART_THRESHOLD {
+            let source = PartSource::Reader(reader);
+            self.upload_multipart(&key, source, size, DEFAULT_CONTENT_TYPE, &|_| {})
+                .await?;
+        } else {
+            // Small enough to buffer in memory as a single request
+            let mut buf = vec![0u8; size as usize];
+            reader.read_exact(&mut buf)?;
 
-        self.client
-            .put_object()
-            .bucket(&self.config.bucket)
-            .key(&key)
-            .body(body)
-            .content_type("application/octet-stream")
-            .send()
-            .await
-            .map_err(|e| StorageError::AwsSdk(e.to_string()))?;
+            let response = self
+                .client
+                .put_object()
+                .bucket(&self.config.bucket)
+                .key(&key)
+                .body(ByteStream::from(buf))
+                .content_type(DEFAULT_CONTENT_TYPE)
+                .set_cache_control(self.config.cache_control.clone())
+                .set_checksum_algorithm(self.config.checksum_algorithm())
+                .set_checksum_sha256(self.config.oid_checksum(oid))
+                .send()
+                .await
+                .map_err(|e| StorageError::AwsSdk(e.to_string()))?;
+            check_stored_checksum(oid, response.checksum_sha256())?;
+        }
 
         Ok(UploadResult {
             oid: oid.to_string(),
@@ -122,63 +586,185 @@ impl Storage for S3Storage {
     }
 
     async fn download(&self, oid: &str, dest: &Path) -> Result<DownloadResult, StorageError> {
+        self.download_from(oid, dest, 0).await
+    }
+
+    async fn download_resume(&self, oid: &str, dest: &Path) -> Result<DownloadResult, StorageError> {
+        let offset = tokio::fs::metadata(dest).await.map(|m| m.len()).unwrap_or(0);
+        self.download_from(oid, dest, offset).await
+    }
+
+    async fn download_to_writer(
+        &self,
+        oid: &str,
+        writer: &mut (dyn Write + Send),
+    ) -> Result<u64, StorageError> {
+        let mut body = self.get(oid, 0).await?.body;
+
+        let mut written = 0;
+        while let Some(chunk) = body
+            .try_next()
+            .await
+            .map_err(|e| StorageError::Network(e.to_string()))?
+        {
+            writer.write_all(&chunk)?;
+            written += chunk.len() as u64;
+        }
+        writer.flush()?;
+        Ok(written)
+    }
+
+    async fn exists(&self, oid: &str) -> Result<bool, StorageError> {
+        self.head(&self.read_client, self.config.read_bucket(), oid).await
+    }
+
+    async fn stat(&self, oid: &str) -> Result<Option<ObjectInfo>, StorageError> {
         let key = self.object_key(oid);
 
-        let response = self
+        match self
             .client
-            .get_object()
+            .head_object()
             .bucket(&self.config.bucket)
             .key(&key)
+            .set_checksum_mode(self.config.checksums.then_some(ChecksumMode::Enabled))
             .send()
             .await
-            .map_err(|e| {
-                let err_str = e.to_string();
-                if err_str.contains("NoSuchKey") || err_str.contains("404") {
-                    StorageError::NotFound(oid.to_string())
-                } else {
-                    StorageError::AwsSdk(err_str)
-                }
-            })?;
+        {
+            Ok(head) => Ok(Some(ObjectInfo {
+                size: head.content_length().unwrap_or(0).max(0) as u64,
+                sha256: head.checksum_sha256().and_then(full_object_sha256),
+            })),
+            Err(e) if is_not_found(&e) => Ok(None),
+            Err(e) => Err(StorageError::AwsSdk(e.to_string())),
+        }
+    }
 
-        // Ensure parent directory exists
-        if let Some(parent) = dest.parent() {
-            tokio::fs::create_dir_all(parent).await?;
+    async fn read_manifest(&self) -> Result<Option<ObjectManifest>, StorageError> {
+        let key = manifest_key(self.config.prefix.as_deref());
+
+        // The manifest describes the primary bucket, not a read mirror
+        match self.get_small(&key).await? {
+            Some((content, _)) => ObjectManifest::parse(&content).map(Some),
+            None => Ok(None),
         }
+    }
 
-        // Stream body to file (avoids loading entire object into memory)
-        let mut body_stream = response.body.into_async_read();
-        let mut file = File::create(dest).await?;
-        let size = tokio::io::copy(&mut body_stream, &mut file).await?;
-        file.flush().await?;
+    async fn write_manifest(&self, manifest: &ObjectManifest) -> Result<(), StorageError> {
+        let key = manifest_key(self.config.prefix.as_deref());
 
-        Ok(DownloadResult {
-            oid: oid.to_string(),
-            size,
-            path: dest.to_path_buf(),
-        })
+        self.client
+            .put_object()
+            .bucket(&self.config.bucket)
+            .key(&key)
+            .body(ByteStream::from(manifest.to_json()))
+            .content_type("application/json")
+            .cache_control("no-cache")
+            .send()
+            .await
+            .map_err(|e| StorageError::AwsSdk(e.to_string()))?;
+
+        Ok(())
     }
 
-    async fn exists(&self, oid: &str) -> Result<bool, StorageError> {
-        let key = self.object_key(oid);
+    async fn read_lock(&self, path: &str) -> Result<Option<Lock>, StorageError> {
+        self.get_lock(&lock_key(self.config.prefix.as_deref(), path)).await
+    }
 
-        match self
+    async fn create_lock(&self, lock: &Lock) -> Result<bool, StorageError> {
+        let key = lock_key(self.config.prefix.as_deref(), &lock.path);
+
+        // Checked first so a backend that ignores If-None-Match still can't
+        // overwrite a lock someone already holds
+        if self.get_small(&key).await?.is_some() {
+            return Ok(false);
+        }
+
+        // If-None-Match makes S3 refuse the write when the lock exists, so
+        // two people taking a lock at once can't both succeed
+        let result = self
             .client
-            .head_object()
+            .put_object()
             .bucket(&self.config.bucket)
             .key(&key)
+            .body(ByteStream::from(lock.to_json()))
+            .content_type("application/json")
+            .cache_control("no-cache")
+            .if_none_match("*")
             .send()
-            .await
-        {
+            .await;
+
+        match result {
+            Ok(_) => {}
+            // 412 when the lock exists, 409 when a concurrent write won
+            Err(e) if matches!(e.raw_response().map(|r| r.status().as_u16()), Some(409 | 412)) => return Ok(false),
+            Err(e) => return Err(StorageError::AwsSdk(e.to_string())),
+        }
+
+        // A backend that ignores If-None-Match lets a concurrent write replace
+        // ours. Read it back so that shows up instead of two people holding it.
+        match self.get_lock(&key).await? {
+            Some(stored) if stored.owner == lock.owner && stored.locked_at == lock.locked_at => Ok(true),
+            _ => Err(StorageError::Config(format!(
+                "the lock on {} was overwritten while being taken; this storage doesn't enforce conditional writes (If-None-Match), so locks aren't safe on it",
+                lock.path
+            ))),
+        }
+    }
+
+    async fn delete_lock(&self, lock: &Lock) -> Result<bool, StorageError> {
+        let key = lock_key(self.config.prefix.as_deref(), &lock.path);
+
+        // If-Match deletes only the lock that was read, not one taken since
+        let result = self
+            .client
+            .delete_object()
+            .bucket(&self.config.bucket)
+            .key(&key)
+            .set_if_match(lock.etag.clone())
+            .send()
+            .await;
+
+        match result {
             Ok(_) => Ok(true),
-            Err(e) => {
-                let err_str = e.to_string();
-                if err_str.contains("NotFound") || err_str.contains("404") {
-                    Ok(false)
-                } else {
-                    Err(StorageError::AwsSdk(err_str))
-                }
+            Err(e) if e.raw_response().is_some_and(|r| r.status().as_u16() == 412) => Ok(false),
+            // Already released by someone else
+            Err(e) if is_not_found(&e) => Ok(true),
+            Err(e) => Err(StorageError::AwsSdk(e.to_string())),
+        }
+    }
+
+    async fn list_locks(&self) -> Result<Vec<Lock>, StorageError> {
+        let prefix = locks_prefix(self.config.prefix.as_deref());
+        let mut keys = Vec::new();
+        let mut continuation = None;
+
+        loop {
+            let response = self
+                .client
+                .list_objects_v2()
+                .bucket(&self.config.bucket)
+                .prefix(&prefix)
+                .set_continuation_token(continuation)
+                .send()
+                .await
+                .map_err(|e| StorageError::AwsSdk(e.to_string()))?;
+
+            keys.extend(response.contents().iter().filter_map(|object| object.key().map(String::from)));
+            match response.next_continuation_token() {
+                Some(token) => continuation = Some(token.to_string()),
+                None => break,
+            }
+        }
+
+        let mut locks = Vec::new();
+        for key in keys.iter().filter(|key| key.ends_with(".json")) {
+            // Released between listing and reading
+            if let Some(lock) = self.get_lock(key).await? {
+                locks.push(lock);
             }
         }
+        locks.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(locks)
     }
 
     async fn delete(&self, oid: &str) -> Result<(), StorageError> {
@@ -200,14 +786,171 @@ impl Storage for S3Storage {
     }
 }
 
+/// The `x-amz-checksum-sha256` value for an object, derived from its OID.
+/// Sending it lets S3 reject an upload whose bytes don't hash to the OID.
+fn oid_checksum(oid: &str) -> Option<String> {
+    if oid.len() != 64 {
+        return None;
+    }
+    let bytes = (0..oid.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(oid.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    Some(base64::engine::general_purpose::STANDARD.encode(bytes))
+}
+
+/// Whether S3 answered 404. The error's message is just "service error",
+/// and HEAD responses carry no body naming the code, so go by the status.
+fn is_not_found<E>(err: &SdkError<E>) -> bool {
+    err.raw_response().is_some_and(|r| r.status().as_u16() == 404)
+}
+
+/// Whether S3 answered 416: a range request started at or past the end
+fn is_range_not_satisfiable<E>(err: &SdkError<E>) -> bool {
+    err.raw_response().is_some_and(|r| r.status().as_u16() == 416)
+}
+
+/// Fail if S3 reports a full-object checksum other than the OID
+fn check_stored_checksum(oid: &str, checksum: Option<&str>) -> Result<(), StorageError> {
+    match checksum.and_then(full_object_sha256) {
+        Some(stored) if stored != oid => Err(StorageError::Verification(format!(
+            "stored checksum {} does not match OID {}",
+            stored, oid
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Key for an object under `prefix`. Hash characters become directories
+/// for better S3 performance, in gg's or git-lfs's arrangement.
+fn object_key(prefix: Option<&str>, layout: KeyLayout, oid: &str) -> String {
+    let shard = |range: std::ops::Range<usize>| oid.get(range).unwrap_or_default();
+    let key = match layout {
+        KeyLayout::Gg => format!("{}/{}", shard(0..2), oid),
+        KeyLayout::GitLfs => format!("objects/{}/{}/{}", shard(0..2), shard(2..4), oid),
+    };
+
+    match prefix {
+        Some(p) => format!("{}/{}", p.trim_end_matches('/'), key),
+        None => key,
+    }
+}
+
+/// Key for the object manifest, directly under `prefix`
+fn manifest_key(prefix: Option<&str>) -> String {
+    match prefix {
+        Some(p) => format!("{}/{}", p.trim_end_matches('/'), MANIFEST_NAME),
+        None => MANIFEST_NAME.to_string(),
+    }
+}
+
+/// Key prefix shared by all locks, `LOCKS_DIR` under `prefix`
+fn locks_prefix(prefix: Option<&str>) -> String {
+    match prefix {
+        Some(p) => format!("{}/{}/", p.trim_end_matches('/'), LOCKS_DIR),
+        None => format!("{}/", LOCKS_DIR),
+    }
+}
+
+/// Key for the lock on `path`
+fn lock_key(prefix: Option<&str>, path: &str) -> String {
+    format!("{}{}", locks_prefix(prefix), Lock::object_name(path))
+}
+
+/// Convert an S3 `x-amz-checksum-sha256` value to hex. Multipart uploads
+/// report a checksum of part checksums ("...-N"), which isn't the OID.
+fn full_object_sha256(checksum: &str) -> Option<String> {
+    if checksum.contains('-') {
+        return None;
+    }
+    let bytes = base64::engine::general_purpose::STANDARD.decode(checksum).ok()?;
+    Some(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     #[test]
     fn test_object_key_no_prefix() {
-        // Can't easily test without async, but we can verify the key format logic
         let oid = "4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393";
-        let prefix = &oid[..2];
-        let expected = format!("{}/{}", prefix, oid);
-        assert_eq!(expected, "4d/4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393");
+        assert_eq!(
+            object_key(None, KeyLayout::Gg, oid),
+            "4d/4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393"
+        );
+    }
+
+    #[test]
+    fn test_object_key_layouts_with_prefix() {
+        let oid = "4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393";
+        assert_eq!(
+            object_key(Some("project/"), KeyLayout::Gg, oid),
+            format!("project/4d/{}", oid)
+        );
+        assert_eq!(
+            object_key(Some("project"), KeyLayout::GitLfs, oid),
+            format!("project/objects/4d/7a/{}", oid)
+        );
+        assert_eq!(object_key(None, KeyLayout::GitLfs, oid), format!("objects/4d/7a/{}", oid));
+    }
+
+    #[test]
+    fn test_lock_key() {
+        let name = Lock::object_name("art/hero.psd");
+        assert_eq!(lock_key(None, "art/hero.psd"), format!("locks/{}", name));
+        assert_eq!(lock_key(Some("project/"), "art/hero.psd"), format!("project/locks/{}", name));
+    }
+
+    #[test]
+    fn test_manifest_key() {
+        assert_eq!(manifest_key(None), "manifest.json");
+        assert_eq!(manifest_key(Some("project/")), "project/manifest.json");
+    }
+
+    #[test]
+    fn test_read_bucket_defaults_to_bucket() {
+        let config: crate::lfs::LfsConfig = toml::from_str("[storage]\nbucket = \"primary\"\n").unwrap();
+        let mut s3 = super::super::s3_config(&config);
+        assert_eq!(s3.read_bucket(), "primary");
+
+        s3.read_bucket = Some("mirror".to_string());
+        assert_eq!(s3.read_bucket(), "mirror");
+        assert_eq!(s3.bucket, "primary");
+    }
+
+    #[test]
+    fn test_full_object_sha256() {
+        // base64 of sha256("hello")
+        assert_eq!(
+            full_object_sha256("LPJNul+wow4m6DsqxbninhsWHlwfp0JecwQzYpOLmCQ=").as_deref(),
+            Some("2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824")
+        );
+        assert_eq!(full_object_sha256("LPJNul+wow4m6DsqxbninhsWHlwfp0JecwQzYpOLmCQ=-3"), None);
+        assert_eq!(full_object_sha256("not base64!"), None);
+    }
+
+    #[test]
+    fn test_oid_checksum_round_trips() {
+        let oid = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        let checksum = oid_checksum(oid).unwrap();
+        assert_eq!(checksum, "LPJNul+wow4m6DsqxbninhsWHlwfp0JecwQzYpOLmCQ=");
+        assert_eq!(full_object_sha256(&checksum).as_deref(), Some(oid));
+        assert_eq!(oid_checksum("abc"), None);
+        assert_eq!(oid_checksum(&"zz".repeat(32)), None);
+    }
+
+    #[test]
+    fn test_check_stored_checksum() {
+        let oid = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        let checksum = oid_checksum(oid).unwrap();
+        assert!(check_stored_checksum(oid, Some(&checksum)).is_ok());
+        assert!(check_stored_checksum(oid, None).is_ok());
+        assert!(check_stored_checksum(oid, Some("AAAA-2")).is_ok());
+
+        let other = oid_checksum(&"0".repeat(64)).unwrap();
+        assert!(matches!(
+            check_stored_checksum(oid, Some(&other)),
+            Err(StorageError::Verification(_))
+        ));
     }
 }
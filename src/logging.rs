@@ -0,0 +1,149 @@
+//! Leveled diagnostics on stderr
+//!
+//! Warnings and debug output from commands that git runs on our behalf
+//! (the filters) can't be controlled with flags, so the level is also read
+//! from `GG_LOG`, then `RUST_LOG`. `-v`/`-vv`/`-vvv` and `--quiet` take
+//! precedence over the environment.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Set once from the global flags and environment; warnings are shown by default
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Warn as u8);
+
+/// How much diagnostic output to print, from least to most
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    /// Parse a level name like `debug`, case-insensitively
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "off" => Some(Self::Off),
+            "error" => Some(Self::Error),
+            "warn" | "warning" => Some(Self::Warn),
+            "info" => Some(Self::Info),
+            "debug" => Some(Self::Debug),
+            "trace" => Some(Self::Trace),
+            _ => None,
+        }
+    }
+
+    /// Parse a `RUST_LOG`-style filter, using the last bare level or
+    /// `gg=`/`git_gud=` directive. Directives for other crates are ignored.
+    pub fn from_filter(filter: &str) -> Option<Self> {
+        filter
+            .split(',')
+            .rev()
+            .find_map(|directive| match directive.split_once('=') {
+                Some((target, level)) if matches!(target.trim(), "gg" | "git_gud") => Self::parse(level),
+                Some(_) => None,
+                None => Self::parse(directive),
+            })
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Self::Off,
+            1 => Self::Error,
+            2 => Self::Warn,
+            3 => Self::Info,
+            4 => Self::Debug,
+            _ => Self::Trace,
+        }
+    }
+}
+
+/// Pick the level from the number of `-v` flags, `--quiet` and the environment
+pub fn resolve(verbose: u8, quiet: bool, gg_log: Option<&str>, rust_log: Option<&str>) -> Level {
+    match verbose {
+        0 if quiet => Level::Error,
+        0 => gg_log
+            .and_then(Level::from_filter)
+            .or_else(|| rust_log.and_then(Level::from_filter))
+            .unwrap_or(Level::Warn),
+        1 => Level::Info,
+        2 => Level::Debug,
+        _ => Level::Trace,
+    }
+}
+
+/// Set the level for the rest of the process. Call this early in main().
+pub fn init(verbose: u8, quiet: bool) {
+    let gg_log = std::env::var("GG_LOG").ok();
+    let rust_log = std::env::var("RUST_LOG").ok();
+    set_level(resolve(verbose, quiet, gg_log.as_deref(), rust_log.as_deref()));
+}
+
+/// Override the level, e.g. from tests
+pub fn set_level(level: Level) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// The current level
+pub fn level() -> Level {
+    Level::from_u8(LEVEL.load(Ordering::Relaxed))
+}
+
+/// Check if messages at `level` should be printed
+pub fn enabled(level: Level) -> bool {
+    level != Level::Off && level <= self::level()
+}
+
+/// Print an error to stderr as `<source>: error: <message>`
+macro_rules! log_error {
+    ($source:expr, $($arg:tt)+) => {
+        if $crate::logging::enabled($crate::logging::Level::Error) {
+            eprintln!("{}: error: {}", $source, format_args!($($arg)+));
+        }
+    };
+}
+
+/// Print a warning to stderr as `<source>: warning: <message>`
+macro_rules! log_warn {
+    ($source:expr, $($arg:tt)+) => {
+        if $crate::logging::enabled($crate::logging::Level::Warn) {
+            eprintln!("{}: warning: {}", $source, format_args!($($arg)+));
+        }
+    };
+}
+
+/// Print a debug message to stderr, shown with `-vv` or `GG_LOG=debug`
+macro_rules! log_debug {
+    ($source:expr, $($arg:tt)+) => {
+        if $crate::logging::enabled($crate::logging::Level::Debug) {
+            eprintln!("{}: debug: {}", $source, format_args!($($arg)+));
+        }
+    };
+}
+
+pub(crate) use {log_debug, log_error, log_warn};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_from_filter() {
+        assert_eq!(Level::from_filter("debug"), Some(Level::Debug));
+        assert_eq!(Level::from_filter("hyper=trace,gg=info"), Some(Level::Info));
+        assert_eq!(Level::from_filter("aws_config=debug"), None);
+        assert_eq!(Level::from_filter("nonsense"), None);
+    }
+
+    #[test]
+    fn test_resolve_precedence() {
+        assert_eq!(resolve(0, false, None, None), Level::Warn);
+        assert_eq!(resolve(0, false, Some("error"), Some("trace")), Level::Error);
+        assert_eq!(resolve(0, false, None, Some("debug")), Level::Debug);
+        assert_eq!(resolve(2, false, Some("off"), None), Level::Debug);
+        assert_eq!(resolve(0, true, Some("debug"), None), Level::Error);
+        assert_eq!(resolve(5, false, None, None), Level::Trace);
+    }
+}
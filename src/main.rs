@@ -4,6 +4,7 @@ mod commands;
 mod config;
 mod git;
 mod lfs;
+mod logging;
 mod utils;
 
 #[derive(Parser)]
@@ -12,6 +13,18 @@ struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
 
+    /// When to use colors
+    #[arg(long, global = true, value_enum, value_name = "WHEN", default_value_t = config::ColorChoice::Auto)]
+    color: config::ColorChoice,
+
+    /// Suppress informational output (errors and summaries are still shown)
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Show more detail and diagnostics on stderr (-vv for debug, -vvv for trace)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count, conflicts_with = "quiet")]
+    verbose: u8,
+
     /// Arguments passed to git when no subcommand matches
     #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
     args: Vec<String>,
@@ -30,6 +43,9 @@ enum Commands {
     /// Sync branch with main/master
     Sync(commands::SyncArgs),
 
+    /// Clone a repository and pull its LFS files
+    Clone(commands::CloneArgs),
+
     /// Quick commit: stage and commit in one step
     #[command(visible_alias = "qc")]
     QuickCommit(commands::QuickCommitArgs),
@@ -63,15 +79,18 @@ enum Commands {
 }
 
 fn main() {
-    // Set up colors based on terminal/environment
-    config::setup_colors();
-
     let cli = Cli::parse();
 
+    // Set up colors based on --color, terminal and environment
+    config::setup_colors(cli.color);
+    config::set_quiet(cli.quiet);
+    logging::init(cli.verbose, cli.quiet);
+
     let exit_code = match cli.command {
         Some(Commands::Status(args)) => commands::status::run(args),
         Some(Commands::Push(args)) => commands::push::run(args),
         Some(Commands::Sync(args)) => commands::sync::run(args),
+        Some(Commands::Clone(args)) => commands::clone::run(args),
         Some(Commands::QuickCommit(args)) => commands::quick_commit::run(args),
         Some(Commands::Amend(args)) => commands::amend::run(args),
         Some(Commands::Undo(args)) => commands::undo::run(args),
@@ -88,7 +107,7 @@ fn main() {
         }
         None => {
             // Unknown command: pass through to git with full colors
-            git::passthrough(&cli.args)
+            git::forward(&cli.args)
         }
     };
 
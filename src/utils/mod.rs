@@ -1,3 +1,5 @@
 pub mod repo;
+pub mod time;
 
 pub use repo::*;
+pub use time::*;
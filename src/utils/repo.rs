@@ -1,10 +1,50 @@
 use git2::{BranchType, Repository};
+use std::path::PathBuf;
 
 /// Open the repository from the current directory (or any parent).
 pub fn get_repo() -> Result<Repository, git2::Error> {
     Repository::discover(".")
 }
 
+/// Open the repository from the current directory along with its working-tree root.
+pub fn get_repo_with_root() -> Result<(Repository, PathBuf), git2::Error> {
+    let repo = get_repo()?;
+    let root = workdir_root(&repo)?;
+    Ok((repo, root))
+}
+
+/// Get the working-tree root of the repository containing the current directory.
+pub fn repo_root() -> Result<PathBuf, git2::Error> {
+    get_repo_with_root().map(|(_, root)| root)
+}
+
+/// Get the canonical working-tree root of a repository.
+/// Canonicalizing keeps paths built from it comparable with `current_dir()`
+/// and directory walks when the repo is reached through a symlink. In a
+/// linked worktree this is the worktree, not the main checkout.
+/// A bare repository (e.g. a server-side mirror) is rejected with an error
+/// that says so, since there are no files to filter or place.
+pub fn workdir_root(repo: &Repository) -> Result<PathBuf, git2::Error> {
+    let workdir = repo.workdir().ok_or_else(|| {
+        git2::Error::from_str(&format!(
+            "gg lfs requires a working tree; bare repositories aren't supported for this command ({})",
+            repo.path().display()
+        ))
+    })?;
+    Ok(workdir.canonicalize().unwrap_or_else(|_| workdir.to_path_buf()))
+}
+
+/// Get the git directory shared by all worktrees, e.g. for `lfs/objects`.
+/// A linked worktree's own git dir (`.git/worktrees/<name>`) names it in a
+/// `commondir` file.
+pub fn common_dir(repo: &Repository) -> PathBuf {
+    let git_dir = repo.path();
+    match std::fs::read_to_string(git_dir.join("commondir")) {
+        Ok(content) => git_dir.join(content.trim()),
+        Err(_) => git_dir.to_path_buf(),
+    }
+}
+
 /// Get the current branch name.
 pub fn get_branch_name(repo: &Repository) -> Option<String> {
     repo.head().ok()?.shorthand().map(String::from)
@@ -15,13 +55,28 @@ pub fn is_main_branch(branch: &str) -> bool {
     matches!(branch, "main" | "master")
 }
 
-/// Get the name of the main branch (prefers "main" over "master").
-pub fn get_main_branch_name(repo: &Repository) -> &'static str {
-    if repo.find_branch("main", BranchType::Local).is_ok() {
-        "main"
-    } else {
-        "master"
+/// Get the name of the main branch.
+/// Uses the remote default branch (`origin/HEAD`) when known, then falls back
+/// to the first local branch named "main", "master" or "develop".
+pub fn get_main_branch_name(repo: &Repository) -> String {
+    if let Some(name) = get_remote_default_branch(repo) {
+        return name;
     }
+
+    ["main", "master", "develop"]
+        .into_iter()
+        .find(|name| repo.find_branch(name, BranchType::Local).is_ok())
+        .unwrap_or("master")
+        .to_string()
+}
+
+/// Get the branch `origin/HEAD` points to, if the remote has one set.
+pub fn get_remote_default_branch(repo: &Repository) -> Option<String> {
+    let reference = repo.find_reference("refs/remotes/origin/HEAD").ok()?;
+    let target = reference.symbolic_target()?;
+    target
+        .strip_prefix("refs/remotes/origin/")
+        .map(String::from)
 }
 
 #[cfg(test)]
@@ -48,6 +103,38 @@ mod tests {
         assert!(!is_main_branch("develop"));
     }
 
+    fn init_repo_with_branch(dir: &std::path::Path, branch: &str) -> Repository {
+        let repo = Repository::init(dir).unwrap();
+        {
+            let sig = git2::Signature::now("Test", "test@example.com").unwrap();
+            let tree_id = repo.index().unwrap().write_tree().unwrap();
+            let tree = repo.find_tree(tree_id).unwrap();
+            let refname = format!("refs/heads/{}", branch);
+            repo.commit(Some(&refname), &sig, &sig, "init", &tree, &[]).unwrap();
+            repo.set_head(&refname).unwrap();
+        }
+        repo
+    }
+
+    #[test]
+    fn test_get_main_branch_name_develop() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let repo = init_repo_with_branch(temp.path(), "develop");
+        assert_eq!(get_main_branch_name(&repo), "develop");
+    }
+
+    #[test]
+    fn test_get_main_branch_name_prefers_origin_head() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let repo = init_repo_with_branch(temp.path(), "main");
+        let head = repo.head().unwrap().target().unwrap();
+        repo.reference("refs/remotes/origin/trunk", head, true, "test").unwrap();
+        repo.reference_symbolic("refs/remotes/origin/HEAD", "refs/remotes/origin/trunk", true, "test")
+            .unwrap();
+
+        assert_eq!(get_main_branch_name(&repo), "trunk");
+    }
+
     #[test]
     fn test_get_repo_in_git_dir() {
         // This test runs from within the git-gud repo itself
@@ -61,4 +148,55 @@ mod tests {
         let branch = get_branch_name(&repo);
         assert!(branch.is_some());
     }
+
+    #[test]
+    fn test_workdir_root_from_nested_directory() {
+        let temp = tempfile::TempDir::new().unwrap();
+        Repository::init(temp.path()).unwrap();
+        let nested = temp.path().join("a").join("b");
+        std::fs::create_dir_all(&nested).unwrap();
+
+        let repo = Repository::discover(&nested).unwrap();
+        assert_eq!(workdir_root(&repo).unwrap(), temp.path().canonicalize().unwrap());
+        assert_eq!(common_dir(&repo), repo.path());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_workdir_root_is_canonical_through_symlink() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let real = temp.path().join("real");
+        std::fs::create_dir_all(real.join("assets")).unwrap();
+        Repository::init(&real).unwrap();
+        let link = temp.path().join("link");
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+
+        let repo = Repository::discover(link.join("assets")).unwrap();
+        assert_eq!(workdir_root(&repo).unwrap(), real.canonicalize().unwrap());
+    }
+
+    #[test]
+    fn test_workdir_root_in_linked_worktree() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let main = temp.path().join("main");
+        let repo = init_repo_with_branch(&main, "main");
+        let linked = temp.path().join("linked");
+        repo.worktree("linked", &linked, None).unwrap();
+        assert!(linked.join(".git").is_file());
+
+        let worktree_repo = Repository::discover(&linked).unwrap();
+        assert_eq!(workdir_root(&worktree_repo).unwrap(), linked.canonicalize().unwrap());
+        assert_eq!(
+            common_dir(&worktree_repo).canonicalize().unwrap(),
+            main.join(".git").canonicalize().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_workdir_root_rejects_bare_repo() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let repo = Repository::init_bare(temp.path()).unwrap();
+        let err = workdir_root(&repo).unwrap_err();
+        assert!(err.message().contains("bare repositories aren't supported"), "Got: {}", err);
+    }
 }
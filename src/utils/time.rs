@@ -0,0 +1,50 @@
+/// Current time in seconds since the Unix epoch
+pub fn unix_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Format an age in seconds as "5 minutes ago", "3 days ago", etc.
+pub fn format_relative_time(seconds: i64) -> String {
+    const MINUTE: i64 = 60;
+    const HOUR: i64 = 60 * MINUTE;
+    const DAY: i64 = 24 * HOUR;
+    const WEEK: i64 = 7 * DAY;
+    const MONTH: i64 = 30 * DAY;
+    const YEAR: i64 = 365 * DAY;
+
+    let (value, unit) = match seconds {
+        s if s < MINUTE => return "just now".to_string(),
+        s if s < HOUR => (s / MINUTE, "minute"),
+        s if s < DAY => (s / HOUR, "hour"),
+        s if s < WEEK => (s / DAY, "day"),
+        s if s < MONTH => (s / WEEK, "week"),
+        s if s < YEAR => (s / MONTH, "month"),
+        s => (s / YEAR, "year"),
+    };
+
+    if value == 1 {
+        format!("1 {} ago", unit)
+    } else {
+        format!("{} {}s ago", value, unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_relative_time() {
+        assert_eq!(format_relative_time(10), "just now");
+        assert_eq!(format_relative_time(60), "1 minute ago");
+        assert_eq!(format_relative_time(5 * 60), "5 minutes ago");
+        assert_eq!(format_relative_time(3 * 3600), "3 hours ago");
+        assert_eq!(format_relative_time(2 * 86400), "2 days ago");
+        assert_eq!(format_relative_time(14 * 86400), "2 weeks ago");
+        assert_eq!(format_relative_time(60 * 86400), "2 months ago");
+        assert_eq!(format_relative_time(400 * 86400), "1 year ago");
+    }
+}
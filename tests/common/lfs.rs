@@ -0,0 +1,60 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// sha256("hello"), the object most LFS tests store.
+pub const HELLO_OID: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+/// The pointer text git-lfs writes for an object.
+pub fn pointer(oid: &str, size: u64) -> String {
+    format!("version https://git-lfs.github.com/spec/v1\noid sha256:{}\nsize {}\n", oid, size)
+}
+
+/// The pointer for "hello".
+pub fn hello_pointer() -> String {
+    pointer(HELLO_OID, 5)
+}
+
+/// Write an object into the cache directory `cache_dir` (as set by
+/// `GG_LFS_CACHE_DIR` or `[cache] dir`), returning its path.
+pub fn cache_object(cache_dir: &Path, oid: &str, content: &str) -> PathBuf {
+    let path = cache_dir.join(&oid[..2]).join(oid);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, content).unwrap();
+    path
+}
+
+/// A private object cache, so tests leave the real one alone. Pass
+/// `home()` to gg as `XDG_CACHE_HOME`.
+pub struct LfsCache {
+    home: TempDir,
+}
+
+impl LfsCache {
+    /// An empty cache.
+    pub fn new() -> Self {
+        Self { home: TempDir::new().expect("Failed to create cache directory") }
+    }
+
+    /// A cache holding "hello".
+    pub fn with_hello() -> Self {
+        let cache = Self::new();
+        cache.add(HELLO_OID, "hello");
+        cache
+    }
+
+    /// The value for `XDG_CACHE_HOME`.
+    pub fn home(&self) -> &Path {
+        self.home.path()
+    }
+
+    /// Where the cache keeps `oid`.
+    pub fn object_path(&self, oid: &str) -> PathBuf {
+        self.home().join("gg-lfs").join(&oid[..2]).join(oid)
+    }
+
+    /// Store `content` as `oid`, which needn't be its hash.
+    pub fn add(&self, oid: &str, content: &str) -> PathBuf {
+        cache_object(&self.home().join("gg-lfs"), oid, content)
+    }
+}
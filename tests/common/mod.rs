@@ -1,3 +1,7 @@
+pub mod lfs;
+pub mod s3_server;
 pub mod temp_repo;
 
+pub use lfs::LfsCache;
+pub use s3_server::S3Server;
 pub use temp_repo::TempRepo;
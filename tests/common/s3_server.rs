@@ -0,0 +1,109 @@
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Bucket created on every server
+pub const BUCKET: &str = "gg-test";
+
+/// A local S3 server (moto) for tests that need real storage.
+/// Stopped when dropped.
+pub struct S3Server {
+    child: Child,
+    pub endpoint: String,
+}
+
+impl S3Server {
+    /// Start a server with an empty `BUCKET`, or None when `moto_server`
+    /// isn't installed. Tests skip themselves in that case.
+    pub fn start() -> Option<Self> {
+        let port = TcpListener::bind("127.0.0.1:0").ok()?.local_addr().ok()?.port();
+        let child = Command::new("moto_server")
+            .args(["-p", &port.to_string()])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        let server = Self { child, endpoint: format!("http://127.0.0.1:{}", port) };
+
+        let started = Instant::now();
+        while TcpStream::connect(("127.0.0.1", port)).is_err() {
+            if started.elapsed() > Duration::from_secs(20) {
+                return None;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        server.request("PUT", &format!("/{}", BUCKET), b"");
+        Some(server)
+    }
+
+    /// `[storage]` config for `BUCKET` on this server
+    pub fn lfs_config(&self) -> String {
+        format!(
+            "[storage]\nbucket = \"{}\"\nregion = \"us-east-1\"\nendpoint = \"{}\"\nforce_path_style = true\n\n\
+             [storage.credentials]\naccess_key_id = \"test\"\nsecret_access_key = \"test\"\n",
+            BUCKET, self.endpoint
+        )
+    }
+
+    /// Keys of every object in `BUCKET`
+    pub fn keys(&self) -> Vec<String> {
+        let (_, body) = self.request("GET", &format!("/{}?list-type=2", BUCKET), b"");
+        let body = String::from_utf8_lossy(&body);
+        body.split("<Key>")
+            .skip(1)
+            .filter_map(|rest| rest.split("</Key>").next())
+            .map(String::from)
+            .collect()
+    }
+
+    /// Whether an object for `oid` is stored, under any key layout
+    pub fn has_object(&self, oid: &str) -> bool {
+        self.keys().iter().any(|key| key.ends_with(oid))
+    }
+
+    /// Whether `bucket` exists on this server
+    pub fn has_bucket(&self, bucket: &str) -> bool {
+        self.request("HEAD", &format!("/{}", bucket), b"").0 == 200
+    }
+
+    /// Store `content` under `key` directly, bypassing gg
+    pub fn put(&self, key: &str, content: &[u8]) {
+        let (status, _) = self.request("PUT", &format!("/{}/{}", BUCKET, key), content);
+        assert_eq!(status, 200, "PUT {} failed", key);
+    }
+
+    /// Send an unsigned request; moto doesn't check signatures.
+    /// Returns the status code and body.
+    fn request(&self, method: &str, path: &str, body: &[u8]) -> (u16, Vec<u8>) {
+        let host = self.endpoint.trim_start_matches("http://");
+        let mut stream = TcpStream::connect(host).expect("Failed to connect to S3 server");
+        let head = format!(
+            "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            method,
+            path,
+            host,
+            body.len()
+        );
+        stream.write_all(head.as_bytes()).unwrap();
+        stream.write_all(body).unwrap();
+
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).unwrap();
+        let split = response.windows(4).position(|w| w == b"\r\n\r\n").unwrap_or(response.len());
+        let status = String::from_utf8_lossy(&response[..split])
+            .split_whitespace()
+            .nth(1)
+            .and_then(|code| code.parse().ok())
+            .unwrap_or(0);
+        let body = response.get(split + 4..).unwrap_or_default().to_vec();
+        (status, body)
+    }
+}
+
+impl Drop for S3Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -2,6 +2,8 @@ use std::path::PathBuf;
 use std::process::{Command, Output};
 use tempfile::TempDir;
 
+use super::lfs::{hello_pointer, LfsCache};
+
 /// A temporary git repository for testing.
 /// Automatically cleaned up when dropped.
 pub struct TempRepo {
@@ -182,6 +184,28 @@ impl TempRepo {
     pub fn stage_all(&self) {
         self.run_git(&["add", "-A"]);
     }
+
+    /// Install gg lfs, commit a pointer for "hello" at `path` and cache its
+    /// object. Returns the refs before and after the commit, and the cache.
+    pub fn commit_cached_pointer(&self, path: &str) -> (String, String, LfsCache) {
+        self.gg(&["lfs", "install"]);
+        self.gg(&["lfs", "track", "*.bin"]);
+        self.commit("Track bins");
+        let old_head = self.git_output(&["rev-parse", "HEAD"]);
+
+        self.create_file(path, &hello_pointer());
+        self.stage_pointers();
+        self.run_git(&["commit", "-m", "Add pointer"]);
+        let new_head = self.git_output(&["rev-parse", "HEAD"]);
+
+        (old_head, new_head, LfsCache::with_hello())
+    }
+
+    /// Stage everything, keeping pointer files as they are instead of
+    /// running them through the clean filter.
+    pub fn stage_pointers(&self) {
+        self.run_git(&["-c", "filter.gg-lfs.clean=cat", "-c", "filter.gg-lfs.smudge=cat", "add", "."]);
+    }
 }
 
 impl Default for TempRepo {
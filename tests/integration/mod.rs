@@ -3,6 +3,7 @@ mod test_push;
 mod test_sync;
 mod test_quick_commit;
 mod test_amend;
+mod test_clone;
 mod test_undo;
 mod test_pr;
 mod test_clean_branches;
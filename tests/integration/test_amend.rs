@@ -98,3 +98,30 @@ fn amend_no_commits_fails() {
     // Should fail because there's nothing to amend
     assert_ne!(output.status.code().unwrap(), 0);
 }
+
+#[test]
+fn amend_reword_changes_only_message() {
+    let repo = TempRepo::new();
+    let initial_count = repo.commit_count();
+
+    repo.create_file("staged.txt", "staged content");
+    repo.stage_all();
+
+    let (code, _, _) = repo.gg(&["amend", "--reword", "Better message"]);
+
+    assert_eq!(code, 0);
+    assert_eq!(repo.last_commit_message(), "Better message");
+    assert_eq!(repo.commit_count(), initial_count);
+    // The staged file stays staged, not folded into the commit
+    assert!(repo.has_staged_changes());
+}
+
+#[test]
+fn amend_reword_conflicts_with_all() {
+    let repo = TempRepo::new();
+
+    let (code, _, stderr) = repo.gg(&["amend", "--all", "--reword", "msg"]);
+
+    assert_ne!(code, 0);
+    assert!(stderr.contains("cannot be used with"));
+}
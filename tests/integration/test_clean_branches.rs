@@ -95,3 +95,122 @@ fn clean_branches_protects_main() {
         branches
     );
 }
+
+#[test]
+fn clean_branches_gone_lists_branches_with_deleted_upstream() {
+    let repo = TempRepo::with_remote();
+
+    repo.checkout_new_branch("gone-branch");
+    repo.create_file("gone.txt", "content");
+    repo.commit("Gone commit");
+    repo.run_git(&["push", "-u", "origin", "gone-branch"]);
+    repo.checkout("main");
+    repo.run_git(&["push", "origin", "--delete", "gone-branch"]);
+
+    repo.checkout_new_branch("local-only");
+    repo.checkout("main");
+
+    let (code, stdout, _) = repo.gg(&["clean-branches", "--gone"]);
+
+    assert_eq!(code, 0);
+    assert!(stdout.contains("gone-branch"), "Expected gone branch, got: {}", stdout);
+    assert!(!stdout.contains("local-only"));
+    assert!(repo.branches().contains(&"gone-branch".to_string()));
+}
+
+#[test]
+fn clean_branches_gone_force_deletes_merged() {
+    let repo = TempRepo::with_remote();
+
+    repo.checkout_new_branch("gone-branch");
+    repo.create_file("gone.txt", "content");
+    repo.commit("Merged gone commit");
+    repo.run_git(&["push", "-u", "origin", "gone-branch"]);
+    repo.checkout("main");
+    repo.run_git(&["merge", "gone-branch"]);
+    repo.run_git(&["push", "origin", "--delete", "gone-branch"]);
+
+    let (code, _, _) = repo.gg(&["clean-branches", "--gone", "--force"]);
+
+    assert_eq!(code, 0);
+    assert!(!repo.branches().contains(&"gone-branch".to_string()));
+}
+
+#[test]
+fn clean_branches_gone_force_keeps_unmerged() {
+    let repo = TempRepo::with_remote();
+
+    repo.checkout_new_branch("gone-branch");
+    repo.create_file("gone.txt", "content");
+    repo.commit("Unmerged gone commit");
+    repo.run_git(&["push", "-u", "origin", "gone-branch"]);
+    repo.checkout("main");
+    repo.run_git(&["push", "origin", "--delete", "gone-branch"]);
+
+    let (code, _, stderr) = repo.gg(&["clean-branches", "--gone", "--force"]);
+
+    assert_eq!(code, 0);
+    assert!(repo.branches().contains(&"gone-branch".to_string()));
+    assert!(stderr.contains("Kept 1 branch(es) that aren't fully merged"), "Got: {}", stderr);
+    assert!(stderr.contains("git branch -D"), "Got: {}", stderr);
+}
+
+#[test]
+fn clean_branches_gone_none_found() {
+    let repo = TempRepo::with_remote();
+
+    let (code, stdout, _) = repo.gg(&["clean-branches", "--gone"]);
+
+    assert_eq!(code, 0);
+    assert!(stdout.contains("No gone branches"));
+}
+
+/// Delete a branch directly in the remote repo, leaving a stale remote-tracking ref locally
+fn delete_on_remote_only(repo: &TempRepo, branch: &str) {
+    let url = repo.git_output(&["remote", "get-url", "origin"]);
+    let remote_path = url.trim_start_matches("file://");
+    std::process::Command::new("git")
+        .args(["--git-dir", remote_path, "branch", "-D", branch])
+        .output()
+        .expect("Failed to delete remote branch");
+}
+
+#[test]
+fn clean_branches_remote_prunes_before_gone() {
+    let repo = TempRepo::with_remote();
+
+    repo.checkout_new_branch("stale-branch");
+    repo.create_file("stale.txt", "content");
+    repo.commit("Stale commit");
+    repo.run_git(&["push", "-u", "origin", "stale-branch"]);
+    repo.checkout("main");
+    delete_on_remote_only(&repo, "stale-branch");
+
+    // Without pruning the remote-tracking ref still exists, so nothing is gone
+    let (_, stdout, _) = repo.gg(&["clean-branches", "--gone"]);
+    assert!(stdout.contains("No gone branches"), "Got: {}", stdout);
+
+    let (code, stdout, _) = repo.gg(&["clean-branches", "--gone", "--remote"]);
+    assert_eq!(code, 0);
+    assert!(stdout.contains("git remote prune origin"));
+    assert!(stdout.contains("stale-branch"), "Got: {}", stdout);
+    assert!(!repo.git_output(&["branch", "-r"]).contains("origin/stale-branch"));
+}
+
+#[test]
+fn clean_branches_skips_current_gone_branch() {
+    let repo = TempRepo::with_remote();
+
+    repo.checkout_new_branch("current-gone");
+    repo.create_file("current.txt", "content");
+    repo.commit("Current commit");
+    repo.run_git(&["push", "-u", "origin", "current-gone"]);
+    repo.run_git(&["push", "origin", "--delete", "current-gone"]);
+
+    let (code, stdout, stderr) = repo.gg(&["clean-branches", "--gone", "--force"]);
+
+    assert_eq!(code, 0);
+    assert!(stderr.contains("current branch"), "Got: {}", stderr);
+    assert!(stdout.contains("No gone branches"));
+    assert!(repo.branches().contains(&"current-gone".to_string()));
+}
@@ -0,0 +1,71 @@
+use crate::common::TempRepo;
+
+#[test]
+fn clone_into_named_directory() {
+    let source = TempRepo::new();
+    let target = TempRepo::new();
+    let url = format!("file://{}", source.path.display());
+
+    let (code, _, _) = target.gg(&["clone", &url, "cloned"]);
+
+    assert_eq!(code, 0);
+    assert!(target.path.join("cloned").join("README.md").exists());
+}
+
+#[test]
+fn clone_derives_directory_from_url() {
+    let source = TempRepo::new();
+    let target = TempRepo::new();
+    let url = format!("file://{}", source.path.display());
+    let expected = source.path.file_name().unwrap().to_string_lossy().to_string();
+
+    let (code, _, _) = target.gg(&["clone", &url]);
+
+    assert_eq!(code, 0);
+    assert!(target.path.join(expected).join("README.md").exists());
+}
+
+#[test]
+fn clone_without_lfs_patterns_skips_install() {
+    let source = TempRepo::new();
+    let target = TempRepo::new();
+    let url = format!("file://{}", source.path.display());
+
+    let (code, stdout, _) = target.gg(&["clone", &url, "cloned"]);
+
+    assert_eq!(code, 0);
+    assert!(!stdout.contains("gg lfs install"));
+    assert!(!target.path.join("cloned").join(".gg").exists());
+}
+
+#[test]
+fn clone_installs_lfs_when_repo_tracks_patterns() {
+    let source = TempRepo::new();
+    source.create_file(".gitattributes", "*.psd filter=gg-lfs diff=gg-lfs merge=gg-lfs -text\n");
+    source.commit("Track psd files");
+    let target = TempRepo::new();
+    let url = format!("file://{}", source.path.display());
+
+    let (code, stdout, _) = target.gg(&["clone", &url, "cloned"]);
+
+    assert_eq!(code, 0);
+    assert!(stdout.contains("gg lfs install"));
+    let hooks_dir = target.path.join("cloned").join(".git").join("hooks");
+    assert!(hooks_dir.join("pre-push").exists());
+}
+
+#[test]
+fn clone_no_lfs_flag_skips_install() {
+    let source = TempRepo::new();
+    source.create_file(".gitattributes", "*.psd filter=gg-lfs diff=gg-lfs merge=gg-lfs -text\n");
+    source.commit("Track psd files");
+    let target = TempRepo::new();
+    let url = format!("file://{}", source.path.display());
+
+    let (code, stdout, _) = target.gg(&["clone", &url, "cloned", "--no-lfs"]);
+
+    assert_eq!(code, 0);
+    assert!(!stdout.contains("gg lfs install"));
+    let hooks_dir = target.path.join("cloned").join(".git").join("hooks");
+    assert!(!hooks_dir.join("pre-push").exists());
+}
@@ -147,6 +147,33 @@ fn passthrough_preserves_failure_exit_code() {
     assert_ne!(code, 0);
 }
 
+#[test]
+fn passthrough_preserves_exact_exit_code() {
+    let repo = TempRepo::new();
+
+    let (code, _, _) = repo.gg(&["-c", "alias.fail=!exit 3", "fail"]);
+
+    assert_eq!(code, 3);
+}
+
+#[cfg(unix)]
+#[test]
+fn passthrough_dies_from_the_signal_that_killed_git() {
+    use std::os::unix::process::ExitStatusExt;
+
+    let repo = TempRepo::new();
+
+    // The alias's shell runs as a child of git, so $PPID is git itself
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_gg"))
+        .args(["-c", "alias.die=!kill -TERM $PPID", "die"])
+        .current_dir(&repo.path)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), None);
+    assert_eq!(output.status.signal(), Some(15));
+}
+
 #[test]
 fn passthrough_checkout_nonexistent_branch() {
     let repo = TempRepo::new();
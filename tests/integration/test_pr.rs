@@ -100,3 +100,47 @@ fn pr_no_remote_fails_gracefully() {
     assert_ne!(code, 0);
     assert!(!stderr.is_empty() || code != 0);
 }
+
+#[test]
+fn pr_base_flag_targets_branch() {
+    let repo = TempRepo::new();
+    repo.run_git(&["remote", "add", "origin", "git@github.com:user/repo.git"]);
+    repo.checkout_new_branch("feature");
+
+    let (code, stdout, _) = repo.gg(&["pr", "-p", "--base", "develop"]);
+
+    assert_eq!(code, 0);
+    assert!(stdout.contains("/compare/develop...feature?expand=1"), "Got: {}", stdout);
+}
+
+#[test]
+fn pr_title_and_body_prefill() {
+    let repo = TempRepo::new();
+    repo.run_git(&["remote", "add", "origin", "git@github.com:user/repo.git"]);
+    repo.checkout_new_branch("feature");
+
+    let (code, stdout, _) = repo.gg(&["pr", "-p", "--title", "My PR", "--body", "Some notes"]);
+
+    assert_eq!(code, 0);
+    assert!(stdout.contains("&title=My%20PR&body=Some%20notes"), "Got: {}", stdout);
+}
+
+#[test]
+fn pr_ignores_malformed_user_config() {
+    let repo = TempRepo::new();
+    repo.run_git(&["remote", "add", "origin", "git@github.com:user/repo.git"]);
+    let config_home = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir_all(config_home.path().join("gg")).unwrap();
+    std::fs::write(config_home.path().join("gg").join("config.toml"), "[pr\n").unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_gg"))
+        .args(["pr", "-p"])
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .current_dir(&repo.path)
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout.contains("github.com/user/repo"), "Got: {}", stdout);
+}
@@ -28,6 +28,32 @@ fn qc_alias_works() {
     assert_eq!(repo.commit_count(), initial_count + 1);
 }
 
+#[test]
+fn qc_quiet_hides_running_lines() {
+    let repo = TempRepo::new();
+    let initial_count = repo.commit_count();
+
+    repo.modify_file("README.md", "Modified quietly");
+
+    let (code, stdout, _) = repo.gg(&["--quiet", "qc", "Quiet commit"]);
+
+    assert_eq!(code, 0);
+    assert_eq!(repo.commit_count(), initial_count + 1);
+    assert!(!stdout.contains("Running:"));
+}
+
+#[test]
+fn qc_quiet_flag_accepted_after_subcommand() {
+    let repo = TempRepo::new();
+
+    repo.modify_file("README.md", "Modified quietly");
+
+    let (code, stdout, _) = repo.gg(&["qc", "-q", "Quiet commit"]);
+
+    assert_eq!(code, 0);
+    assert!(!stdout.contains("Running:"));
+}
+
 #[test]
 fn qc_all_flag_includes_untracked() {
     let repo = TempRepo::new();
@@ -109,3 +135,42 @@ fn qc_shows_running_commands() {
 
     assert!(stdout.contains("Running:") || stdout.contains("git"));
 }
+
+#[test]
+fn qc_type_and_scope_compose_conventional_subject() {
+    let repo = TempRepo::new();
+
+    repo.modify_file("README.md", "Modified");
+
+    let (code, _, _) = repo.gg(&["qc", "add login", "--type", "feat", "--scope", "auth"]);
+
+    assert_eq!(code, 0);
+    assert_eq!(repo.last_commit_message(), "feat(auth): add login");
+}
+
+#[test]
+fn qc_scope_requires_type() {
+    let repo = TempRepo::new();
+    let initial_count = repo.commit_count();
+
+    repo.modify_file("README.md", "Modified");
+
+    let (code, _, _) = repo.gg(&["qc", "add login", "--scope", "auth"]);
+
+    assert_ne!(code, 0);
+    assert_eq!(repo.commit_count(), initial_count);
+}
+
+#[test]
+fn qc_type_with_empty_message_fails() {
+    let repo = TempRepo::new();
+    let initial_count = repo.commit_count();
+
+    repo.modify_file("README.md", "Modified");
+
+    let (code, _, stderr) = repo.gg(&["qc", "", "--type", "fix"]);
+
+    assert_ne!(code, 0);
+    assert!(stderr.contains("message"));
+    assert_eq!(repo.commit_count(), initial_count);
+}
@@ -66,3 +66,20 @@ fn recent_default_count_is_10() {
 
     assert_eq!(code, 0);
 }
+
+#[test]
+fn recent_shows_age_and_ahead_behind() {
+    let repo = TempRepo::new();
+
+    repo.checkout_new_branch("feature");
+    repo.create_file("feature.txt", "content");
+    repo.commit("Feature commit");
+    repo.checkout("main");
+
+    let (code, stdout, _) = repo.gg(&["recent"]);
+
+    assert_eq!(code, 0);
+    assert!(stdout.contains("feature"), "Output: {}", stdout);
+    assert!(stdout.contains("ago") || stdout.contains("just now"), "Output: {}", stdout);
+    assert!(stdout.contains("↑1"), "Output: {}", stdout);
+}
@@ -79,3 +79,77 @@ fn standup_empty_result() {
     // Should not crash, even with edge case
     assert!(code == 0 || code == 1);
 }
+
+#[test]
+fn standup_groups_commits_under_date_header() {
+    let repo = TempRepo::new();
+
+    repo.create_file("standup.txt", "content");
+    repo.commit("Standup grouped commit");
+
+    let (code, stdout, _) = repo.gg(&["standup", "-a", "-d", "1"]);
+    let today = repo.git_output(&["log", "-1", "--date=short-local", "--pretty=format:%ad"]);
+
+    assert_eq!(code, 0);
+    assert!(stdout.contains(&today), "Expected date header {}, got: {}", today, stdout);
+    assert!(stdout.contains("  "));
+    assert!(stdout.contains("Standup grouped commit"));
+}
+
+#[test]
+fn standup_all_repos_prints_section_per_repo() {
+    let parent = tempfile::TempDir::new().unwrap();
+    for name in ["alpha", "beta"] {
+        let dir = parent.path().join(name);
+        std::fs::create_dir(&dir).unwrap();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git").args(args).current_dir(&dir).output().unwrap();
+        };
+        git(&["init"]);
+        git(&["config", "user.email", "test@example.com"]);
+        git(&["config", "user.name", "Test User"]);
+        std::fs::write(dir.join("file.txt"), name).unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", &format!("Work in {}", name)]);
+    }
+    std::fs::create_dir(parent.path().join("not-a-repo")).unwrap();
+
+    let repo = TempRepo::new();
+    let parent_arg = parent.path().to_string_lossy().to_string();
+    let (code, stdout, _) = repo.gg(&["standup", "-d", "1", "--all-repos", &parent_arg]);
+
+    assert_eq!(code, 0);
+    assert!(stdout.contains("alpha"));
+    assert!(stdout.contains("Work in alpha"));
+    assert!(stdout.contains("beta"));
+    assert!(stdout.contains("Work in beta"));
+    assert!(!stdout.contains("not-a-repo"));
+}
+
+#[test]
+fn standup_since_until_range() {
+    let repo = TempRepo::new();
+
+    repo.create_file("standup.txt", "content");
+    repo.commit("Standup range commit");
+
+    let (code, stdout, _) = repo.gg(&["standup", "-a", "--since", "2000-01-01", "--until", "2000-01-14"]);
+
+    assert_eq!(code, 0);
+    assert!(!stdout.contains("Standup range commit"));
+
+    let (code, stdout, _) = repo.gg(&["standup", "-a", "--since", "2000-01-01"]);
+
+    assert_eq!(code, 0);
+    assert!(stdout.contains("Standup range commit"));
+}
+
+#[test]
+fn standup_since_conflicts_with_days() {
+    let repo = TempRepo::new();
+
+    let (code, _, stderr) = repo.gg(&["standup", "--days", "3", "--since", "2024-01-01"]);
+
+    assert_ne!(code, 0);
+    assert!(stderr.contains("cannot be used with"));
+}
@@ -148,3 +148,202 @@ fn status_empty_repo() {
         code
     );
 }
+
+#[test]
+fn status_piped_output_has_no_color() {
+    let repo = TempRepo::new();
+    repo.create_file("new_file.txt", "content");
+
+    let (code, stdout, _) = repo.gg(&["status"]);
+
+    assert_eq!(code, 0);
+    assert!(!stdout.contains("\x1b["));
+}
+
+#[test]
+fn status_color_always_forces_color() {
+    let repo = TempRepo::new();
+    repo.create_file("new_file.txt", "content");
+
+    let (code, stdout, _) = repo.gg(&["--color", "always", "status"]);
+
+    assert_eq!(code, 0);
+    assert!(stdout.contains("\x1b["));
+}
+
+#[test]
+fn status_color_never_disables_color() {
+    let repo = TempRepo::new();
+    repo.create_file("new_file.txt", "content");
+
+    let (code, stdout, _) = repo.gg(&["status", "--color=never"]);
+
+    assert_eq!(code, 0);
+    assert!(!stdout.contains("\x1b["));
+}
+
+#[test]
+fn status_uses_theme_from_user_config() {
+    let repo = TempRepo::new();
+    repo.create_file("new_file.txt", "content");
+    let config_home = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir_all(config_home.path().join("gg")).unwrap();
+    std::fs::write(
+        config_home.path().join("gg").join("config.toml"),
+        "[theme]\nuntracked = \"magenta\"\n",
+    )
+    .unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_gg"))
+        .args(["--color", "always", "status"])
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .current_dir(&repo.path)
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout.contains("\x1b[35m"), "Got: {:?}", stdout);
+}
+
+#[test]
+fn status_ignores_malformed_user_config() {
+    let repo = TempRepo::new();
+    repo.create_file("new_file.txt", "content");
+    let config_home = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir_all(config_home.path().join("gg")).unwrap();
+    std::fs::write(config_home.path().join("gg").join("config.toml"), "[theme\n").unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_gg"))
+        .args(["status"])
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .current_dir(&repo.path)
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout.contains("new_file.txt"));
+}
+
+#[test]
+fn status_outside_repo_fails_cleanly() {
+    let dir = tempfile::TempDir::new().unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_gg"))
+        .args(["status"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr.starts_with("gg: "), "Got: {}", stderr);
+    assert!(!stderr.contains("panicked"));
+}
+
+#[test]
+fn status_shows_ahead_of_upstream() {
+    let repo = TempRepo::with_remote();
+    repo.create_file("ahead.txt", "content");
+    repo.commit("Ahead commit");
+
+    let (code, stdout, _) = repo.gg(&["status"]);
+
+    assert_eq!(code, 0);
+    assert!(stdout.contains("Ahead of 'origin/main' by 1 commit."), "Got: {}", stdout);
+}
+
+#[test]
+fn status_no_upstream_omits_tracking_line() {
+    let repo = TempRepo::new();
+
+    let (code, stdout, _) = repo.gg(&["status"]);
+
+    assert_eq!(code, 0);
+    assert!(!stdout.contains("origin/"));
+}
+
+#[test]
+fn status_shows_stash_count() {
+    let repo = TempRepo::new();
+    repo.modify_file("README.md", "Stashed change");
+    repo.run_git(&["stash"]);
+
+    let (code, stdout, _) = repo.gg(&["status"]);
+
+    assert_eq!(code, 0);
+    assert!(stdout.contains("Stashes: 1"), "Got: {}", stdout);
+}
+
+#[test]
+fn status_detached_head() {
+    let repo = TempRepo::new();
+    repo.run_git(&["checkout", "--detach"]);
+
+    let (code, stdout, _) = repo.gg(&["status"]);
+
+    assert_eq!(code, 0);
+    assert!(stdout.contains("HEAD detached at"), "Got: {}", stdout);
+}
+
+#[test]
+fn status_shows_renamed_files() {
+    let repo = TempRepo::new();
+    repo.create_file("old_name.txt", "some content that is long enough to match");
+    repo.commit("Add file");
+    repo.run_git(&["mv", "old_name.txt", "new_name.txt"]);
+
+    let (code, stdout, _) = repo.gg(&["status"]);
+
+    assert_eq!(code, 0);
+    assert!(stdout.contains("Renamed files:"), "Got: {}", stdout);
+    assert!(stdout.contains("old_name.txt -> new_name.txt"));
+    assert!(!stdout.contains("new file:"));
+}
+
+#[test]
+fn status_tags_lfs_tracked_files() {
+    let repo = TempRepo::new();
+    repo.create_file(".gitattributes", "*.bin filter=gg-lfs diff=gg-lfs merge=gg-lfs -text\n");
+    repo.commit("Track bins");
+    repo.create_file("asset.bin", "binary data");
+    repo.create_file("notes.txt", "text");
+
+    let (code, stdout, _) = repo.gg(&["status"]);
+
+    assert_eq!(code, 0);
+    assert!(stdout.contains("asset.bin (lfs)"), "Got: {}", stdout);
+    assert!(!stdout.contains("notes.txt (lfs)"));
+}
+
+#[test]
+fn status_tags_lfs_pointer_files() {
+    let repo = TempRepo::new();
+    repo.create_file(".gitattributes", "*.bin filter=gg-lfs diff=gg-lfs merge=gg-lfs -text\n");
+    repo.commit("Track bins");
+    repo.create_file(
+        "asset.bin",
+        "version https://git-lfs.github.com/spec/v1\noid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\nsize 12345\n",
+    );
+
+    let (code, stdout, _) = repo.gg(&["status"]);
+
+    assert_eq!(code, 0);
+    assert!(stdout.contains("asset.bin (lfs pointer)"), "Got: {}", stdout);
+}
+
+#[test]
+fn status_warns_about_lfs_files_committed_as_content() {
+    let repo = TempRepo::new();
+    repo.create_file(".gitattributes", "*.bin filter=gg-lfs diff=gg-lfs merge=gg-lfs -text\n");
+    repo.create_file("asset.bin", "binary data");
+    repo.commit("Add asset without importing");
+    repo.modify_file("asset.bin", "new binary data");
+
+    let (code, stdout, _) = repo.gg(&["status"]);
+
+    assert_eq!(code, 0);
+    assert!(stdout.contains("committed as real content"), "Got: {}", stdout);
+    assert!(stdout.contains("gg lfs import"));
+}
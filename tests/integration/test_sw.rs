@@ -83,3 +83,48 @@ fn sw_with_branch_history() {
     assert!(code == 0 || code == 1);
     let _ = current_before; // Silence unused warning
 }
+
+#[test]
+fn sw_pattern_switches_to_single_match() {
+    let repo = TempRepo::new();
+
+    repo.checkout_new_branch("feature-login");
+    repo.checkout("main");
+    repo.checkout_new_branch("bugfix");
+    repo.checkout("main");
+
+    let (code, _, _) = repo.gg(&["sw", "login"]);
+
+    assert_eq!(code, 0);
+    assert_eq!(repo.current_branch(), "feature-login");
+}
+
+#[test]
+fn sw_pattern_ambiguous_lists_matches() {
+    let repo = TempRepo::new();
+
+    repo.checkout_new_branch("feature-login");
+    repo.checkout("main");
+    repo.checkout_new_branch("feature-signup");
+    repo.checkout("main");
+
+    let (code, _, stderr) = repo.gg(&["sw", "feature"]);
+
+    assert_ne!(code, 0);
+    assert!(stderr.contains("feature-login"));
+    assert!(stderr.contains("feature-signup"));
+    assert_eq!(repo.current_branch(), "main");
+}
+
+#[test]
+fn sw_pattern_no_match_fails() {
+    let repo = TempRepo::new();
+
+    repo.checkout_new_branch("feature-login");
+    repo.checkout("main");
+
+    let (code, _, stderr) = repo.gg(&["sw", "nothing-like-this"]);
+
+    assert_ne!(code, 0);
+    assert!(stderr.contains("No recent branch matches"));
+}
@@ -107,3 +107,69 @@ fn sync_with_uncommitted_changes() {
         stdout
     );
 }
+
+#[test]
+fn sync_detached_head_shows_error() {
+    let repo = TempRepo::with_remote();
+    repo.run_git(&["checkout", "--detach"]);
+
+    let (code, _, stderr) = repo.gg(&["sync"]);
+
+    assert_ne!(code, 0);
+    assert!(stderr.contains("detached"), "Expected detached HEAD error, got: {}", stderr);
+}
+
+#[test]
+fn sync_on_develop_default_branch() {
+    let repo = TempRepo::new();
+    repo.run_git(&["branch", "-m", "develop"]);
+
+    let (_, stdout, _) = repo.gg(&["sync"]);
+
+    // develop is the only default candidate, so sync treats it as main
+    assert!(stdout.contains("pull --rebase"), "Expected pull --rebase, got: {}", stdout);
+    assert!(!stdout.contains("checkout"));
+}
+
+#[test]
+fn sync_feature_branch_rebases_onto_remote_main() {
+    let repo = TempRepo::with_remote();
+    repo.checkout_new_branch("feature");
+
+    let (code, stdout, _) = repo.gg(&["sync", "--no-stash"]);
+
+    assert_eq!(code, 0, "Expected sync to succeed, got: {}", stdout);
+    assert!(stdout.contains("git fetch origin"));
+    assert!(stdout.contains("git rebase origin/main"));
+    assert!(!stdout.contains("git checkout"));
+}
+
+#[test]
+fn sync_follows_main_tracking_upstream() {
+    let repo = TempRepo::with_remote();
+    let url = repo.git_output(&["remote", "get-url", "origin"]);
+    repo.run_git(&["remote", "add", "upstream", &url]);
+    repo.run_git(&["fetch", "upstream"]);
+    repo.run_git(&["branch", "-u", "upstream/main", "main"]);
+    repo.checkout_new_branch("feature");
+
+    let (code, stdout, _) = repo.gg(&["sync", "--no-stash"]);
+
+    assert_eq!(code, 0, "Expected sync to succeed, got: {}", stdout);
+    assert!(stdout.contains("git fetch upstream"));
+    assert!(stdout.contains("git rebase upstream/main"));
+}
+
+#[test]
+fn sync_onto_flag_overrides_target() {
+    let repo = TempRepo::with_remote();
+    let url = repo.git_output(&["remote", "get-url", "origin"]);
+    repo.run_git(&["remote", "add", "upstream", &url]);
+    repo.checkout_new_branch("feature");
+
+    let (code, stdout, _) = repo.gg(&["sync", "--no-stash", "--onto", "upstream/main"]);
+
+    assert_eq!(code, 0, "Expected sync to succeed, got: {}", stdout);
+    assert!(stdout.contains("git fetch upstream"));
+    assert!(stdout.contains("git rebase upstream/main"));
+}
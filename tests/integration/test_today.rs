@@ -77,3 +77,27 @@ fn today_uses_oneline_format() {
         }
     }
 }
+
+#[test]
+fn today_lists_commit_subjects() {
+    let repo = TempRepo::new();
+
+    repo.create_file("file1.txt", "1");
+    repo.commit("Listed today commit");
+
+    let (code, stdout, _) = repo.gg(&["today", "--all"]);
+
+    assert_eq!(code, 0);
+    assert!(stdout.contains("Listed today commit"));
+}
+
+#[test]
+fn today_no_commits_message() {
+    let repo = TempRepo::new();
+    repo.run_git(&["config", "user.email", "someone-else@example.com"]);
+
+    let (code, stdout, _) = repo.gg(&["today"]);
+
+    assert_eq!(code, 0);
+    assert!(stdout.contains("No commits today"));
+}
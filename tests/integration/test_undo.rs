@@ -125,3 +125,117 @@ fn undo_no_commits_fails() {
     // Should fail because there's nothing to undo
     assert_ne!(output.status.code().unwrap(), 0);
 }
+
+#[test]
+fn undo_creates_backup_ref() {
+    let repo = TempRepo::new();
+    repo.create_file("file.txt", "content");
+    repo.commit("To be undone");
+    let head_before = repo.git_output(&["rev-parse", "HEAD"]);
+
+    let (code, stdout, _) = repo.gg(&["undo", "--hard"]);
+
+    assert_eq!(code, 0);
+    assert!(stdout.contains("gg undo --restore"));
+    let backups = repo.git_output(&["for-each-ref", "--format=%(objectname)", "refs/gg/undo-backup/"]);
+    assert_eq!(backups, head_before);
+}
+
+#[test]
+fn undo_list_shows_backups() {
+    let repo = TempRepo::new();
+    repo.create_file("file.txt", "content");
+    repo.commit("Listed commit");
+    repo.gg(&["undo"]);
+
+    let (code, stdout, _) = repo.gg(&["undo", "--list"]);
+
+    assert_eq!(code, 0);
+    assert!(stdout.contains("Listed commit"), "Got: {}", stdout);
+}
+
+#[test]
+fn undo_list_empty() {
+    let repo = TempRepo::new();
+
+    let (code, stdout, _) = repo.gg(&["undo", "--list"]);
+
+    assert_eq!(code, 0);
+    assert!(stdout.contains("No undo backups"));
+}
+
+#[test]
+fn undo_restore_brings_back_hard_reset_commit() {
+    let repo = TempRepo::new();
+    repo.create_file("file.txt", "content");
+    repo.commit("Restore me");
+    let count = repo.commit_count();
+
+    repo.gg(&["undo", "--hard"]);
+    assert_eq!(repo.commit_count(), count - 1);
+
+    let backup = repo.git_output(&["for-each-ref", "--format=%(refname)", "refs/gg/undo-backup/"]);
+    let id = backup.trim_start_matches("refs/gg/undo-backup/");
+    let (code, _, _) = repo.gg(&["undo", "--restore", id]);
+
+    assert_eq!(code, 0);
+    assert_eq!(repo.commit_count(), count);
+    assert_eq!(repo.last_commit_message(), "Restore me");
+    assert!(repo.path.join("file.txt").exists());
+}
+
+#[test]
+fn undo_restore_unknown_backup_fails() {
+    let repo = TempRepo::new();
+
+    let (code, _, stderr) = repo.gg(&["undo", "--restore", "12345"]);
+
+    assert_ne!(code, 0);
+    assert!(stderr.contains("No undo backup"));
+}
+
+/// Create a merge commit of a "topic" branch into the current branch
+fn make_merge(repo: &TempRepo) {
+    let main_branch = repo.current_branch();
+    repo.checkout_new_branch("topic");
+    repo.create_file("topic.txt", "topic");
+    repo.commit("Topic work");
+    repo.checkout(&main_branch);
+    repo.create_file("main.txt", "main");
+    repo.commit("Main work");
+    repo.run_git(&["merge", "--no-ff", "-m", "Merge topic", "topic"]);
+}
+
+#[test]
+fn undo_warns_when_range_includes_merge() {
+    let repo = TempRepo::new();
+    make_merge(&repo);
+
+    let (code, _, stderr) = repo.gg(&["undo"]);
+
+    assert_eq!(code, 0);
+    assert!(stderr.contains("merge commit"), "Got: {}", stderr);
+}
+
+#[test]
+fn undo_merge_resets_to_first_parent() {
+    let repo = TempRepo::new();
+    make_merge(&repo);
+
+    let (code, stdout, _) = repo.gg(&["undo", "--undo-merge"]);
+
+    assert_eq!(code, 0);
+    assert!(stdout.contains("git revert -m 1"));
+    assert_eq!(repo.last_commit_message(), "Main work");
+    assert!(!repo.path.join("topic.txt").exists());
+}
+
+#[test]
+fn undo_merge_requires_merge_at_head() {
+    let repo = TempRepo::new();
+
+    let (code, _, stderr) = repo.gg(&["undo", "--undo-merge"]);
+
+    assert_ne!(code, 0);
+    assert!(stderr.contains("not a merge commit"));
+}